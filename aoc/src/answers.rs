@@ -0,0 +1,164 @@
+//! The verified answers for our inputs, kept in `answers.toml`.
+//! Only the little TOML needed is understood: a `[dayNN]` table per
+//! day holding `part1` and `part2`, each an integer, a string, or an
+//! image as an array of rows with `#` for lit pixels.
+
+use common::Answer;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Answers(BTreeMap<(u8, u8), Answer>);
+
+impl Answers {
+
+    pub fn parse(text: &str) -> Result<Answers, String> {
+        let mut answers = BTreeMap::new();
+        let mut day = None;
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            let error = |e: &str| format!("line {}: {}", i + 1, e);
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(table) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let d = table.strip_prefix("day")
+                    .and_then(|d| d.parse().ok())
+                    .ok_or_else(|| error("expected a table named like [day01]"))?;
+                day = Some(d);
+                continue;
+            }
+
+            let (key, value) = line.split_once('=').ok_or_else(|| error("expected part = answer"))?;
+            let part = match key.trim() {
+                "part1" => 1,
+                "part2" => 2,
+                k => return Err(error(&format!("unknown key {:?}", k))),
+            };
+            let day = day.ok_or_else(|| error("answer given before any [dayNN] table"))?;
+            let answer = parse_value(value.trim()).map_err(|e| error(&e))?;
+
+            answers.insert((day, part), answer);
+        }
+
+        Ok(Answers(answers))
+    }
+
+    /// Load the answers at `path`, or none if there is no such file.
+    pub fn load(path: &Path) -> Result<Answers, String> {
+        if !path.exists() {
+            return Ok(Answers::default());
+        }
+
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("unable to read {}: {}", path.display(), e))?;
+
+        Answers::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        std::fs::write(path, self.to_toml())
+            .map_err(|e| format!("unable to write {}: {}", path.display(), e))
+    }
+
+    pub fn get(&self, day: u8, part: u8) -> Option<&Answer> {
+        self.0.get(&(day, part))
+    }
+
+    pub fn record(&mut self, day: u8, part: u8, answer: Answer) {
+        self.0.insert((day, part), answer);
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut text = String::from("# Verified answers for our inputs.\n\
+            # Checked by `aoc check`, and written by `aoc check --record`.\n");
+
+        let mut last_day = None;
+        for (&(day, part), answer) in &self.0 {
+            if last_day != Some(day) {
+                text += &format!("\n[day{:02}]\n", day);
+                last_day = Some(day);
+            }
+            // JSON strings and arrays of them are also valid TOML.
+            text += &format!("part{} = {}\n", part, answer.to_json());
+        }
+
+        text
+    }
+}
+
+/// Parses an integer, a string or an array of strings.
+fn parse_value(value: &str) -> Result<Answer, String> {
+    if let Some(rows) = value.strip_prefix('[') {
+        let mut rows = rows.trim_start();
+        let mut image = Vec::new();
+        while !rows.starts_with(']') {
+            let (row, rest) = parse_string(rows)?;
+            image.push(row.chars().map(|c| c == '#').collect());
+
+            rows = rest.trim_start();
+            rows = rows.strip_prefix(',').unwrap_or(rows).trim_start();
+            if rows.is_empty() {
+                return Err("unterminated array".to_string());
+            }
+        }
+        expect_end(&rows[1..])?;
+
+        Ok(Answer::Image(image))
+    } else if value.starts_with('"') {
+        let (s, rest) = parse_string(value)?;
+        expect_end(rest)?;
+
+        Ok(Answer::Text(s))
+    } else {
+        value.parse()
+            .map(Answer::Number)
+            .map_err(|_| format!("invalid answer {:?}", value))
+    }
+}
+
+/// Parses the basic string at the start of `text`,
+/// returning it and what follows.
+fn parse_string(text: &str) -> Result<(String, &str), String> {
+    let mut chars = text.strip_prefix('"').ok_or("expected a string")?.char_indices();
+    let mut s = String::new();
+
+    while let Some((_, c)) = chars.next() {
+        match c {
+            '"' => return Ok((s, chars.as_str())),
+            '\\' => match chars.next().map(|(_, c)| c) {
+                Some('"') => s.push('"'),
+                Some('\\') => s.push('\\'),
+                Some('n') => s.push('\n'),
+                Some('t') => s.push('\t'),
+                Some('u') => {
+                    let rest = chars.as_str();
+                    let c = rest.get(..4)
+                        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                        .and_then(std::char::from_u32)
+                        .ok_or("invalid \\u escape")?;
+                    s.push(c);
+                    chars = rest[4..].char_indices();
+                },
+                _ => return Err("invalid escape in string".to_string()),
+            },
+            c => s.push(c),
+        }
+    }
+
+    Err("unterminated string".to_string())
+}
+
+/// Allows only a comment after a value.
+fn expect_end(rest: &str) -> Result<(), String> {
+    let rest = rest.trim();
+    if rest.is_empty() || rest.starts_with('#') {
+        Ok(())
+    } else {
+        Err(format!("unexpected {:?} after the answer", rest))
+    }
+}
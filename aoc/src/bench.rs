@@ -0,0 +1,228 @@
+//! Timing solutions properly: parsing and each part are timed apart,
+//! warmed up, then run many times and summarized, and can be compared
+//! against a baseline saved by an earlier run.
+
+use common::{InputError, Params, Puzzle};
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::hint::black_box;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// How long to spend sampling a stage when the number of runs isn't given.
+const TIME_BUDGET: Duration = Duration::from_millis(500);
+const MIN_RUNS: usize = 5;
+const MAX_RUNS: usize = 10_000;
+
+/// A timed piece of a solution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    Parse,
+    Part(u8),
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stage::Parse => write!(f, "parse"),
+            Stage::Part(p) => write!(f, "part{}", p),
+        }
+    }
+}
+
+impl Stage {
+    fn from_name(name: &str) -> Option<Stage> {
+        match name {
+            "parse" => Some(Stage::Parse),
+            "part1" => Some(Stage::Part(1)),
+            "part2" => Some(Stage::Part(2)),
+            _ => None,
+        }
+    }
+}
+
+/// A summary of a stage's run times.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+
+    /// Summarizes at least one sample.
+    pub fn new(samples: &[Duration]) -> Stats {
+        assert!(!samples.is_empty(), "no samples to summarize");
+
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let n = sorted.len();
+        let median = if n % 2 == 1 {
+            sorted[n / 2]
+        } else {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2
+        };
+
+        let secs: Vec<f64> = sorted.iter().map(Duration::as_secs_f64).collect();
+        let mean = secs.iter().sum::<f64>() / n as f64;
+        let variance = secs.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / n as f64;
+
+        Stats {
+            runs: n,
+            min: sorted[0],
+            median,
+            mean: Duration::from_secs_f64(mean),
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+}
+
+/// How many times to run each stage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Settings {
+    /// Timed runs, or `None` for as many as fit in the time budget.
+    pub runs: Option<usize>,
+    /// Untimed runs first, to warm caches and the like.
+    pub warmup: usize,
+}
+
+/// Times `f` as the settings say.
+fn measure<T, F: FnMut() -> T>(settings: Settings, mut f: F) -> Stats {
+    let time = |f: &mut F| {
+        let start = Instant::now();
+        black_box(f());
+        start.elapsed()
+    };
+
+    let mut first = None;
+    for _ in 0..settings.warmup {
+        first = Some(time(&mut f));
+    }
+
+    let runs = settings.runs.unwrap_or_else(|| {
+        let once = first.unwrap_or_else(|| time(&mut f)).max(Duration::from_nanos(1));
+        ((TIME_BUDGET.as_secs_f64() / once.as_secs_f64()) as usize).clamp(MIN_RUNS, MAX_RUNS)
+    });
+    let samples: Vec<Duration> = (0..runs).map(|_| time(&mut f)).collect();
+
+    Stats::new(&samples)
+}
+
+/// Times parsing `input`, then each of `parts`.
+/// Fails without timing anything if the input can't be parsed.
+pub fn bench(
+    puzzle: &Puzzle,
+    input: &str,
+    params: &Params,
+    parts: &[u8],
+    settings: Settings,
+) -> Result<Vec<(Stage, Stats)>, InputError> {
+    let parsed = puzzle.parse(input, params)?;
+    let mut stages = vec![(Stage::Parse, measure(settings, || puzzle.parse(input, params)))];
+
+    for &part in parts {
+        stages.push((Stage::Part(part), measure(settings, || puzzle.solve(&parsed, part))));
+    }
+
+    Ok(stages)
+}
+
+/// The median and standard deviation of every stage timed in an
+/// earlier run, kept as lines of `DAY STAGE MEDIAN_NS STDDEV_NS`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Baseline(BTreeMap<(u8, Stage), (Duration, Duration)>);
+
+impl Baseline {
+
+    pub fn parse(text: &str) -> Result<Baseline, String> {
+        let mut stages = BTreeMap::new();
+
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let stage = match fields[..] {
+                [day, stage, median, stddev] => day.parse().ok()
+                    .zip(Stage::from_name(stage))
+                    .zip(median.parse().ok().zip(stddev.parse().ok())),
+                _ => None,
+            };
+            let ((day, stage), (median, stddev)) = stage
+                .ok_or_else(|| format!("line {}: expected DAY STAGE MEDIAN_NS STDDEV_NS", i + 1))?;
+
+            stages.insert((day, stage), (Duration::from_nanos(median), Duration::from_nanos(stddev)));
+        }
+
+        Ok(Baseline(stages))
+    }
+
+    pub fn load(path: &Path) -> Result<Baseline, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("unable to read baseline {}: {}", path.display(), e))?;
+
+        Baseline::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let error = |e: std::io::Error| format!("unable to write baseline {}: {}", path.display(), e);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(error)?;
+        }
+
+        std::fs::write(path, self.to_text()).map_err(error)
+    }
+
+    pub fn record(&mut self, day: u8, stage: Stage, stats: &Stats) {
+        self.0.insert((day, stage), (stats.median, stats.stddev));
+    }
+
+    /// The median and standard deviation recorded for a stage.
+    pub fn get(&self, day: u8, stage: Stage) -> Option<(Duration, Duration)> {
+        self.0.get(&(day, stage)).copied()
+    }
+
+    pub fn to_text(&self) -> String {
+        self.0.iter()
+            .map(|((day, stage), (median, stddev))| {
+                format!("{} {} {} {}\n", day, stage, median.as_nanos(), stddev.as_nanos())
+            })
+            .collect()
+    }
+}
+
+/// How a stage's median changed from the baseline's.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Change {
+    /// Slower by more than the threshold, as a fraction.
+    Regressed(f64),
+    /// Faster by more than the threshold.
+    Improved(f64),
+    /// Within the threshold either way.
+    Unchanged(f64),
+}
+
+/// Compares `stats` against a baseline's `median` and `stddev`. A change
+/// only counts once it is beyond `threshold`, a fraction of the baseline,
+/// and beyond twice the standard deviation of the two runs combined, as
+/// anything less may be noise.
+pub fn compare(stats: &Stats, median: Duration, stddev: Duration, threshold: f64) -> Change {
+    let now = stats.median.as_secs_f64();
+    let then = median.as_secs_f64().max(1e-9);
+    let ratio = now / then - 1.0;
+    let spread = stats.stddev.as_secs_f64().hypot(stddev.as_secs_f64());
+    let noisy = (now - then).abs() <= 2.0 * spread;
+
+    if ratio > threshold && !noisy {
+        Change::Regressed(ratio)
+    } else if ratio < -threshold && !noisy {
+        Change::Improved(ratio)
+    } else {
+        Change::Unchanged(ratio)
+    }
+}
@@ -0,0 +1,21 @@
+//! Every solved day, in order.
+
+use common::Puzzle;
+
+pub fn all() -> Vec<Puzzle> {
+    vec![
+        day01::puzzle(),
+        day02::puzzle(),
+        day03::puzzle(),
+        day04::puzzle(),
+        day05::puzzle(),
+        day06::puzzle(),
+        day07::puzzle(),
+        day08::puzzle(),
+        day09::puzzle(),
+        day10::puzzle(),
+        day11::puzzle(),
+        day12::puzzle(),
+        day13::puzzle(),
+    ]
+}
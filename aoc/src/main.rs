@@ -0,0 +1,785 @@
+//! Runs any or all of the days' solutions from one place.
+//! See `aoc --help` for the options.
+
+mod answers;
+mod bench;
+mod days;
+mod scaffold;
+
+use answers::Answers;
+use bench::{Baseline, Change, Settings, Stage, Stats};
+use common::*;
+
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "\
+Usage: aoc <COMMAND> [OPTIONS] [DAYS]...
+       aoc new <DAY>
+
+Runs the solutions for DAYS, each a day number like `7`, an inclusive range
+like `1..13`, or `all`, which is the default.
+
+Commands:
+  run    Print every answer and how long it took, then the total time
+  check  Compare the answers with those recorded in `answers.toml`,
+         failing if any differ
+  bench  Time parsing and each part many times, printing statistics,
+         and optionally compare them against a saved baseline
+  new    Create a crate for DAY from a template, with empty input and
+         example files, and add it to the workspace and this runner
+
+Options:
+  -p, --part <1|2>          Only run the given part
+      --record              With check, record the answers instead of comparing
+      --answers <PATH>      With check, use the answers file at PATH
+      --runs <N>            With bench, time each stage N times, rather than
+                            as often as fits in half a second
+      --warmup <N>          With bench, run each stage N times untimed first (3)
+      --save-baseline <NAME>
+                            With bench, save the timings as a baseline
+      --baseline <NAME>     With bench, compare against a saved baseline,
+                            failing if anything got slower
+      --threshold <PCT>     With --baseline, how much slower counts (10)
+  -h, --help                Show this message";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Command {
+    Run,
+    Check,
+    Bench,
+    New,
+}
+
+/// Which days were asked for.
+#[derive(Debug, Clone, PartialEq)]
+enum DaySpec {
+    Day(u8),
+    Range(RangeInclusive<u8>),
+    All,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Options {
+    command: Command,
+    days: Vec<DaySpec>,
+    parts: Vec<u8>,
+    record: bool,
+    answers: Option<PathBuf>,
+    settings: Settings,
+    save_baseline: Option<String>,
+    baseline: Option<String>,
+    threshold: f64,
+}
+
+impl Options {
+
+    /// Parse the arguments following the binary's name.
+    /// `Ok(None)` means help was asked for.
+    fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Option<Options>, String> {
+        let mut command = None;
+        let mut days = Vec::new();
+        let mut parts = vec![1, 2];
+        let mut record = false;
+        let mut answers = None;
+        let mut settings = Settings { runs: None, warmup: 3 };
+        let mut save_baseline = None;
+        let mut baseline = None;
+        let mut threshold = None;
+        let mut bench_options = false;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| args.next().ok_or_else(|| format!("{} needs a value", name));
+
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "-p" | "--part" => {
+                    parts = match value(&arg)?.as_str() {
+                        "1" => vec![1],
+                        "2" => vec![2],
+                        p => return Err(format!("there is no part {:?}", p)),
+                    };
+                },
+                "--record" => record = true,
+                "--answers" => answers = Some(PathBuf::from(value(&arg)?)),
+                "--runs" | "--warmup" => {
+                    let n = value(&arg)?;
+                    let n = n.parse().map_err(|_| format!("invalid count {:?}", n))?;
+                    match arg.as_str() {
+                        "--runs" if n == 0 => return Err("--runs must be at least 1".to_string()),
+                        "--runs" => settings.runs = Some(n),
+                        _ => settings.warmup = n,
+                    }
+                    bench_options = true;
+                },
+                "--save-baseline" => {
+                    save_baseline = Some(baseline_name(value(&arg)?)?);
+                    bench_options = true;
+                },
+                "--baseline" => {
+                    baseline = Some(baseline_name(value(&arg)?)?);
+                    bench_options = true;
+                },
+                "--threshold" => {
+                    let pct = value(&arg)?;
+                    threshold = Some(pct.parse::<f64>().ok()
+                        .filter(|p| *p >= 0.0)
+                        .ok_or_else(|| format!("invalid threshold {:?}", pct))? / 100.0);
+                },
+                a if a.starts_with('-') => return Err(format!("unknown option {}", a)),
+                "run" if command.is_none() => command = Some(Command::Run),
+                "check" if command.is_none() => command = Some(Command::Check),
+                "bench" if command.is_none() => command = Some(Command::Bench),
+                "new" if command.is_none() => command = Some(Command::New),
+                _ if command.is_none() => return Err(format!("unknown command {:?}", arg)),
+                _ => days.push(parse_days(&arg)?),
+            }
+        }
+
+        let command = command.ok_or("no command given")?;
+        if command != Command::Check && (record || answers.is_some()) {
+            return Err("--record and --answers only apply to check".to_string());
+        }
+        if command != Command::Bench && bench_options {
+            return Err("--runs, --warmup and baselines only apply to bench".to_string());
+        }
+        if command == Command::New && (!matches!(days[..], [DaySpec::Day(_)]) || parts.len() != 2) {
+            return Err("new takes a single day and no options".to_string());
+        }
+        if threshold.is_some() && baseline.is_none() {
+            return Err("--threshold only applies with --baseline".to_string());
+        }
+        if days.is_empty() {
+            days.push(DaySpec::All);
+        }
+
+        Ok(Some(Options {
+            command,
+            days,
+            parts,
+            record,
+            answers,
+            settings,
+            save_baseline,
+            baseline,
+            threshold: threshold.unwrap_or(0.1),
+        }))
+    }
+}
+
+/// Baselines are files, so keep their names plain.
+fn baseline_name(name: String) -> Result<String, String> {
+    let plain = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if plain {
+        Ok(name)
+    } else {
+        Err(format!("invalid baseline name {:?}: use letters, digits, - and _", name))
+    }
+}
+
+/// The directory holding the whole workspace.
+fn workspace_root() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap()
+}
+
+/// Where the baseline called `name` is kept, in the workspace's `target`.
+fn baseline_path(name: &str) -> PathBuf {
+    workspace_root()
+        .join("target").join("aoc-baselines").join(format!("{}.txt", name))
+}
+
+/// Parses `N`, `A..B`, `A..=B` or `all`.
+fn parse_days(arg: &str) -> Result<DaySpec, String> {
+    let day = |d: &str| d.parse::<u8>().map_err(|_| format!("invalid day {:?}", arg));
+
+    if arg == "all" {
+        Ok(DaySpec::All)
+    } else if let Some((a, b)) = arg.split_once("..") {
+        let b = b.strip_prefix('=').unwrap_or(b);
+        Ok(DaySpec::Range(day(a)?..=day(b)?))
+    } else {
+        Ok(DaySpec::Day(day(arg)?))
+    }
+}
+
+/// Picks the puzzles asked for, in order.
+/// A day asked for by itself must have been solved.
+fn select(specs: &[DaySpec], puzzles: &[Puzzle]) -> Result<Vec<Puzzle>, String> {
+    for spec in specs {
+        if let DaySpec::Day(d) = spec {
+            if !puzzles.iter().any(|p| p.day() == *d) {
+                return Err(format!("day {} isn't solved", d));
+            }
+        }
+    }
+
+    let selected: Vec<Puzzle> = puzzles.iter()
+        .filter(|p| specs.iter().any(|spec| match spec {
+            DaySpec::Day(d) => p.day() == *d,
+            DaySpec::Range(r) => r.contains(&p.day()),
+            DaySpec::All => true,
+        }))
+        .copied()
+        .collect();
+
+    if selected.is_empty() {
+        return Err("no solved days were selected".to_string());
+    }
+
+    Ok(selected)
+}
+
+/// One part's answer.
+#[derive(Debug, Clone, PartialEq)]
+struct Row {
+    day: u8,
+    part: u8,
+    answer: Answer,
+    time: Duration,
+}
+
+/// Everything a run produced.
+#[derive(Debug, Clone, PartialEq)]
+struct Report {
+    rows: Vec<Row>,
+    parsing: Duration,
+    /// The day and part of each part skipped as not solved yet.
+    unsolved: Vec<(u8, u8)>,
+}
+
+impl Report {
+    fn total(&self) -> Duration {
+        self.parsing + self.rows.iter().map(|r| r.time).sum::<Duration>()
+    }
+}
+
+/// Reads a day's input and parameters.
+fn load_input(puzzle: &Puzzle) -> Result<(String, Params), String> {
+    let input = get_input(puzzle.dir()).map_err(|e| format!("day {}: {}", puzzle.day(), e))?;
+    let params = Params::load(puzzle.dir()).map_err(|e| format!("day {}: {}", puzzle.day(), e))?;
+
+    Ok((input, params))
+}
+
+/// Solves the selected parts of every puzzle, setting aside those not
+/// solved yet.
+fn run(opts: &Options, puzzles: &[Puzzle]) -> Result<Report, String> {
+    let mut report = Report {
+        rows: Vec::new(),
+        parsing: Duration::default(),
+        unsolved: Vec::new(),
+    };
+
+    for puzzle in puzzles {
+        let (input, params) = load_input(puzzle)?;
+
+        let start = Instant::now();
+        let parsed = puzzle.parse(&input, &params).map_err(|e| format!("day {}: {}", puzzle.day(), e))?;
+        report.parsing += start.elapsed();
+
+        for &part in &opts.parts {
+            let start = Instant::now();
+            let answer = puzzle.solve(&parsed, part);
+            let time = start.elapsed();
+            if answer == Answer::Unsolved {
+                report.unsolved.push((puzzle.day(), part));
+                continue;
+            }
+
+            report.rows.push(Row { day: puzzle.day(), part, answer, time });
+        }
+    }
+
+    Ok(report)
+}
+
+/// Prints the answers as a table, then any images below it.
+fn write_report(out: &mut dyn Write, report: &Report) -> io::Result<()> {
+    let shown = |answer: &Answer| if answer.is_multiline() {
+        "(see below)".to_string()
+    } else {
+        answer.to_string()
+    };
+    let width = report.rows.iter()
+        .map(|r| shown(&r.answer).chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Answer".len());
+
+    writeln!(out, "Day  Part  {:<width$}  {:>12}", "Answer", "Time", width = width)?;
+    for row in &report.rows {
+        writeln!(out, "{:>3}  {:>4}  {:<width$}  {:>12}",
+            row.day, row.part, shown(&row.answer), format_time(row.time), width = width)?;
+    }
+    writeln!(out, "Parsing   {:<width$}  {:>12}", "", format_time(report.parsing), width = width)?;
+    writeln!(out, "Total     {:<width$}  {:>12}", "", format_time(report.total()), width = width)?;
+
+    for row in report.rows.iter().filter(|r| r.answer.is_multiline()) {
+        writeln!(out, "\nDay {} part {}:\n{}", row.day, row.part, row.answer)?;
+    }
+
+    write_unsolved(out, report)
+}
+
+/// Lists the parts the report skipped as not solved yet, if any.
+fn write_unsolved(out: &mut dyn Write, report: &Report) -> io::Result<()> {
+    if !report.unsolved.is_empty() {
+        let parts: Vec<String> = report.unsolved.iter()
+            .map(|(day, part)| format!("day {} part {}", day, part))
+            .collect();
+        writeln!(out, "\nNot solved yet: {}", parts.join(", "))?;
+    }
+
+    Ok(())
+}
+
+/// How a fresh answer compares with the recorded one.
+#[derive(Debug, Clone, PartialEq)]
+enum Verdict {
+    Correct,
+    Wrong(Answer),
+    Unrecorded,
+}
+
+fn verdict(row: &Row, answers: &Answers) -> Verdict {
+    match answers.get(row.day, row.part) {
+        Some(expected) if *expected == row.answer => Verdict::Correct,
+        Some(expected) => Verdict::Wrong(expected.clone()),
+        None => Verdict::Unrecorded,
+    }
+}
+
+/// Prints how every answer compares with the recorded ones, then a diff
+/// of each that is wrong. Returns whether none were.
+fn write_check(out: &mut dyn Write, report: &Report, answers: &Answers) -> io::Result<bool> {
+    let verdicts: Vec<Verdict> = report.rows.iter().map(|r| verdict(r, answers)).collect();
+
+    writeln!(out, "Day  Part  Result")?;
+    for (row, verdict) in report.rows.iter().zip(&verdicts) {
+        let result = match verdict {
+            Verdict::Correct => "ok",
+            Verdict::Wrong(_) => "WRONG",
+            Verdict::Unrecorded => "not recorded",
+        };
+        writeln!(out, "{:>3}  {:>4}  {}", row.day, row.part, result)?;
+    }
+
+    for (row, verdict) in report.rows.iter().zip(&verdicts) {
+        if let Verdict::Wrong(expected) = verdict {
+            writeln!(out, "\nDay {} part {}:", row.day, row.part)?;
+            for line in expected.to_string().lines() {
+                writeln!(out, "- {}", line)?;
+            }
+            for line in row.answer.to_string().lines() {
+                writeln!(out, "+ {}", line)?;
+            }
+        }
+    }
+
+    let wrong = verdicts.iter().filter(|v| matches!(v, Verdict::Wrong(_))).count();
+    let unrecorded = verdicts.iter().filter(|v| **v == Verdict::Unrecorded).count();
+    writeln!(out, "\n{} correct, {} wrong, {} not recorded",
+        verdicts.len() - wrong - unrecorded, wrong, unrecorded)?;
+    write_unsolved(out, report)?;
+
+    Ok(wrong == 0)
+}
+
+/// The workspace's `answers.toml`.
+fn answers_path() -> PathBuf {
+    workspace_root().join("answers.toml")
+}
+
+/// Checks the answers against those in the answers file, or records
+/// them there instead. Returns whether every recorded answer matched.
+fn check(opts: &Options, report: &Report, out: &mut dyn Write) -> Result<bool, String> {
+    let path = opts.answers.clone().unwrap_or_else(answers_path);
+    let path = path.as_path();
+    let mut answers = Answers::load(path)?;
+
+    if opts.record {
+        for row in &report.rows {
+            answers.record(row.day, row.part, row.answer.clone());
+        }
+        answers.save(path)?;
+        writeln!(out, "Recorded {} answers to {}", report.rows.len(), path.display())
+            .map_err(|e| e.to_string())?;
+
+        return Ok(true);
+    }
+
+    write_check(out, report, &answers).map_err(|e| e.to_string())
+}
+
+/// Times every puzzle, saving or comparing against baselines as asked.
+/// Returns whether nothing regressed.
+fn bench_all(opts: &Options, puzzles: &[Puzzle], out: &mut dyn Write) -> Result<bool, String> {
+    let baseline = opts.baseline.as_deref()
+        .map(|name| Baseline::load(&baseline_path(name)))
+        .transpose()?;
+
+    let mut rows = Vec::new();
+    for puzzle in puzzles {
+        let (input, params) = load_input(puzzle)?;
+        let stages = bench::bench(puzzle, &input, &params, &opts.parts, opts.settings)
+            .map_err(|e| format!("day {}: {}", puzzle.day(), e))?;
+        for (stage, stats) in stages {
+            rows.push((puzzle.day(), stage, stats));
+        }
+    }
+
+    let io_err = |e: io::Error| e.to_string();
+    let ok = write_bench(out, &rows, baseline.as_ref(), opts.threshold).map_err(io_err)?;
+
+    if let Some(name) = &opts.save_baseline {
+        let mut saved = Baseline::default();
+        for (day, stage, stats) in &rows {
+            saved.record(*day, *stage, stats);
+        }
+
+        let path = baseline_path(name);
+        saved.save(&path)?;
+        writeln!(out, "Saved baseline {:?} to {}", name, path.display()).map_err(io_err)?;
+    }
+
+    Ok(ok)
+}
+
+/// Prints the statistics of every stage, and how each changed from the
+/// baseline if there is one. Returns whether nothing regressed.
+fn write_bench(
+    out: &mut dyn Write,
+    rows: &[(u8, Stage, Stats)],
+    baseline: Option<&Baseline>,
+    threshold: f64,
+) -> io::Result<bool> {
+    write!(out, "Day  Stage  {:>12}  {:>12}  {:>12}  {:>12}  {:>6}", "Min", "Median", "Mean", "Stddev", "Runs")?;
+    writeln!(out, "{}", if baseline.is_some() { "  Change" } else { "" })?;
+
+    let mut regressions = 0;
+    for (day, stage, stats) in rows {
+        write!(out, "{:>3}  {:<5}  {:>12}  {:>12}  {:>12}  {:>12}  {:>6}", day, stage.to_string(),
+            format_time(stats.min), format_time(stats.median), format_time(stats.mean),
+            format_time(stats.stddev), stats.runs)?;
+
+        let change = match baseline.map(|b| b.get(*day, *stage)) {
+            None => String::new(),
+            Some(None) => "  new".to_string(),
+            Some(Some((median, stddev))) => match bench::compare(stats, median, stddev, threshold) {
+                Change::Regressed(r) => {
+                    regressions += 1;
+                    format!("  {:+.1}% REGRESSED", r * 100.0)
+                },
+                Change::Improved(r) => format!("  {:+.1}% improved", r * 100.0),
+                Change::Unchanged(r) => format!("  {:+.1}%", r * 100.0),
+            },
+        };
+        writeln!(out, "{}", change)?;
+    }
+
+    if baseline.is_some() {
+        writeln!(out, "\n{} of {} stages regressed by more than {}%",
+            regressions, rows.len(), threshold * 100.0)?;
+    }
+
+    Ok(regressions == 0)
+}
+
+/// Shows a time in whichever unit keeps it readable.
+fn format_time(time: Duration) -> String {
+    match time.as_nanos() {
+        ns if ns < 1_000 => format!("{}ns", ns),
+        ns if ns < 1_000_000 => format!("{:.2}us", ns as f64 / 1e3),
+        ns => format!("{:.3}ms", ns as f64 / 1e6),
+    }
+}
+
+/// Creates and registers a new day, saying what was written.
+fn new_day(days: &[DaySpec], out: &mut dyn Write) -> Result<bool, String> {
+    let day = match days {
+        [DaySpec::Day(day)] => *day,
+        _ => return Err("new takes a single day".to_string()),
+    };
+
+    let root = workspace_root();
+    let files = scaffold::new_day(root, day)?;
+    let write = |out: &mut dyn Write| -> io::Result<()> {
+        writeln!(out, "Created day {}:", day)?;
+        for file in &files {
+            writeln!(out, "  {}", file.strip_prefix(root).unwrap_or(file).display())?;
+        }
+        Ok(())
+    };
+    write(out).map_err(|e| e.to_string())?;
+
+    Ok(true)
+}
+
+fn main() {
+    let opts = match Options::parse(std::env::args().skip(1)) {
+        Ok(Some(opts)) => opts,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        },
+        Err(e) => {
+            eprintln!("aoc: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        },
+    };
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let puzzles = || select(&opts.days, &days::all());
+    let result = match opts.command {
+        Command::Run => puzzles()
+            .and_then(|puzzles| run(&opts, &puzzles))
+            .and_then(|report| write_report(&mut out, &report).map_err(|e| e.to_string()))
+            .map(|_| true),
+        Command::Check => puzzles()
+            .and_then(|puzzles| run(&opts, &puzzles))
+            .and_then(|report| check(&opts, &report, &mut out)),
+        Command::Bench => puzzles().and_then(|puzzles| bench_all(&opts, &puzzles, &mut out)),
+        Command::New => new_day(&opts.days, &mut out),
+    };
+
+    match result {
+        Ok(true) => {},
+        Ok(false) => process::exit(1),
+        Err(e) => {
+            eprintln!("aoc: {}", e);
+            process::exit(1);
+        },
+    }
+}
+
+#[cfg(test)]
+fn args(line: &str) -> Options {
+    Options::parse(line.split_whitespace().map(String::from)).unwrap().unwrap()
+}
+
+#[test]
+fn parses_options() {
+    let opts = args("run 7 1..3 10..=11");
+    assert_eq!(Command::Run, opts.command);
+    assert_eq!(vec![DaySpec::Day(7), DaySpec::Range(1..=3), DaySpec::Range(10..=11)], opts.days);
+    assert_eq!(vec![1, 2], opts.parts);
+
+    let opts = args("run all --part 2");
+    assert_eq!((vec![DaySpec::All], vec![2]), (opts.days, opts.parts));
+    assert_eq!(vec![DaySpec::All], args("run").days);
+
+    let opts = args("check 3 --record --answers a.toml");
+    assert_eq!((Command::Check, true), (opts.command, opts.record));
+    assert_eq!(Some(PathBuf::from("a.toml")), opts.answers);
+
+    let opts = args("bench 7 --runs 20 --warmup 0 --save-baseline new --baseline old_1 --threshold 5");
+    assert_eq!(Settings { runs: Some(20), warmup: 0 }, opts.settings);
+    assert_eq!((Some("new".to_string()), Some("old_1".to_string())), (opts.save_baseline, opts.baseline));
+    assert!((opts.threshold - 0.05).abs() < 1e-9);
+    assert_eq!((Command::New, vec![DaySpec::Day(14)]), (args("new 14").command, args("new 14").days));
+
+    assert_eq!(Ok(None), Options::parse(vec!["-h".to_string()]));
+    for bad in ["", "walk 1", "run x", "run 1..", "run -p 3", "run --bogus", "run --record",
+            "check --runs 3", "bench --runs 0", "bench --baseline ../x", "bench --threshold 5",
+            "new", "new 1..3", "new 3 4", "new 3 -p 1"].iter() {
+        assert!(Options::parse(bad.split_whitespace().map(String::from)).is_err(), "{:?}", bad);
+    }
+}
+
+#[test]
+fn runs_days() {
+    let puzzles = days::all();
+    let days = |line: &str| select(&args(line).days, &puzzles)
+        .map(|p| p.iter().map(Puzzle::day).collect::<Vec<_>>());
+    let all: Vec<u8> = puzzles.iter().map(Puzzle::day).collect();
+    assert!(all.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(Ok(all.clone()), days("run"));
+    assert_eq!(Ok(vec![2, 3, 4, 8]), days("run 8 2..4 3"));
+    assert_eq!(Ok(all.iter().copied().filter(|&d| d >= 12).collect()), days("run 12..25"));
+    assert!(days("run 25").is_err());
+    assert!(days("run 20..25").is_err());
+
+    let opts = args("run 1 8");
+    let report = run(&opts, &select(&opts.days, &puzzles).unwrap()).unwrap();
+    let answers: Vec<_> = report.rows.iter().map(|r| (r.day, r.part, r.answer.clone())).collect();
+    assert_eq!((1, 1, Answer::Number(3348909)), answers[0]);
+    assert_eq!((1, 2, Answer::Number(5020494)), answers[1]);
+    assert_eq!((8, 1, Answer::Number(2760)), answers[2]);
+    assert_eq!((8, 2, Answer::Text("AGUEB".to_string())), answers[3]);
+
+    let mut out = Vec::new();
+    write_report(&mut out, &report).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.starts_with("Day  Part  Answer      "));
+    assert!(out.contains("  1     2  5020494"));
+    assert!(out.contains("  8     2  AGUEB"));
+
+    // Images that aren't letters are drawn below the table.
+    let image = Row { day: 8, part: 2, answer: Answer::Image(vec![vec![true, false]]), time: Duration::default() };
+    let mut out = Vec::new();
+    write_report(&mut out, &Report { rows: vec![image], parsing: Duration::default(), unsolved: Vec::new() }).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("  8     2  (see below)"));
+    assert!(out.ends_with("\nDay 8 part 2:\n█ \n"));
+
+    let time = |ns| format_time(Duration::from_nanos(ns));
+    assert_eq!(("999ns", "1.50us", "12.346ms"), (&time(999)[..], &time(1500)[..], &time(12_345_678)[..]));
+}
+
+#[test]
+fn skips_unsolved_parts() {
+    struct Half;
+    impl Solution for Half {
+        type Input = ();
+
+        fn parse(_input: &str) {}
+
+        fn part_one(_input: &()) -> Answer {
+            Answer::Number(1)
+        }
+
+        fn part_two(_input: &()) -> Answer {
+            Answer::Unsolved
+        }
+    }
+
+    // Any day's directory will do for its input.
+    let puzzle = Puzzle::new::<Half>(14, days::all()[0].dir());
+    let report = run(&args("run"), &[puzzle]).unwrap();
+    assert_eq!(vec![(14, 1)], report.rows.iter().map(|r| (r.day, r.part)).collect::<Vec<_>>());
+    assert_eq!(vec![(14, 2)], report.unsolved);
+
+    let mut out = Vec::new();
+    write_report(&mut out, &report).unwrap();
+    assert!(String::from_utf8(out).unwrap().ends_with("\nNot solved yet: day 14 part 2\n"));
+    let mut out = Vec::new();
+    assert!(write_check(&mut out, &report, &Answers::default()).unwrap());
+    assert!(String::from_utf8(out).unwrap().ends_with("1 not recorded\n\nNot solved yet: day 14 part 2\n"));
+}
+
+#[test]
+fn checks_answers() {
+    let text = "# Comment\n\n[day01]\npart1 = 12\npart2 = \"A\\\"B\\u0043\" # note\n[day08]\npart2 = [\"#.\", \".#\"]\n";
+    let answers = Answers::parse(text).unwrap();
+    assert_eq!(Some(&Answer::Number(12)), answers.get(1, 1));
+    assert_eq!(Some(&Answer::Text("A\"BC".into())), answers.get(1, 2));
+    assert_eq!(Some(&Answer::Image(vec![vec![true, false], vec![false, true]])), answers.get(8, 2));
+    assert_eq!(None, answers.get(8, 1));
+    assert_eq!(Ok(answers.clone()), Answers::parse(&answers.to_toml()));
+
+    for bad in ["part1 = 1", "[day01]\npart3 = 1", "[dayx]", "[day01]\npart1 = \"a", "[day01]\npart1 = [\"#\"", "[day01]\npart1 = 1 2"].iter() {
+        assert!(Answers::parse(bad).is_err(), "{:?}", bad);
+    }
+
+    let row = |day, part, answer| Row { day, part, answer, time: Duration::default() };
+    let report = Report {
+        rows: vec![
+            row(1, 1, Answer::Number(12)),
+            row(1, 2, Answer::Text("ABD".into())),
+            row(2, 1, Answer::Number(5)),
+        ],
+        parsing: Duration::default(),
+        unsolved: Vec::new(),
+    };
+    let mut out = Vec::new();
+    assert!(!write_check(&mut out, &report, &answers).unwrap());
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("  1     1  ok\n  1     2  WRONG\n  2     1  not recorded\n"));
+    assert!(out.contains("Day 1 part 2:\n- A\"BC\n+ ABD\n"));
+    assert!(out.ends_with("1 correct, 1 wrong, 1 not recorded\n"));
+
+    // Every recorded answer is still right.
+    let opts = args("check");
+    let report = run(&opts, &select(&opts.days, &days::all()).unwrap()).unwrap();
+    let path = answers_path();
+    assert!(write_check(&mut Vec::new(), &report, &Answers::load(&path).unwrap()).unwrap());
+}
+
+#[test]
+fn bench_statistics() {
+    let ms = Duration::from_millis;
+    let stats = Stats::new(&[ms(4), ms(1), ms(3), ms(2)]);
+    assert_eq!((4, ms(1)), (stats.runs, stats.min));
+    assert_eq!((Duration::from_micros(2500), Duration::from_micros(2500)), (stats.median, stats.mean));
+    assert_eq!(1118, stats.stddev.as_micros());
+    assert_eq!(ms(2), Stats::new(&[ms(3), ms(2), ms(1)]).median);
+
+    // Changes count once they are beyond the threshold and the noise.
+    let steady = Stats::new(&[ms(10), ms(10)]);
+    let noisy = Stats { stddev: ms(5), ..steady };
+    let none = Duration::ZERO;
+    assert!(matches!(bench::compare(&steady, ms(8), none, 0.1), Change::Regressed(r) if (r - 0.25).abs() < 1e-9));
+    assert!(matches!(bench::compare(&steady, ms(12), none, 0.1), Change::Improved(_)));
+    assert!(matches!(bench::compare(&steady, ms(8), none, 0.5), Change::Unchanged(_)));
+    assert!(matches!(bench::compare(&noisy, ms(8), none, 0.1), Change::Unchanged(_)));
+    // A noisy baseline counts too.
+    assert!(matches!(bench::compare(&steady, ms(8), ms(5), 0.1), Change::Unchanged(_)));
+    assert!(matches!(bench::compare(&steady, ms(8), Duration::from_micros(500), 0.1), Change::Regressed(_)));
+
+    let mut baseline = Baseline::default();
+    baseline.record(3, Stage::Part(2), &steady);
+    baseline.record(3, Stage::Parse, &noisy);
+    assert_eq!(Ok(baseline.clone()), Baseline::parse(&baseline.to_text()));
+    assert!(baseline.to_text().starts_with("3 parse 10000000 5000000\n3 part2 "));
+    assert!(Baseline::parse("3 part3 1 1").is_err());
+
+    // Timing a real day, compared against its own timings.
+    let day = days::all()[0];
+    let settings = Settings { runs: Some(3), warmup: 1 };
+    let (input, params) = load_input(&day).unwrap();
+    let stages = bench::bench(&day, &input, &params, &[2], settings).unwrap();
+    assert_eq!(vec![Stage::Parse, Stage::Part(2)], stages.iter().map(|s| s.0).collect::<Vec<_>>());
+    assert!(stages.iter().all(|(_, stats)| stats.runs == 3));
+
+    let rows: Vec<_> = stages.iter().map(|&(stage, stats)| (1, stage, stats)).collect();
+    let mut slower = Baseline::default();
+    slower.record(1, Stage::Parse, &Stats { median: stages[0].1.median * 100, ..stages[0].1 });
+    let mut out = Vec::new();
+    assert!(write_bench(&mut out, &rows, Some(&slower), 0.1).unwrap());
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("% improved\n"));
+    assert!(out.contains("  new\n"));
+}
+
+#[test]
+fn scaffolds_days() {
+    let root = common::testing::ScratchDir::new("scaffold");
+    for file in ["Cargo.toml", "aoc/Cargo.toml", "aoc/src/days.rs"].iter() {
+        root.write(file, &std::fs::read_to_string(workspace_root().join(file)).unwrap());
+    }
+    let read = |file: &str| std::fs::read_to_string(root.join(file)).unwrap();
+
+    // Starts the days after the last one there is.
+    let last = days::all().last().unwrap().day();
+    let (old, new, newer) = (format!("day{:02}", last), format!("day{:02}", last + 1), format!("day{:02}", last + 2));
+
+    let files = scaffold::new_day(&root, last + 1).unwrap();
+    assert_eq!(10, files.len());
+    assert!(files[..7].iter().all(|f| f.starts_with(root.join(&new))), "the day's files come first");
+    assert!(read("Cargo.toml").contains(&format!("    \"{}\",\n    \"{}\",\n    \"intcode\"\n]", old, new)));
+    assert!(read("aoc/Cargo.toml").contains(&format!("{0} = {{ path = \"../{0}\" }}\n{1} = {{ path = \"../{1}\" }}\n\n", old, new)));
+    assert!(read("aoc/src/days.rs").contains(&format!("{}::puzzle(),\n        {}::puzzle(),\n    ]", old, new)));
+    assert!(read(&format!("{}/src/lib.rs", new)).contains(&format!("impl Solution for Day{:02} {{", last + 1)));
+    assert!(read(&format!("{}/src/lib.rs", new)).contains("Answer::Unsolved"));
+    assert!(read(&format!("{}/src/main.rs", new)).contains(&format!("part_selector::<Day{:02}>(", last + 1)));
+    assert_eq!("", read(&format!("{}/input.txt", new)));
+
+    // Days are kept in order, and are never made twice.
+    std::fs::remove_dir_all(root.join(&new)).unwrap();
+    let before = read("aoc/src/days.rs");
+    assert!(scaffold::new_day(&root, last + 1).unwrap_err().contains("already"));
+    assert!(!root.join(&new).exists());
+    assert_eq!(before, read("aoc/src/days.rs"));
+    assert!(scaffold::new_day(&root, last).unwrap_err().ends_with(&format!("{} is already a member", old)));
+    std::fs::create_dir(root.join(&old)).unwrap();
+    assert!(scaffold::new_day(&root, last).unwrap_err().ends_with(&format!("{} already exists", old)));
+    assert!(scaffold::new_day(&root, 26).is_err());
+
+    scaffold::new_day(&root, last + 2).unwrap();
+    assert!(read("aoc/src/days.rs").contains(&format!("{}::puzzle(),\n        {}::puzzle(),\n    ]", new, newer)));
+}
@@ -0,0 +1,177 @@
+//! Starting a new day: its crate is made from a template, with empty
+//! input and example files, and it is added to the workspace's members,
+//! the runner's dependencies and the runner's list of days.
+
+use std::path::{Path, PathBuf};
+
+const MANIFEST: &str = r#"[package]
+name = "{name}"
+version = "0.1.0"
+authors = ["braydenw <brayden.wright0x08@gmail.com>"]
+edition = "2018"
+
+[[test]]
+name = "examples"
+harness = false
+
+[dependencies]
+common = { path = "../common" }"#;
+
+const LIB: &str = r#"use common::*;
+
+pub struct {Type};
+
+/// Day {day}, for running alongside the other days.
+pub fn puzzle() -> Puzzle {
+    Puzzle::new::<{Type}>({day}, env!("CARGO_MANIFEST_DIR"))
+}
+
+impl Solution for {Type} {
+    type Input = String;
+
+    fn parse(input: &str) -> String {
+        input.to_string()
+    }
+
+    fn part_one(_input: &String) -> Answer {
+        Answer::Unsolved
+    }
+
+    fn part_two(_input: &String) -> Answer {
+        Answer::Unsolved
+    }
+}
+"#;
+
+const MAIN: &str = r#"use common::*;
+use {name}::{Type};
+
+/// Setup
+fn main() {
+    part_selector::<{Type}>(env!("CARGO_MANIFEST_DIR"));
+}
+"#;
+
+const EXAMPLES_TEST: &str = r#"/// Checks the answers to the puzzle's examples, kept in `examples/`.
+fn main() {
+    common::run_examples({name}::puzzle());
+}
+"#;
+
+const EXAMPLE_ANSWERS: &str = "\
+# The example's answers, once known, and any puzzle parameters it needs.
+# part1 =
+# part2 =
+";
+
+/// Creates day `day` in the workspace at `root` and registers it,
+/// returning the files written. Nothing is written if the day exists
+/// already, either as a directory or in any of the lists.
+pub fn new_day(root: &Path, day: u8) -> Result<Vec<PathBuf>, String> {
+    if !(1..=25).contains(&day) {
+        return Err(format!("there is no day {}", day));
+    }
+
+    let name = format!("day{:02}", day);
+    let dir = root.join(&name);
+    if dir.exists() {
+        return Err(format!("{} already exists", dir.display()));
+    }
+
+    let fill = |template: &str| template
+        .replace("{name}", &name)
+        .replace("{Type}", &format!("Day{:02}", day))
+        .replace("{day}", &day.to_string());
+
+    // The day's own files are written before it is registered anywhere,
+    // so a failure partway never leaves the workspace naming a crate
+    // that isn't there.
+    let mut files = vec![
+        (dir.join("Cargo.toml"), fill(MANIFEST)),
+        (dir.join("src").join("lib.rs"), fill(LIB)),
+        (dir.join("src").join("main.rs"), fill(MAIN)),
+        (dir.join("tests").join("examples.rs"), fill(EXAMPLES_TEST)),
+        (dir.join("input.txt"), String::new()),
+        (dir.join("examples").join("1.txt"), String::new()),
+        (dir.join("examples").join("1.toml"), EXAMPLE_ANSWERS.to_string()),
+    ];
+
+    let registries = [
+        (root.join("Cargo.toml"), add_member as fn(&str, &str) -> Result<String, String>),
+        (root.join("aoc").join("Cargo.toml"), add_dependency),
+        (root.join("aoc").join("src").join("days.rs"), add_puzzle),
+    ];
+    for (path, add) in registries.iter() {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("unable to read {}: {}", path.display(), e))?;
+        let text = add(&text, &name).map_err(|e| format!("{}: {}", path.display(), e))?;
+        files.push((path.clone(), text));
+    }
+
+    for (path, text) in &files {
+        let error = |e: std::io::Error| format!("unable to write {}: {}", path.display(), e);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(error)?;
+        }
+        std::fs::write(path, text).map_err(error)?;
+    }
+
+    Ok(files.into_iter().map(|(path, _)| path).collect())
+}
+
+/// Adds `name` to the workspace `members`, keeping them sorted.
+fn add_member(manifest: &str, name: &str) -> Result<String, String> {
+    let start = manifest.find("members = [").ok_or("no workspace members found")? + "members = [".len();
+    let end = start + manifest[start..].find(']').ok_or("unterminated members list")?;
+
+    let mut members: Vec<&str> = manifest[start..end].split(',')
+        .map(|m| m.trim().trim_matches('"'))
+        .filter(|m| !m.is_empty())
+        .collect();
+    if members.contains(&name) {
+        return Err(format!("{} is already a member", name));
+    }
+    members.push(name);
+    members.sort_unstable();
+
+    let members: Vec<String> = members.iter().map(|m| format!("    \"{}\"", m)).collect();
+    Ok(format!("{}\n{}\n{}", &manifest[..start], members.join(",\n"), &manifest[end..]))
+}
+
+/// Adds `name` as a path dependency, among the other days in order.
+fn add_dependency(manifest: &str, name: &str) -> Result<String, String> {
+    let line = format!("{} = {{ path = \"../{}\" }}", name, name);
+    insert_sorted(manifest, &line, |l| l.split('=').next().map(str::trim), name)
+}
+
+/// Adds `name`'s puzzle to the list of every day, in order.
+fn add_puzzle(days: &str, name: &str) -> Result<String, String> {
+    let line = format!("        {}::puzzle(),", name);
+    insert_sorted(days, &line, |l| l.trim().strip_suffix("::puzzle(),"), name)
+}
+
+/// Inserts `line` after the last line whose name, as found by `key`,
+/// comes before `name`. Only lines named like days or `common` count,
+/// and only up to the end of the first section holding any.
+fn insert_sorted<F>(text: &str, line: &str, key: F, name: &str) -> Result<String, String>
+where F: Fn(&str) -> Option<&str> {
+    let mut lines: Vec<&str> = text.split('\n').collect();
+    let mut at = None;
+
+    for (i, l) in lines.iter().enumerate() {
+        if at.is_some() && l.starts_with('[') {
+            break;
+        }
+
+        match key(l) {
+            Some(k) if k == name => return Err(format!("{} is already listed", name)),
+            Some(k) if (k == "common" || k.starts_with("day")) && k < name => at = Some(i + 1),
+            _ => {},
+        }
+    }
+
+    let at = at.ok_or("nowhere to list the day")?;
+    lines.insert(at, line);
+
+    Ok(lines.join("\n"))
+}
@@ -0,0 +1,229 @@
+//! Command-line handling shared by every day's binary.
+
+use std::error::Error;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use super::examples::Example;
+use super::input::{self, InputError, InputSource, Params};
+use super::solution::{Answer, Solution};
+
+const USAGE: &str = "\
+Usage: {name} [OPTIONS] [1|2]
+
+Solves the given part, or both, from the day's `input.txt`,
+or its `$AOC_INPUT_DIR/dayNN.txt` when that is set.
+
+Options:
+  -i, --input <PATH>  Read the input from PATH instead, or stdin if it is `-`
+  -e, --example <N>   Read the input from the day's `examples/N.txt`,
+                      with the parameters in `examples/N.toml`
+  -P, --param <K=V>   Set a puzzle parameter, overriding the day's `params.toml`
+                      and the example's
+  -q, --quiet         Only print the answers
+      --json          Print the answers and time as JSON
+  -r, --repeat <N>    Solve N times, reporting the average time
+  -h, --help          Show this message";
+
+/// The options given to a day's binary.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Args {
+    pub parts: Vec<u8>,
+    pub input: InputSource,
+    /// Parameters set on the command line.
+    pub params: Vec<(String, String)>,
+    pub quiet: bool,
+    pub json: bool,
+    pub repeat: usize,
+}
+
+impl Args {
+
+    /// Parse the arguments following the binary's name.
+    /// `Ok(None)` means help was asked for.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Option<Args>, String> {
+        let mut part = None;
+        let mut input = InputSource::Default;
+        let mut params = Vec::new();
+        let mut quiet = false;
+        let mut json = false;
+        let mut repeat = 1;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| args.next().ok_or_else(|| format!("{} needs a value", name));
+
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "-i" | "--input" => {
+                    input = match value(&arg)? {
+                        p if p == "-" => InputSource::Stdin,
+                        p => InputSource::Path(p.into()),
+                    };
+                },
+                "-e" | "--example" => {
+                    let n = value(&arg)?;
+                    input = InputSource::Example(n.parse().map_err(|_| format!("invalid example {:?}", n))?);
+                },
+                "-P" | "--param" => {
+                    let param = value(&arg)?;
+                    let (key, value) = param.split_once('=')
+                        .ok_or_else(|| format!("expected KEY=VALUE, not {:?}", param))?;
+                    params.push((key.trim().to_string(), value.trim().to_string()));
+                },
+                "-q" | "--quiet" => quiet = true,
+                "--json" => json = true,
+                "-r" | "--repeat" => {
+                    let n = value(&arg)?;
+                    repeat = n.parse().ok()
+                        .filter(|&n| n > 0)
+                        .ok_or_else(|| format!("invalid repeat count {:?}", n))?;
+                },
+                "1" | "2" if part.is_none() => part = Some(arg.parse().unwrap()),
+                a if a.starts_with('-') => return Err(format!("unknown option {}", a)),
+                _ => return Err(format!("unexpected argument {:?}", arg)),
+            }
+        }
+
+        if quiet && json {
+            return Err("--quiet and --json can't be used together".to_string());
+        }
+
+        Ok(Some(Args {
+            parts: part.map_or(vec![1, 2], |p| vec![p]),
+            input,
+            params,
+            quiet,
+            json,
+            repeat,
+        }))
+    }
+
+    /// Read the input from its source, where `dir` is the day's directory.
+    pub fn read_input(&self, dir: &str) -> Result<String, InputError> {
+        input::read_input(dir, &self.input)
+    }
+
+    /// The day's parameters: its own, then those of the example being
+    /// solved, if any, then any set on the command line, each in place
+    /// of the ones before.
+    pub fn params(&self, dir: &str) -> Result<Params, InputError> {
+        let mut params = Params::load(dir)?;
+        if let InputSource::Example(n) = self.input {
+            params.extend(Example::load(&Path::new(dir).join("examples"), n)?.params);
+        }
+        for (key, value) in &self.params {
+            params.set(key, value);
+        }
+
+        Ok(params)
+    }
+}
+
+/// The help text, for the binary called `name`.
+pub fn usage(name: &str) -> String {
+    USAGE.replace("{name}", name)
+}
+
+/// Solves the parts asked for `args.repeat` times, then prints the
+/// answers and the average time taken, parsing included.
+pub fn solve<S: Solution>(args: &Args, input: &str, params: &Params, out: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    let mut answers = Vec::new();
+    let start = Instant::now();
+    for _ in 0..args.repeat {
+        let parsed = S::parse_with(input, params)?;
+        answers = args.parts.iter()
+            .map(|&part| (part, if part == 1 { S::part_one(&parsed) } else { S::part_two(&parsed) }))
+            .collect();
+    }
+    let time = start.elapsed() / args.repeat as u32;
+
+    if args.json {
+        write_json(out, &answers, time, args.repeat)?;
+    } else {
+        for (part, answer) in &answers {
+            match (args.quiet, answer.is_multiline()) {
+                (true, _) => writeln!(out, "{}", answer)?,
+                (false, true) => writeln!(out, "[Part {}]\n{}", part, answer)?,
+                (false, false) => writeln!(out, "[Part {}] {}", part, answer)?,
+            }
+        }
+
+        if args.quiet {
+            return Ok(());
+        }
+
+        if args.repeat > 1 {
+            writeln!(out, "Time: {}ms ({}us) on average over {} runs",
+                time.as_millis(), time.as_micros(), args.repeat)?;
+        } else {
+            writeln!(out, "Time: {}ms ({}us)", time.as_millis(), time.as_micros())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_json(out: &mut dyn Write, answers: &[(u8, Answer)], time: Duration, runs: usize) -> io::Result<()> {
+    let parts: Vec<String> = answers.iter()
+        .map(|(part, answer)| format!("{{\"part\": {}, \"answer\": {}}}", part, answer.to_json()))
+        .collect();
+
+    writeln!(out, "{{\"parts\": [{}], \"time_us\": {}, \"runs\": {}}}",
+        parts.join(", "), time.as_micros(), runs)
+}
+
+#[cfg(test)]
+fn args(line: &str) -> Args {
+    Args::parse(line.split_whitespace().map(String::from)).unwrap().unwrap()
+}
+
+#[test]
+fn parses_args() {
+    let a = args("");
+    assert_eq!((vec![1, 2], InputSource::Default, 1), (a.parts, a.input, a.repeat));
+
+    let a = args("2 --input in.txt -q -r 5");
+    assert_eq!((vec![2], InputSource::Path("in.txt".into())), (a.parts, a.input));
+    assert_eq!((true, false, 5), (a.quiet, a.json, a.repeat));
+    assert_eq!(InputSource::Stdin, args("-i -").input);
+    assert_eq!(InputSource::Example(3), args("1 --example 3 --json").input);
+    assert_eq!(vec![("width".to_string(), "3".to_string())], args("-P width=3").params);
+
+    assert_eq!(Ok(None), Args::parse(vec!["--help".to_string()]));
+    for bad in ["3", "1 2", "--bogus", "-r 0", "-e x", "-i", "-q --json", "-P width"].iter() {
+        assert!(Args::parse(bad.split_whitespace().map(String::from)).is_err(), "{:?}", bad);
+    }
+}
+
+#[test]
+fn takes_example_params() {
+    let day = super::testing::ScratchDir::new("cli-params");
+    day.write("params.toml", "width = 25\nheight = 6");
+    day.write("examples/1.txt", "0222");
+    day.write("examples/1.toml", "width = 2\nheight = 2\npart2 = \".#\"");
+
+    let params = args("--example 1 -P height=1").params(day.as_str()).unwrap();
+    assert_eq!((Some(2), Some(1)), (params.get("width").unwrap(), params.get("height").unwrap()));
+    assert_eq!(None::<String>, params.get("part2").unwrap());
+    assert_eq!(Some(25), args("").params(day.as_str()).unwrap().get("width").unwrap());
+    assert!(matches!(args("--example 2").params(day.as_str()), Err(InputError::Read(..))));
+}
+
+#[test]
+fn prints_answers() {
+    use super::testing::Sum;
+
+    let output = |line: &str| {
+        let mut out = Vec::new();
+        solve::<Sum>(&args(line), "1, 0, 2", &Params::default(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+
+    assert!(output("").starts_with("[Part 1] 3\n[Part 2]\n█ █\nTime: "));
+    assert!(output("1 -r 3").contains("over 3 runs"));
+    assert_eq!("3\n█ █\n", output("-q"));
+    assert!(output("--json").starts_with(
+        "{\"parts\": [{\"part\": 1, \"answer\": 3}, {\"part\": 2, \"answer\": [\"#.#\"]}], \"time_us\": "));
+}
@@ -0,0 +1,202 @@
+//! Checking solutions against the puzzles' examples, kept as files
+//! rather than pasted into tests. Example `N` of a day is its
+//! `examples/N.txt`, with the expected answers beside it in `N.toml`:
+//!
+//! ```toml
+//! part1 = 8
+//! part2 = ".#/#."
+//! ```
+//!
+//! Any other keys there are the puzzle parameters for that example.
+//! An image answer is written as its rows of `#` and `.`, split by `/`.
+//!
+//! A day runs its examples from a test target with `harness = false`:
+//!
+//! ```ignore
+//! fn main() {
+//!     common::run_examples(day08::puzzle());
+//! }
+//! ```
+
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+use super::input::{InputError, Params};
+use super::solution::{Answer, Puzzle};
+
+/// One of a day's examples.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Example {
+    pub number: usize,
+    pub input: String,
+    pub params: Params,
+    /// The expected answer to each part, where known.
+    pub expected: [Option<String>; 2],
+}
+
+impl Example {
+
+    /// Reads example `number` from `dir`, the day's `examples` directory.
+    pub fn load(dir: &Path, number: usize) -> Result<Example, InputError> {
+        let path = dir.join(format!("{}.txt", number));
+        let input = std::fs::read_to_string(&path).map_err(|e| InputError::Read(path, e))?;
+        let path = dir.join(format!("{}.toml", number));
+        let mut params = if path.exists() {
+            Params::read(&path)?
+        } else {
+            Params::default()
+        };
+        let expected = [params.remove("part1"), params.remove("part2")];
+
+        Ok(Example { number, input, params, expected })
+    }
+
+    /// Solves `part` of this example, or describes how it went wrong.
+    /// `Ok(false)` means there was no answer to check against.
+    pub fn check(&self, puzzle: &Puzzle, part: u8) -> Result<bool, String> {
+        let expected = match &self.expected[part as usize - 1] {
+            Some(expected) => expected,
+            None => return Ok(false),
+        };
+
+        let answer = panic::catch_unwind(AssertUnwindSafe(|| {
+            puzzle.parse(&self.input, &self.params).map(|parsed| puzzle.solve(&parsed, part))
+        }));
+        match answer {
+            Ok(Ok(answer)) if shown(&answer) == *expected => Ok(true),
+            Ok(Ok(answer)) => Err(format!("expected {}, got {}", expected, shown(&answer))),
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(_) => Err("panicked".to_string()),
+        }
+    }
+}
+
+/// Every example in a day's directory `dir`, in order.
+pub fn find_examples(dir: &str) -> Result<Vec<Example>, InputError> {
+    let dir = Path::new(dir).join("examples");
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let entries = std::fs::read_dir(&dir).map_err(|e| InputError::Read(dir.clone(), e))?;
+    let mut numbers = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(|e| InputError::Read(dir.clone(), e))?.path();
+        if path.extension().is_some_and(|e| e == "txt") {
+            if let Some(n) = path.file_stem().and_then(|s| s.to_str()).and_then(|s| s.parse().ok()) {
+                numbers.push(n);
+            }
+        }
+    }
+    numbers.sort_unstable();
+
+    numbers.into_iter().map(|n| Example::load(&dir, n)).collect()
+}
+
+/// An answer as written in an example's expected answers.
+fn shown(answer: &Answer) -> String {
+    match answer {
+        Answer::Image(rows) => {
+            let rows: Vec<String> = rows.iter()
+                .map(|row| row.iter().map(|&lit| if lit { '#' } else { '.' }).collect())
+                .collect();
+            rows.join("/")
+        },
+        answer => answer.to_string(),
+    }
+}
+
+/// Checks every part of every example of `puzzle` with a known answer,
+/// reporting like `cargo test` does, and exits with failure if any were
+/// wrong. Takes a name filter and `--list`, `--skip` and `--ignored` from
+/// the command line, and ignores the test harness's other options.
+pub fn run_examples(puzzle: Puzzle) {
+    let mut filters = Vec::new();
+    let mut skips = Vec::new();
+    let mut list = false;
+    let mut ignored = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--list" => list = true,
+            "--ignored" => ignored = true,
+            "--skip" => skips.extend(args.next()),
+            "--test-threads" | "--format" | "--color" | "--logfile" | "-Z" => {
+                args.next();
+            },
+            a if a.starts_with('-') => {},
+            _ => filters.push(arg),
+        }
+    }
+
+    let examples = find_examples(puzzle.dir()).unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        std::process::exit(101);
+    });
+    let tests: Vec<(String, &Example, u8)> = examples.iter()
+        .flat_map(|example| (1..=2)
+            .filter(move |&part| example.expected[part as usize - 1].is_some())
+            .map(move |part| (format!("example {} part {}", example.number, part), example, part)))
+        .filter(|(name, _, _)| filters.is_empty() || filters.iter().any(|f| name.contains(f.as_str())))
+        .filter(|(name, _, _)| !skips.iter().any(|s| name.contains(s.as_str())))
+        .filter(|_| !ignored)
+        .collect();
+
+    if list {
+        for (name, _, _) in &tests {
+            println!("{}: test", name);
+        }
+        return;
+    }
+
+    println!("\nrunning {} tests", tests.len());
+    let mut failures = Vec::new();
+    for (name, example, part) in &tests {
+        match example.check(&puzzle, *part) {
+            Ok(_) => println!("test {} ... ok", name),
+            Err(e) => {
+                println!("test {} ... FAILED", name);
+                failures.push(format!("{}: {}", name, e));
+            },
+        }
+    }
+
+    if !failures.is_empty() {
+        println!("\nfailures:");
+        for failure in &failures {
+            println!("    {}", failure);
+        }
+    }
+    let result = if failures.is_empty() { "ok" } else { "FAILED" };
+    println!("\ntest result: {}. {} passed; {} failed\n", result, tests.len() - failures.len(), failures.len());
+
+    if !failures.is_empty() {
+        std::process::exit(101);
+    }
+}
+
+#[test]
+fn checks_examples() {
+    use super::testing::{ScratchDir, Sum};
+
+    let root = ScratchDir::new("examples");
+    root.write("examples/1.txt", "1, 2");
+    root.write("examples/1.toml", "part1 = 3\npart2 = \"##\"");
+    root.write("examples/2.txt", "1, 0");
+    root.write("examples/2.toml", "part1 = 5\nextra = 3");
+    root.write("examples/10.txt", "7");
+    root.write("examples/notes.txt", "not an example");
+
+    let examples = find_examples(root.as_str()).unwrap();
+    assert_eq!(vec![1, 2, 10], examples.iter().map(|e| e.number).collect::<Vec<_>>());
+    assert_eq!([Some("5".to_string()), None], examples[1].expected);
+    assert_eq!(Some(3), examples[1].params.get::<i64>("extra").unwrap());
+
+    let puzzle = Puzzle::new::<Sum>(1, "");
+    assert_eq!(Ok(true), examples[0].check(&puzzle, 1));
+    assert_eq!(Ok(true), examples[0].check(&puzzle, 2));
+    assert_eq!(Err("expected 5, got 4".to_string()), examples[1].check(&puzzle, 1));
+    assert_eq!(Ok(false), examples[1].check(&puzzle, 2));
+    assert_eq!(Ok(false), examples[2].check(&puzzle, 1));
+}
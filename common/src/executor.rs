@@ -0,0 +1,112 @@
+//! A minimal single-threaded executor, enough to drive async Intcode
+//! machines without pulling in a runtime.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+/// Wakes a parked thread.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Run a future to completion on the current thread.
+pub fn block_on<F: Future>(fut: F) -> F::Output {
+    let mut fut = Box::pin(fut);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+            return out;
+        }
+
+        thread::park();
+    }
+}
+
+/// Queues a task to be polled again when woken.
+struct TaskWaker {
+    id: usize,
+    queue: Arc<Mutex<VecDeque<usize>>>,
+    thread: Thread,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.queue.lock().unwrap().push_back(self.id);
+        self.thread.unpark();
+    }
+}
+
+type Task<'a> = Pin<Box<dyn Future<Output = ()> + 'a>>;
+
+/// Runs several futures on the current thread, polling each only when woken.
+#[derive(Default)]
+pub struct LocalExecutor<'a> {
+    tasks: Vec<Option<Task<'a>>>,
+    queue: Arc<Mutex<VecDeque<usize>>>,
+}
+
+impl<'a> LocalExecutor<'a> {
+
+    /// Create an executor with no tasks.
+    pub fn new() -> LocalExecutor<'a> {
+        LocalExecutor {
+            tasks: Vec::new(),
+            queue: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Add a task; it is first polled by the next `run`.
+    pub fn spawn<F: Future<Output = ()> + 'a>(&mut self, fut: F) {
+        self.queue.lock().unwrap().push_back(self.tasks.len());
+        self.tasks.push(Some(Box::pin(fut)));
+    }
+
+    /// Number of tasks that have not completed yet.
+    pub fn pending(&self) -> usize {
+        self.tasks.iter().filter(|t| t.is_some()).count()
+    }
+
+    /// Poll tasks until every one has completed, parking the thread
+    /// while waiting to be woken.
+    pub fn run(&mut self) {
+        while self.run_until_stalled() > 0 {
+            thread::park();
+        }
+    }
+
+    /// Poll woken tasks until none are left to poll, returning the number
+    /// of tasks still pending. Non-zero means they are all waiting on
+    /// something, which for purely local tasks is a deadlock.
+    pub fn run_until_stalled(&mut self) -> usize {
+        loop {
+            let next = self.queue.lock().unwrap().pop_front();
+            let id = match next {
+                Some(id) => id,
+                None => return self.pending(),
+            };
+
+            let waker = Waker::from(Arc::new(TaskWaker {
+                id,
+                queue: self.queue.clone(),
+                thread: thread::current(),
+            }));
+            let mut cx = Context::from_waker(&waker);
+
+            if let Some(task) = self.tasks[id].as_mut() {
+                if task.as_mut().poll(&mut cx).is_ready() {
+                    self.tasks[id] = None;
+                }
+            }
+        }
+    }
+}
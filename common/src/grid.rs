@@ -0,0 +1,615 @@
+//! Points, directions and grids, for the puzzles set on a plane.
+//! Y grows downwards, as text does, so `Direction::Up` is towards
+//! smaller y and row 0 of a parsed grid is its top row.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::Hash;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign};
+
+/// The integer types a `Point` can be made of.
+pub trait Coord:
+    Copy + Ord + Hash + fmt::Debug + fmt::Display
+    + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Neg<Output = Self> {
+
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn abs(self) -> Self;
+
+    /// Converts a row or column index.
+    fn from_index(i: usize) -> Self;
+
+    /// Converts to a row or column index, if not negative.
+    fn to_index(self) -> Option<usize>;
+}
+
+macro_rules! coord {
+    ($($t:ty),*) => {$(
+        impl Coord for $t {
+            const ZERO: $t = 0;
+            const ONE: $t = 1;
+
+            fn abs(self) -> $t {
+                <$t>::abs(self)
+            }
+
+            fn from_index(i: usize) -> $t {
+                i as $t
+            }
+
+            fn to_index(self) -> Option<usize> {
+                usize::try_from(self).ok()
+            }
+        }
+    )*};
+}
+
+coord!(i16, i32, i64, isize);
+
+/// A position on the plane.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Point<T = i64> {
+    pub x: T,
+    pub y: T,
+}
+
+/// A displacement between two points.
+pub type Vec2<T = i64> = Point<T>;
+
+impl<T: Coord> Point<T> {
+
+    pub fn new(x: T, y: T) -> Point<T> {
+        Point { x, y }
+    }
+
+    pub fn origin() -> Point<T> {
+        Point::new(T::ZERO, T::ZERO)
+    }
+
+    /// The taxicab distance to `other`.
+    pub fn manhattan(self, other: Point<T>) -> T {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    /// The point one step away in `dir`.
+    pub fn step(self, dir: Direction) -> Point<T> {
+        self + dir.offset()
+    }
+
+    /// The four points sharing an edge with this one, clockwise from up.
+    pub fn neighbors4(self) -> impl Iterator<Item = Point<T>> {
+        Direction::ALL.iter().map(move |&dir| self.step(dir))
+    }
+
+    /// The eight points around this one, clockwise from up.
+    pub fn neighbors8(self) -> impl Iterator<Item = Point<T>> {
+        let (o, i) = (T::ZERO, T::ONE);
+        let offsets = [(o, -i), (i, -i), (i, o), (i, i), (o, i), (-i, i), (-i, o), (-i, -i)];
+
+        (0..offsets.len()).map(move |n| self + Point::new(offsets[n].0, offsets[n].1))
+    }
+}
+
+impl<T: Coord> Add for Point<T> {
+    type Output = Point<T>;
+
+    fn add(self, other: Point<T>) -> Point<T> {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<T: Coord> Sub for Point<T> {
+    type Output = Point<T>;
+
+    fn sub(self, other: Point<T>) -> Point<T> {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<T: Coord> AddAssign for Point<T> {
+    fn add_assign(&mut self, other: Point<T>) {
+        *self = *self + other;
+    }
+}
+
+impl<T: Coord> SubAssign for Point<T> {
+    fn sub_assign(&mut self, other: Point<T>) {
+        *self = *self - other;
+    }
+}
+
+/// Scales a displacement.
+impl<T: Coord> Mul<T> for Point<T> {
+    type Output = Point<T>;
+
+    fn mul(self, n: T) -> Point<T> {
+        Point::new(self.x * n, self.y * n)
+    }
+}
+
+impl<T: Coord> Neg for Point<T> {
+    type Output = Point<T>;
+
+    fn neg(self) -> Point<T> {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+impl<T> From<(T, T)> for Point<T> {
+    fn from((x, y): (T, T)) -> Point<T> {
+        Point { x, y }
+    }
+}
+
+impl<T> From<Point<T>> for (T, T) {
+    fn from(p: Point<T>) -> (T, T) {
+        (p.x, p.y)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+/// One of the four ways along the grid.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+
+    /// Every direction, clockwise from up.
+    pub const ALL: [Direction; 4] = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+
+    pub fn turn_left(self) -> Direction {
+        Direction::ALL[(self as usize + 3) % 4]
+    }
+
+    pub fn turn_right(self) -> Direction {
+        Direction::ALL[(self as usize + 1) % 4]
+    }
+
+    pub fn reverse(self) -> Direction {
+        Direction::ALL[(self as usize + 2) % 4]
+    }
+
+    /// One step in this direction.
+    pub fn offset<T: Coord>(self) -> Vec2<T> {
+        let (o, i) = (T::ZERO, T::ONE);
+        match self {
+            Direction::Up => Point::new(o, -i),
+            Direction::Right => Point::new(i, o),
+            Direction::Down => Point::new(o, i),
+            Direction::Left => Point::new(-i, o),
+        }
+    }
+}
+
+/// Reads `U`, `R`, `D` and `L`, or `^`, `>`, `v` and `<`.
+impl TryFrom<char> for Direction {
+    type Error = char;
+
+    fn try_from(c: char) -> Result<Direction, char> {
+        match c {
+            'U' | '^' => Ok(Direction::Up),
+            'R' | '>' => Ok(Direction::Right),
+            'D' | 'v' => Ok(Direction::Down),
+            'L' | '<' => Ok(Direction::Left),
+            c => Err(c),
+        }
+    }
+}
+
+/// The smallest rectangle holding some points, edges included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds<T = i64> {
+    pub min: Point<T>,
+    pub max: Point<T>,
+}
+
+impl<T: Coord> Bounds<T> {
+
+    /// The bounds of a single point.
+    pub fn new(p: Point<T>) -> Bounds<T> {
+        Bounds { min: p, max: p }
+    }
+
+    /// The bounds of `points`, or `None` if there are none.
+    pub fn of<I: IntoIterator<Item = Point<T>>>(points: I) -> Option<Bounds<T>> {
+        let mut points = points.into_iter();
+        let mut bounds = Bounds::new(points.next()?);
+        for p in points {
+            bounds.include(p);
+        }
+
+        Some(bounds)
+    }
+
+    /// Grows to hold `p`.
+    pub fn include(&mut self, p: Point<T>) {
+        self.min = Point::new(self.min.x.min(p.x), self.min.y.min(p.y));
+        self.max = Point::new(self.max.x.max(p.x), self.max.y.max(p.y));
+    }
+
+    pub fn contains(&self, p: Point<T>) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x) && (self.min.y..=self.max.y).contains(&p.y)
+    }
+
+    pub fn width(&self) -> usize {
+        (self.max.x - self.min.x).to_index().unwrap() + 1
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max.y - self.min.y).to_index().unwrap() + 1
+    }
+
+    /// Every point inside, row by row from the top.
+    pub fn points(self) -> impl Iterator<Item = Point<T>> {
+        let width = self.width();
+
+        (0..width * self.height())
+            .map(move |i| self.min + Point::new(T::from_index(i % width), T::from_index(i / width)))
+    }
+}
+
+/// A rectangle of cells, every one holding a value, with its top left at
+/// the origin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+
+    /// A grid with every cell holding `value`.
+    ///
+    /// # Panics
+    /// If either dimension is 0.
+    pub fn new(width: usize, height: usize, value: T) -> Grid<T>
+    where T: Clone {
+        assert!(width > 0 && height > 0, "a grid can't be {} by {}", width, height);
+
+        Grid { width, height, cells: vec![value; width * height] }
+    }
+
+    /// A grid from its cells, row by row.
+    ///
+    /// # Panics
+    /// If there aren't `width * height` of them for some height above 0.
+    pub fn from_cells(width: usize, cells: Vec<T>) -> Grid<T> {
+        assert!(width > 0 && !cells.is_empty() && cells.len().is_multiple_of(width),
+            "{} cells don't fill rows of {}", cells.len(), width);
+
+        Grid { width, height: cells.len() / width, cells }
+    }
+
+    /// Parses text with a cell per character, converted by `cell`.
+    /// Every line must be as long as the first.
+    pub fn parse<F: FnMut(char) -> T>(text: &str, mut cell: F) -> Result<Grid<T>, String> {
+        let mut width = None;
+        let mut cells = Vec::with_capacity(text.len());
+
+        for (y, line) in text.lines().filter(|l| !l.is_empty()).enumerate() {
+            let before = cells.len();
+            cells.extend(line.chars().map(&mut cell));
+
+            let w = cells.len() - before;
+            match width {
+                Some(width) if width != w => {
+                    return Err(format!("row {} is {} wide, not {} like the first", y, w, width));
+                },
+                _ => width = Some(w),
+            }
+        }
+
+        match width {
+            Some(width) => Ok(Grid::from_cells(width, cells)),
+            None => Err("no rows to make a grid of".to_string()),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        Bounds {
+            min: Point::origin(),
+            max: Point::new(self.width as i64 - 1, self.height as i64 - 1),
+        }
+    }
+
+    fn index(&self, p: Point) -> Option<usize> {
+        let x = p.x.to_index().filter(|&x| x < self.width)?;
+        let y = p.y.to_index().filter(|&y| y < self.height)?;
+
+        Some(y * self.width + x)
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        self.index(p).is_some()
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.index(p).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.index(p).map(move |i| &mut self.cells[i])
+    }
+
+    /// Every cell with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.bounds().points().zip(self.cells.iter())
+    }
+
+    /// The rows, top first.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width)
+    }
+
+    /// The neighbors of `p` sharing an edge with it, inside the grid.
+    pub fn neighbors4(&self, p: Point) -> impl Iterator<Item = (Point, &T)> {
+        p.neighbors4().filter_map(move |n| Some((n, self.get(n)?)))
+    }
+
+    /// The neighbors of `p` including diagonals, inside the grid.
+    pub fn neighbors8(&self, p: Point) -> impl Iterator<Item = (Point, &T)> {
+        p.neighbors8().filter_map(move |n| Some((n, self.get(n)?)))
+    }
+
+    /// Draws the grid as text, a line per row.
+    pub fn render<F: Fn(&T) -> char>(&self, glyph: F) -> String {
+        let mut out = String::with_capacity((self.width + 1) * self.height);
+        for row in self.rows() {
+            out.extend(row.iter().map(&glyph));
+            out.push('\n');
+        }
+
+        out
+    }
+}
+
+/// # Panics
+/// If the point is outside the grid.
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &T {
+        self.get(p).unwrap_or_else(|| panic!("{} is outside the grid", p))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        self.get_mut(p).unwrap_or_else(|| panic!("{} is outside the grid", p))
+    }
+}
+
+/// Cells holding values anywhere on the plane, with the bounds
+/// growing to hold every cell ever set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point, T>,
+    bounds: Option<Bounds>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> SparseGrid<T> {
+        SparseGrid { cells: HashMap::new(), bounds: None }
+    }
+}
+
+impl<T> SparseGrid<T> {
+
+    pub fn new() -> SparseGrid<T> {
+        SparseGrid::default()
+    }
+
+    /// Parses text with a character per cell, where `cell` gives a
+    /// cell's value or `None` to leave it empty.
+    pub fn parse<F: FnMut(char) -> Option<T>>(text: &str, mut cell: F) -> SparseGrid<T> {
+        let mut grid = SparseGrid::new();
+        for (y, line) in text.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                if let Some(value) = cell(c) {
+                    grid.insert(Point::new(x as i64, y as i64), value);
+                }
+            }
+        }
+
+        grid
+    }
+
+    /// Sets a cell, returning what it held before.
+    pub fn insert(&mut self, p: Point, value: T) -> Option<T> {
+        match &mut self.bounds {
+            Some(bounds) => bounds.include(p),
+            None => self.bounds = Some(Bounds::new(p)),
+        }
+
+        self.cells.insert(p, value)
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.cells.get(&p)
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.cells.get_mut(&p)
+    }
+
+    /// Empties a cell. The bounds stay as they were.
+    pub fn remove(&mut self, p: Point) -> Option<T> {
+        self.cells.remove(&p)
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        self.cells.contains_key(&p)
+    }
+
+    /// The number of cells set.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// The bounds of every cell set so far, if any were.
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    /// Every cell set, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells.iter().map(|(&p, v)| (p, v))
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.values()
+    }
+
+    /// The neighbors of `p` sharing an edge with it that are set.
+    pub fn neighbors4(&self, p: Point) -> impl Iterator<Item = (Point, &T)> {
+        p.neighbors4().filter_map(move |n| Some((n, self.get(n)?)))
+    }
+
+    /// The neighbors of `p` including diagonals that are set.
+    pub fn neighbors8(&self, p: Point) -> impl Iterator<Item = (Point, &T)> {
+        p.neighbors8().filter_map(move |n| Some((n, self.get(n)?)))
+    }
+
+    /// Draws everything within the bounds as text, a line per row,
+    /// with `glyph` given `None` for empty cells.
+    pub fn render<F: Fn(Option<&T>) -> char>(&self, glyph: F) -> String {
+        let bounds = match self.bounds {
+            Some(bounds) => bounds,
+            None => return String::new(),
+        };
+
+        let mut out = String::with_capacity((bounds.width() + 1) * bounds.height());
+        for p in bounds.points() {
+            out.push(glyph(self.get(p)));
+            if p.x == bounds.max.x {
+                out.push('\n');
+            }
+        }
+
+        out
+    }
+}
+
+#[test]
+fn points() {
+    let p = Point::new(3, -4);
+    assert_eq!(7, p.manhattan(Point::origin()));
+    assert_eq!(Point::new(4, -2), p + Point::new(1, 2));
+    assert_eq!(Point::new(-3, 4), -p);
+    assert_eq!(Point::new(6, -8), p * 2);
+    assert_eq!(Point::new(3, -5), p.step(Direction::Up));
+    assert_eq!((3, -4), p.into());
+    assert_eq!("(3, -4)", p.to_string());
+
+    // Any integer type will do.
+    let q: Point<i16> = Point::new(1, 1);
+    assert_eq!(Point::new(0, 1), q.step(Direction::Left));
+    assert_eq!(vec![Point::new(1, 0), Point::new(2, 1), Point::new(1, 2), Point::new(0, 1)],
+        q.neighbors4().collect::<Vec<_>>());
+    assert_eq!(8, q.neighbors8().count());
+    assert!(!q.neighbors8().any(|n| n == q));
+}
+
+#[test]
+fn directions() {
+    assert_eq!(Direction::Left, Direction::Up.turn_left());
+    assert_eq!(Direction::Up, Direction::Left.turn_right());
+    assert_eq!(Direction::Right, Direction::Left.reverse());
+    assert_eq!(Point::new(0, 1), Direction::Down.offset::<i32>());
+    assert_eq!(Ok(Direction::Down), Direction::try_from('v'));
+    assert_eq!(Ok(Direction::Right), Direction::try_from('R'));
+    assert_eq!(Err('x'), Direction::try_from('x'));
+}
+
+#[test]
+fn bounds() {
+    let bounds = Bounds::of(vec![Point::new(2, 1), Point::new(-1, 3), Point::new(0, 0)]).unwrap();
+    assert_eq!((Point::new(-1, 0), Point::new(2, 3)), (bounds.min, bounds.max));
+    assert_eq!((4, 4), (bounds.width(), bounds.height()));
+    assert!(bounds.contains(Point::new(2, 3)) && !bounds.contains(Point::new(3, 3)));
+    assert_eq!(16, bounds.points().count());
+    assert_eq!(Some(Point::new(0, 0)), bounds.points().nth(1));
+    assert_eq!(None, Bounds::<i64>::of(Vec::new()));
+}
+
+#[test]
+fn dense_grids() {
+    let mut grid = Grid::parse("#..\n.#.\n", |c| c == '#').unwrap();
+    assert_eq!((3, 2), (grid.width(), grid.height()));
+    assert!(grid[Point::new(1, 1)]);
+    assert_eq!(None, grid.get(Point::new(3, 0)));
+    assert_eq!(None, grid.get(Point::new(-1, 0)));
+    assert_eq!(2, grid.iter().filter(|(_, &lit)| lit).count());
+    assert_eq!(3, grid.neighbors4(Point::new(1, 0)).count());
+    assert_eq!(3, grid.neighbors8(Point::new(0, 0)).count());
+
+    grid[Point::new(2, 1)] = true;
+    assert_eq!("#..\n.##\n", grid.render(|&lit| if lit { '#' } else { '.' }));
+    assert_eq!(vec![vec![1, 2], vec![3, 4]], Grid::from_cells(2, vec![1, 2, 3, 4]).rows()
+        .map(<[i32]>::to_vec).collect::<Vec<_>>());
+
+    assert!(Grid::parse("##\n#\n", |c| c).is_err());
+    assert!(Grid::parse("", |c| c).is_err());
+}
+
+#[test]
+#[should_panic(expected = "can't be 3 by 0")]
+fn grids_need_rows() {
+    Grid::new(3, 0, false);
+}
+
+#[test]
+#[should_panic(expected = "can't be 0 by 2")]
+fn grids_need_columns() {
+    Grid::new(0, 2, false);
+}
+
+#[test]
+#[should_panic(expected = "0 cells don't fill rows of 3")]
+fn grids_need_cells() {
+    Grid::<bool>::from_cells(3, Vec::new());
+}
+
+#[test]
+fn sparse_grids() {
+    let mut grid = SparseGrid::parse(".#\n#.\n", |c| if c == '#' { Some(1) } else { None });
+    assert_eq!(2, grid.len());
+    assert_eq!(Some(&1), grid.get(Point::new(1, 0)));
+    assert_eq!(2, grid.neighbors4(Point::new(0, 0)).count());
+
+    grid.insert(Point::new(-1, 2), 2);
+    let bounds = grid.bounds().unwrap();
+    assert_eq!((Point::new(-1, 0), Point::new(1, 2)), (bounds.min, bounds.max));
+    assert_eq!(Some(1), grid.remove(Point::new(1, 0)));
+    assert_eq!("...\n.#.\n2..\n", grid.render(|c| match c {
+        Some(1) => '#',
+        Some(_) => '2',
+        None => '.',
+    }));
+    assert_eq!("", SparseGrid::<u8>::new().render(|_| '#'));
+}
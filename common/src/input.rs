@@ -0,0 +1,259 @@
+//! Finding and reading puzzle input, along with the parameters some
+//! puzzles take that aren't part of the input itself, like the size of
+//! day 8's image. Parameters live in the day's `params.toml`, as
+//! `key = value` lines.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// The environment variable naming a directory of inputs to use instead
+/// of the ones kept with each day, as `day01.txt`, `day02.txt` and so on.
+pub const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+
+/// Where a day's input comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputSource {
+    /// The day's `input.txt`, unless overridden by `AOC_INPUT_DIR`.
+    Default,
+    Path(PathBuf),
+    Stdin,
+    /// One of the day's numbered examples.
+    Example(usize),
+}
+
+#[derive(Debug)]
+pub enum InputError {
+    /// No input was found in any of these places.
+    Missing(Vec<PathBuf>),
+    Read(PathBuf, io::Error),
+    Stdin(io::Error),
+    /// A line of a parameters file that couldn't be understood.
+    Params { path: PathBuf, line: usize, message: String },
+    /// A parameter set on the command line that couldn't be understood.
+    Param(String),
+    /// Input that doesn't suit the puzzle's parameters.
+    Invalid(String),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Missing(tried) => {
+                let tried: Vec<String> = tried.iter().map(|p| p.display().to_string()).collect();
+                write!(f, "no input found; looked for {}", tried.join(" and "))
+            },
+            InputError::Read(path, e) => write!(f, "unable to read {}: {}", path.display(), e),
+            InputError::Stdin(e) => write!(f, "unable to read stdin: {}", e),
+            InputError::Params { path, line, message } => {
+                write!(f, "{} line {}: {}", path.display(), line, message)
+            },
+            InputError::Param(message) | InputError::Invalid(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for InputError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            InputError::Read(_, e) | InputError::Stdin(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Read a day's input, where `dir` is the day's directory.
+pub fn get_input(dir: &str) -> Result<String, InputError> {
+    read_input(dir, &InputSource::Default)
+}
+
+/// Read a day's input from `source`, where `dir` is the day's directory.
+pub fn read_input(dir: &str, source: &InputSource) -> Result<String, InputError> {
+    let path = match source {
+        InputSource::Default => {
+            let override_dir = std::env::var_os(INPUT_DIR_VAR).map(PathBuf::from);
+            find_input(Path::new(dir), override_dir.as_deref())?
+        },
+        InputSource::Path(path) => path.clone(),
+        InputSource::Example(n) => Path::new(dir).join("examples").join(format!("{}.txt", n)),
+        InputSource::Stdin => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input).map_err(InputError::Stdin)?;
+
+            return Ok(input);
+        },
+    };
+
+    std::fs::read_to_string(&path).map_err(|e| InputError::Read(path, e))
+}
+
+/// The first input that exists: the day's file in `override_dir`,
+/// named after the day's directory, then the day's own `input.txt`.
+fn find_input(dir: &Path, override_dir: Option<&Path>) -> Result<PathBuf, InputError> {
+    let mut tried = Vec::new();
+    if let (Some(over), Some(day)) = (override_dir, dir.file_name()) {
+        let mut name = day.to_os_string();
+        name.push(".txt");
+        tried.push(over.join(name));
+    }
+    tried.push(dir.join("input.txt"));
+
+    match tried.iter().find(|p| p.is_file()) {
+        Some(path) => Ok(path.clone()),
+        None => Err(InputError::Missing(tried)),
+    }
+}
+
+/// A puzzle's parameters, by name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Params(BTreeMap<String, Param>);
+
+/// A parameter's value, with the file and line it came from, if any.
+#[derive(Debug, Clone, PartialEq)]
+struct Param {
+    value: String,
+    from: Option<(PathBuf, usize)>,
+}
+
+impl Params {
+
+    /// Parse `key = value` lines, where a value may be quoted.
+    /// Blank lines and `#` comments are skipped.
+    /// Errors give the line number and what was wrong with it.
+    pub fn parse(text: &str) -> Result<Params, (usize, String)> {
+        Params::parse_from(text, None)
+    }
+
+    /// Parse like `parse`, noting that the values came from `path`.
+    fn parse_from(text: &str, path: Option<&Path>) -> Result<Params, (usize, String)> {
+        let mut params = Params::default();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line.split_once('=')
+                .ok_or_else(|| (i + 1, "expected key = value".to_string()))?;
+            let key = key.trim();
+            if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                return Err((i + 1, format!("invalid key {:?}", key)));
+            }
+
+            let value = value.trim();
+            let value = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value);
+            let from = path.map(|p| (p.to_path_buf(), i + 1));
+            params.0.insert(key.to_string(), Param { value: value.to_string(), from });
+        }
+
+        Ok(params)
+    }
+
+    /// Read the parameters in the file at `path`.
+    pub fn read(path: &Path) -> Result<Params, InputError> {
+        let text = std::fs::read_to_string(path).map_err(|e| InputError::Read(path.to_path_buf(), e))?;
+        Params::parse_from(&text, Some(path))
+            .map_err(|(line, message)| InputError::Params { path: path.to_path_buf(), line, message })
+    }
+
+    /// Load the parameters in `dir`'s `params.toml`, or none if it has none.
+    pub fn load(dir: &str) -> Result<Params, InputError> {
+        let path = Path::new(dir).join("params.toml");
+        if !path.exists() {
+            return Ok(Params::default());
+        }
+
+        Params::read(&path)
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) {
+        self.0.insert(key.to_string(), Param { value: value.to_string(), from: None });
+    }
+
+    /// Set every parameter in `other`, in place of any already set.
+    pub(crate) fn extend(&mut self, other: Params) {
+        self.0.extend(other.0);
+    }
+
+    pub(crate) fn remove(&mut self, key: &str) -> Option<String> {
+        self.0.remove(key).map(|p| p.value)
+    }
+
+    /// The parameter `key`, if given, or an error saying where it was
+    /// given if it can't be parsed as a `T`.
+    pub fn get<T: FromStr>(&self, key: &str) -> Result<Option<T>, InputError> {
+        let param = match self.0.get(key) {
+            Some(param) => param,
+            None => return Ok(None),
+        };
+
+        param.value.parse().map(Some).map_err(|_| {
+            let message = format!("invalid value {:?} for parameter {}", param.value, key);
+            match &param.from {
+                Some((path, line)) => InputError::Params { path: path.clone(), line: *line, message },
+                None => InputError::Param(message),
+            }
+        })
+    }
+
+    /// The parameter `key`, or `default` if it wasn't given.
+    pub fn get_or<T: FromStr>(&self, key: &str, default: T) -> Result<T, InputError> {
+        Ok(self.get(key)?.unwrap_or(default))
+    }
+}
+
+#[test]
+fn finds_input() {
+    let root = super::testing::ScratchDir::new("input");
+    let day = root.join("day05");
+    let over = root.join("inputs");
+    std::fs::create_dir_all(&day).unwrap();
+    std::fs::create_dir_all(&over).unwrap();
+
+    match find_input(&day, Some(&over)) {
+        Err(InputError::Missing(tried)) => assert_eq!(vec![over.join("day05.txt"), day.join("input.txt")], tried),
+        r => panic!("expected missing input, got {:?}", r),
+    }
+    let e = find_input(&day, None).unwrap_err().to_string();
+    assert!(e.starts_with("no input found; looked for ") && e.ends_with("input.txt"), "{}", e);
+
+    std::fs::write(day.join("input.txt"), "1,2").unwrap();
+    assert_eq!(day.join("input.txt"), find_input(&day, Some(&over)).unwrap());
+    std::fs::write(over.join("day05.txt"), "3,4").unwrap();
+    assert_eq!(over.join("day05.txt"), find_input(&day, Some(&over)).unwrap());
+    assert_eq!(day.join("input.txt"), find_input(&day, None).unwrap());
+
+    let source = InputSource::Path(over.join("day05.txt"));
+    assert_eq!("3,4", read_input(day.to_str().unwrap(), &source).unwrap());
+    assert!(matches!(read_input(day.to_str().unwrap(), &InputSource::Example(1)), Err(InputError::Read(..))));
+}
+
+#[test]
+fn parses_params() {
+    let params = Params::parse("# The image's size.\nwidth = 25\n\nheight=6\nname = \"bios\"\n").unwrap();
+    assert_eq!(Some(25), params.get::<usize>("width").unwrap());
+    assert_eq!(6, params.get_or("height", 0).unwrap());
+    assert_eq!(3, params.get_or("depth", 3).unwrap());
+    assert_eq!(Some("bios".to_string()), params.get("name").unwrap());
+    assert_eq!(None::<u8>, params.get("missing").unwrap());
+    assert_eq!("invalid value \"bios\" for parameter name", params.get::<u8>("name").unwrap_err().to_string());
+
+    assert_eq!(Err(2), Params::parse("a = 1\nb").map_err(|e| e.0));
+    assert_eq!(Err(1), Params::parse("two words = 1").map_err(|e| e.0));
+    assert_eq!(Params::default(), Params::load("no/such/day").unwrap());
+
+    // Values read from a file are reported with where they came from.
+    let root = super::testing::ScratchDir::new("params");
+    root.write("params.toml", "width = 25\nheight = x");
+    let mut params = Params::load(root.as_str()).unwrap();
+    match params.get::<usize>("height") {
+        Err(InputError::Params { path, line, .. }) => assert_eq!((root.join("params.toml"), 2), (path, line)),
+        r => panic!("expected an invalid parameter, got {:?}", r),
+    }
+    params.set("height", "6");
+    assert_eq!(Some(6), params.get::<usize>("height").unwrap());
+}
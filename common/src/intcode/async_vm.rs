@@ -0,0 +1,180 @@
+//! Async execution for embedding machines in async code.
+//! The machine runs as a future that suspends whenever it needs input,
+//! and is woken again when input arrives through an `AsyncIOHandle`.
+
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use super::queue::TrySendError;
+use super::event::Event;
+use super::vm::{IOHandle, IntcodeVM, VmError};
+
+/// Instructions executed per poll before yielding to other tasks.
+const STEP_BUDGET: usize = 4096;
+
+/// Wakers shared between a machine and its async handles.
+#[derive(Default)]
+pub(super) struct AsyncState {
+    machine: Option<Waker>,
+    output: Option<Waker>,
+    input: Option<Waker>,
+    halted: bool,
+}
+
+/// Async counterpart of `IOHandle`.
+pub struct AsyncIOHandle {
+    io: IOHandle<i64, i64>,
+    state: Arc<Mutex<AsyncState>>,
+}
+
+impl AsyncIOHandle {
+
+    /// Send `data` as input, waking the machine if it is waiting for it.
+    /// Waits for room if the input queue is bounded and full, unless the
+    /// machine has halted, in which case the input is dropped.
+    pub async fn send(&self, data: i64) {
+        poll_fn(|cx| {
+            let mut state = self.state.lock().unwrap();
+            match self.io.try_send(data) {
+                Ok(()) => {
+                    if let Some(w) = state.machine.take() {
+                        w.wake();
+                    }
+                    Poll::Ready(())
+                },
+                Err(TrySendError::Full(_)) if !state.halted => {
+                    state.input = Some(cx.waker().clone());
+                    Poll::Pending
+                },
+                Err(TrySendError::Full(_)) => Poll::Ready(()),
+                Err(TrySendError::Closed(_)) => panic!("unable to send input: input is closed"),
+            }
+        }).await
+    }
+
+    /// Wait for the next output, or `None` once the machine has halted
+    /// with nothing left in the output queue.
+    pub async fn recv(&self) -> Option<i64> {
+        poll_fn(|cx| {
+            let mut state = self.state.lock().unwrap();
+
+            // Taking output makes room for a machine held up by a full queue.
+            if let Some(data) = self.io.recv() {
+                if let Some(w) = state.machine.take() {
+                    w.wake();
+                }
+                Poll::Ready(Some(data))
+            } else if state.halted {
+                Poll::Ready(None)
+            } else {
+                state.output = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }).await
+    }
+
+    /// Collects all data from the output queue without waiting.
+    pub fn dump(&self) -> Vec<i64> {
+        self.io.dump()
+    }
+}
+
+/// A machine running until it halts; see `IntcodeVM::run_async`.
+pub struct RunFuture<'a> {
+    vm: &'a mut IntcodeVM,
+}
+
+impl Future for RunFuture<'_> {
+    type Output = Result<(), VmError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let vm = &mut *self.vm;
+
+        for _ in 0..STEP_BUDGET {
+            match vm.step() {
+                Some(Event::Halted) | Some(Event::EndOfInput) => return finish(vm, Ok(())),
+                Some(Event::Error(e)) => return finish(vm, Err(e)),
+                Some(Event::NeedInput) => {
+                    let state = vm.async_state();
+                    let mut state = state.lock().unwrap();
+
+                    // Input may have arrived before the waker was stored.
+                    if vm.input_pending() {
+                        continue;
+                    }
+
+                    state.machine = Some(cx.waker().clone());
+                    wake_handles(&mut state);
+
+                    return Poll::Pending;
+                },
+                Some(Event::OutputFull) => {
+                    let state = vm.async_state();
+                    let mut state = state.lock().unwrap();
+
+                    // Output may have been taken before the waker was stored.
+                    if !vm.output_full() {
+                        continue;
+                    }
+
+                    state.machine = Some(cx.waker().clone());
+                    wake_handles(&mut state);
+
+                    return Poll::Pending;
+                },
+                // Breakpoints are for stepping; a running future passes them.
+                Some(_) | None => {},
+            }
+        }
+
+        // Out of budget; let other tasks run.
+        wake_handles(&mut vm.async_state().lock().unwrap());
+        cx.waker().wake_by_ref();
+
+        Poll::Pending
+    }
+}
+
+/// Marks the machine as halted and wakes anyone waiting on output.
+fn finish(vm: &IntcodeVM, result: Result<(), VmError>) -> Poll<Result<(), VmError>> {
+    let state = vm.async_state();
+    let mut state = state.lock().unwrap();
+    state.halted = true;
+    wake_handles(&mut state);
+
+    Poll::Ready(result)
+}
+
+/// Wakes anyone waiting on output, or for room to send input,
+/// as the machine pauses having maybe produced or read some.
+fn wake_handles(state: &mut AsyncState) {
+    if let Some(w) = state.output.take() {
+        w.wake();
+    }
+    if let Some(w) = state.input.take() {
+        w.wake();
+    }
+}
+
+impl IntcodeVM {
+
+    /// Get an async handle to the machine's IO.
+    pub fn async_io(&self) -> AsyncIOHandle {
+        AsyncIOHandle {
+            io: self.io(),
+            state: self.async_state(),
+        }
+    }
+
+    /// Run the program as a future, which completes when the machine halts
+    /// and suspends whenever input is needed but none is queued.
+    pub fn run_async(&mut self) -> RunFuture<'_> {
+        self.async_state().lock().unwrap().halted = false;
+
+        RunFuture {
+            vm: self,
+        }
+    }
+}
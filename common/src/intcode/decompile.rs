@@ -0,0 +1,761 @@
+//! Turning programs into C-like pseudo-code, recovering functions from
+//! the calling convention puzzle programs build on the relative base.
+//!
+//! A call stores its return address in `[rb + 0]` and its arguments in
+//! `[rb + 1]`, `[rb + 2]`, ..., then jumps to the function. The function
+//! moves the base past its frame with `ARB #n`, finding its arguments
+//! just below the base, and returns by moving it back with `ARB #-n`
+//! and jumping to `[rb + 0]`. Results are left in the argument cells.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use super::disasm::{Disassembly, Instruction, Opcode, Param};
+
+/// A function found in a program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Function {
+    pub entry: usize,
+    /// Cells the function reserves on entry, if it keeps a fixed frame.
+    pub frame: Option<i64>,
+    /// The most arguments any caller passes.
+    pub params: usize,
+    body: BTreeSet<usize>,
+}
+
+impl Function {
+
+    /// The name used in pseudo-code.
+    pub fn name(&self) -> String {
+        if self.entry == 0 {
+            "main".to_string()
+        } else {
+            format!("f_{}", self.entry)
+        }
+    }
+
+    /// The addresses of the instructions making up the function.
+    pub fn body(&self) -> &BTreeSet<usize> {
+        &self.body
+    }
+
+    /// The name of the cell at an offset from the relative base, as seen
+    /// from inside the function: parameters, locals, then the arguments
+    /// of the next call.
+    fn slot(&self, offset: i64) -> String {
+        let params = self.params as i64;
+        match self.frame {
+            Some(_) if offset > 0 => format!("a{}", offset),
+            Some(n) if offset < 0 && offset + n >= 1 && offset + n <= params => format!("p{}", offset + n),
+            Some(n) if offset < 0 && offset + n > params => format!("l{}", offset + n - params),
+            _ if offset < 0 => format!("stack[rb - {}]", offset.unsigned_abs()),
+            _ if offset > 0 => format!("stack[rb + {}]", offset),
+            _ => "stack[rb]".to_string(),
+        }
+    }
+
+    /// The number of locals, for functions with a fixed frame.
+    fn locals(&self) -> i64 {
+        self.frame.map_or(0, |n| (n - 1 - self.params as i64).max(0))
+    }
+}
+
+/// A call found in a program, keyed by the jump into the function.
+#[derive(Debug, Clone)]
+struct Call {
+    target: usize,
+    /// The instructions storing each argument, by argument number.
+    args: BTreeMap<i64, usize>,
+}
+
+/// A program decompiled into functions.
+///
+/// Loops and conditionals are recovered where jumps nest the way
+/// structured code compiles to; anything else is left as a `goto`.
+#[derive(Debug, Clone)]
+pub struct Decompilation {
+    dis: Disassembly,
+    functions: BTreeMap<usize, Function>,
+    calls: BTreeMap<usize, Call>,
+    /// Cells the functions write through position parameters.
+    writes: BTreeSet<usize>,
+}
+
+impl Decompilation {
+
+    /// Decompile a program.
+    pub fn new(program: &[i64]) -> Decompilation {
+        let dis = Disassembly::new(program);
+        let calls = find_calls(&dis);
+
+        let mut entries: BTreeSet<usize> = calls.values().map(|c| c.target).collect();
+        entries.insert(0);
+
+        let functions: BTreeMap<usize, Function> = entries.into_iter()
+            .filter(|e| dis.instructions().contains_key(e))
+            .map(|entry| {
+                let body = find_body(&dis, &calls, entry);
+                let params = calls.values()
+                    .filter(|c| c.target == entry)
+                    .filter_map(|c| c.args.keys().next_back())
+                    .max()
+                    .map_or(0, |&k| k as usize);
+                let frame = find_frame(&dis, &body, entry);
+                let params = frame.map_or(params, |n| params.min(n as usize - 1));
+
+                (entry, Function { entry, frame, params, body })
+            })
+            .collect();
+
+        // Guessed jump targets can decode to junk writing anywhere,
+        // so only trust writes from inside functions.
+        let writes = functions.values()
+            .flat_map(|f| f.body.iter())
+            .filter_map(|a| dis.instructions()[a].destination()?.address())
+            .collect();
+
+        Decompilation {
+            dis,
+            functions,
+            calls,
+            writes,
+        }
+    }
+
+    /// The disassembly the pseudo-code was built from.
+    pub fn disassembly(&self) -> &Disassembly {
+        &self.dis
+    }
+
+    /// Every function found, by entry address. The program's
+    /// starting point is included as `main`.
+    pub fn functions(&self) -> &BTreeMap<usize, Function> {
+        &self.functions
+    }
+
+    /// The function starting at `entry`.
+    pub fn function(&self, entry: usize) -> Option<&Function> {
+        self.functions.get(&entry)
+    }
+}
+
+impl fmt::Display for Decompilation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, func) in self.functions.values().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+
+            let params: Vec<String> = (1..=func.params).map(|i| format!("p{}", i)).collect();
+            writeln!(f, "fn {}({}) {{", func.name(), params.join(", "))?;
+            if func.locals() > 0 {
+                let locals: Vec<String> = (1..=func.locals()).map(|i| format!("l{}", i)).collect();
+                writeln!(f, "    var {};", locals.join(", "))?;
+            }
+
+            for line in Emitter::new(self, func).run() {
+                writeln!(f, "{}{}", "    ".repeat(line.depth), line.text)?;
+            }
+            writeln!(f, "}}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The target of a jump that is always taken.
+fn unconditional_target(instr: &Instruction) -> Option<Param> {
+    match (instr.opcode, instr.params.first()?) {
+        (Opcode::JumpIfTrue, Param::Immediate(v)) if *v != 0 => Some(instr.params[1]),
+        (Opcode::JumpIfFalse, Param::Immediate(0)) => Some(instr.params[1]),
+        _ => None,
+    }
+}
+
+/// Whether an instruction is a jump that is never taken.
+fn never_taken(instr: &Instruction) -> bool {
+    match (instr.opcode, instr.params.first()) {
+        (Opcode::JumpIfTrue, Some(Param::Immediate(0))) => true,
+        (Opcode::JumpIfFalse, Some(Param::Immediate(v))) => *v != 0,
+        _ => false,
+    }
+}
+
+fn is_return(instr: &Instruction) -> bool {
+    unconditional_target(instr) == Some(Param::Relative(0))
+}
+
+/// The value an instruction stores, if it only stores a constant.
+fn constant(instr: &Instruction) -> Option<i64> {
+    match (instr.opcode, instr.sources()) {
+        (Opcode::Add, [Param::Immediate(a), Param::Immediate(b)]) => a.checked_add(*b),
+        (Opcode::Mul, [Param::Immediate(a), Param::Immediate(b)]) => a.checked_mul(*b),
+        _ => None,
+    }
+}
+
+/// The instruction ending right where `addr` starts. Instructions
+/// decoded from guessed jump targets can overlap it, so look past them.
+fn previous(dis: &Disassembly, addr: usize) -> Option<&Instruction> {
+    dis.instructions()
+        .range(addr.saturating_sub(4)..addr)
+        .map(|(_, i)| i)
+        .find(|i| i.addr + i.size() == addr)
+}
+
+/// Finds jumps into functions: an unconditional jump straight after
+/// storing the address following it in `[rb + 0]`.
+fn find_calls(dis: &Disassembly) -> BTreeMap<usize, Call> {
+    let mut calls = BTreeMap::new();
+
+    for jump in dis.instructions().values() {
+        let target = match unconditional_target(jump) {
+            Some(Param::Immediate(t)) if t >= 0 => t as usize,
+            _ => continue,
+        };
+
+        let store = match previous(dis, jump.addr) {
+            Some(s) if s.destination() == Some(Param::Relative(0)) => s,
+            _ => continue,
+        };
+        if constant(store) != Some((jump.addr + jump.size()) as i64) {
+            continue;
+        }
+
+        // Arguments are stored just before, each once.
+        let mut args = BTreeMap::new();
+        let mut at = store.addr;
+        while let Some(prev) = previous(dis, at) {
+            match prev.destination() {
+                Some(Param::Relative(k)) if k > 0 && !args.contains_key(&k) => {
+                    args.insert(k, prev.addr);
+                },
+                _ => break,
+            }
+            at = prev.addr;
+        }
+
+        calls.insert(jump.addr, Call { target, args });
+    }
+
+    calls
+}
+
+/// Follows execution within a function, stepping over calls.
+fn find_body(dis: &Disassembly, calls: &BTreeMap<usize, Call>, entry: usize) -> BTreeSet<usize> {
+    let instructions = dis.instructions();
+    let mut body = BTreeSet::new();
+    let mut pending = vec![entry];
+
+    while let Some(addr) = pending.pop() {
+        let instr = match instructions.get(&addr) {
+            Some(i) if body.insert(addr) => i,
+            _ => continue,
+        };
+
+        let next = addr + instr.size();
+        if calls.contains_key(&addr) {
+            pending.push(next);
+            continue;
+        }
+
+        match instr.opcode {
+            Opcode::Halt => {},
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse => {
+                if let Param::Immediate(t) = instr.params[1] {
+                    if t >= 0 && !never_taken(instr) {
+                        pending.push(t as usize);
+                    }
+                }
+                if unconditional_target(instr).is_none() {
+                    pending.push(next);
+                }
+            },
+            _ => pending.push(next),
+        }
+    }
+
+    body
+}
+
+/// The frame size, if the function reserves one with its first
+/// instruction and releases the same amount right before every return,
+/// and otherwise leaves the base alone.
+fn find_frame(dis: &Disassembly, body: &BTreeSet<usize>, entry: usize) -> Option<i64> {
+    let instructions = dis.instructions();
+    let n = match instructions[&entry] {
+        Instruction { opcode: Opcode::AdjustBase, ref params, .. } => match params[0] {
+            Param::Immediate(n) if n > 0 => n,
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    let mut epilogues = BTreeSet::new();
+    for &addr in body {
+        if !is_return(&instructions[&addr]) {
+            continue;
+        }
+        match previous(dis, addr) {
+            Some(i) if i.opcode == Opcode::AdjustBase && i.params[0] == Param::Immediate(-n) => {
+                epilogues.insert(i.addr);
+            },
+            _ => return None,
+        }
+    }
+
+    let other_adjustments = body.iter()
+        .any(|&a| a != entry && !epilogues.contains(&a) && instructions[&a].opcode == Opcode::AdjustBase);
+    if epilogues.is_empty() || other_adjustments {
+        return None;
+    }
+
+    Some(n)
+}
+
+/// A comparison of two expressions.
+#[derive(Debug, Clone)]
+struct Condition {
+    lhs: String,
+    op: &'static str,
+    rhs: String,
+}
+
+impl Condition {
+    fn negate(self) -> Condition {
+        let op = match self.op {
+            "<" => ">=",
+            ">=" => "<",
+            "==" => "!=",
+            _ => "==",
+        };
+
+        Condition { op, ..self }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.lhs, self.op, self.rhs)
+    }
+}
+
+/// A line of pseudo-code, with the address it came from
+/// so labels can be placed in front of it.
+struct Line {
+    addr: usize,
+    depth: usize,
+    text: String,
+}
+
+/// A loop being emitted: its first instruction, the address after it,
+/// and whether jumping back to the start continues it.
+struct Loop {
+    header: usize,
+    exit: usize,
+    continues: bool,
+}
+
+/// Writes out the pseudo-code for one function.
+struct Emitter<'a> {
+    dis: &'a Disassembly,
+    functions: &'a BTreeMap<usize, Function>,
+    calls: &'a BTreeMap<usize, Call>,
+    writes: &'a BTreeSet<usize>,
+    func: &'a Function,
+    /// Instructions folded into another's line.
+    consumed: BTreeSet<usize>,
+    /// Comparisons folded into the jump that follows them.
+    conditions: BTreeMap<usize, Condition>,
+    loops: Vec<Loop>,
+    gotos: BTreeSet<usize>,
+    lines: Vec<Line>,
+}
+
+impl<'a> Emitter<'a> {
+
+    fn new(dec: &'a Decompilation, func: &'a Function) -> Emitter<'a> {
+        let mut emitter = Emitter {
+            dis: &dec.dis,
+            functions: &dec.functions,
+            calls: &dec.calls,
+            writes: &dec.writes,
+            func,
+            consumed: BTreeSet::new(),
+            conditions: BTreeMap::new(),
+            loops: Vec::new(),
+            gotos: BTreeSet::new(),
+            lines: Vec::new(),
+        };
+        emitter.fold();
+
+        emitter
+    }
+
+    /// Works out which instructions are shown as part of another.
+    fn fold(&mut self) {
+        let instructions = self.dis.instructions();
+
+        if self.func.frame.is_some() {
+            self.consumed.insert(self.func.entry);
+        }
+
+        // Addresses execution arrives at other than by falling through.
+        let mut targets: BTreeSet<usize> = self.calls.keys()
+            .map(|&a| a + instructions[&a].size())
+            .collect();
+        for instr in instructions.values() {
+            if matches!(instr.opcode, Opcode::JumpIfTrue | Opcode::JumpIfFalse) {
+                if let Param::Immediate(t) = instr.params[1] {
+                    targets.insert(t as usize);
+                }
+            }
+        }
+
+        for &addr in &self.func.body {
+            let instr = &instructions[&addr];
+
+            if let Some(call) = self.calls.get(&addr) {
+                self.consumed.extend(call.args.values());
+                self.consumed.extend(previous(self.dis, addr).map(|s| s.addr));
+                continue;
+            }
+
+            let prev = previous(self.dis, addr).filter(|p| self.func.body.contains(&p.addr));
+
+            if is_return(instr) && self.func.frame.is_some() {
+                self.consumed.extend(prev.map(|p| p.addr));
+                continue;
+            }
+
+            // A comparison only read by the jump straight after it.
+            let tested = match instr.opcode {
+                Opcode::JumpIfTrue | Opcode::JumpIfFalse => instr.params[0],
+                _ => continue,
+            };
+            let cmp = match prev {
+                Some(p) if !targets.contains(&addr) && p.destination() == Some(tested) => p,
+                _ => continue,
+            };
+            let op = match cmp.opcode {
+                Opcode::LessThan => "<",
+                Opcode::Equals => "==",
+                _ => continue,
+            };
+
+            let cond = Condition {
+                lhs: self.operand(cmp, 0),
+                op,
+                rhs: self.operand(cmp, 1),
+            };
+            let cond = if instr.opcode == Opcode::JumpIfTrue { cond } else { cond.negate() };
+
+            self.consumed.insert(cmp.addr);
+            self.conditions.insert(addr, cond);
+        }
+    }
+
+    /// Emits the whole function, placing labels in front of goto targets.
+    fn run(mut self) -> Vec<Line> {
+        let end = self.func.body.iter().next_back().map_or(0, |&a| a + 1);
+        self.emit(self.func.entry, end, 1, None);
+
+        let mut lines = Vec::with_capacity(self.lines.len() + self.gotos.len());
+        let mut gotos = self.gotos.iter().peekable();
+        for line in self.lines {
+            while let Some(&&t) = gotos.peek().filter(|&&&t| t <= line.addr) {
+                lines.push(Line { addr: t, depth: line.depth, text: format!("L_{}:", t) });
+                gotos.next();
+            }
+            lines.push(line);
+        }
+        for &t in gotos {
+            lines.push(Line { addr: t, depth: 1, text: format!("L_{}:", t) });
+        }
+
+        lines
+    }
+
+    fn line(&mut self, addr: usize, depth: usize, text: String) {
+        self.lines.push(Line { addr, depth, text });
+    }
+
+    fn instr(&self, addr: usize) -> &'a Instruction {
+        &self.dis.instructions()[&addr]
+    }
+
+    /// Emits the function's instructions in `lo..hi`, in address order.
+    /// `header` is the start of a loop already being emitted.
+    fn emit(&mut self, lo: usize, hi: usize, depth: usize, header: Option<usize>) {
+        let mut cursor = lo;
+        while cursor < hi {
+            let addr = match self.func.body.range(cursor..hi).next() {
+                Some(&a) => a,
+                None => break,
+            };
+            let instr = self.instr(addr);
+            let next = addr + instr.size();
+            cursor = next;
+
+            if header != Some(addr) {
+                if let Some(back) = self.back_jump(addr, hi) {
+                    cursor = self.emit_loop(addr, back, depth);
+                    continue;
+                }
+            }
+            if self.consumed.contains(&addr) {
+                continue;
+            }
+
+            if let Some(call) = self.calls.get(&addr) {
+                let text = self.call(call);
+                self.line(addr, depth, text);
+                continue;
+            }
+            if is_return(instr) {
+                self.line(addr, depth, "return;".to_string());
+                continue;
+            }
+
+            match instr.opcode {
+                Opcode::JumpIfTrue | Opcode::JumpIfFalse => {
+                    if let Some(after) = self.emit_jump(instr, hi, depth) {
+                        cursor = after;
+                    }
+                },
+                Opcode::Halt => self.line(addr, depth, "halt();".to_string()),
+                Opcode::AdjustBase => {
+                    let text = match instr.params[0] {
+                        Param::Immediate(n) if n < 0 => format!("rb -= {};", n.unsigned_abs()),
+                        _ => format!("rb += {};", self.operand(instr, 0)),
+                    };
+                    self.line(addr, depth, text);
+                },
+                Opcode::Output => {
+                    let text = format!("output({});", self.operand(instr, 0));
+                    self.line(addr, depth, text);
+                },
+                _ => {
+                    let dest = self.operand(instr, instr.params.len() - 1);
+                    let text = format!("{} = {};", dest, self.expression(instr));
+                    self.line(addr, depth, text);
+                },
+            }
+        }
+    }
+
+    /// The last jump in `addr..hi` back to `addr`, if any.
+    fn back_jump(&self, addr: usize, hi: usize) -> Option<usize> {
+        self.func.body.range(addr..hi)
+            .rev()
+            .copied()
+            .find(|&b| {
+                let instr = self.instr(b);
+                matches!(instr.opcode, Opcode::JumpIfTrue | Opcode::JumpIfFalse)
+                    && instr.params[1] == Param::Immediate(addr as i64)
+                    && !self.calls.contains_key(&b)
+                    && !never_taken(instr)
+            })
+    }
+
+    /// Emits the loop from `header` to the jump back at `back`,
+    /// returning the address after it.
+    fn emit_loop(&mut self, header: usize, back: usize, depth: usize) -> usize {
+        let exit = back + self.instr(back).size();
+
+        if unconditional_target(self.instr(back)).is_some() {
+            // A test at the top leaving the loop makes it a while loop.
+            let test = self.func.body.range(header..back)
+                .copied()
+                .find(|a| !self.consumed.contains(a))
+                .filter(|&a| {
+                    let instr = self.instr(a);
+                    matches!(instr.opcode, Opcode::JumpIfTrue | Opcode::JumpIfFalse)
+                        && instr.params[1] == Param::Immediate(exit as i64)
+                        && !never_taken(instr)
+                        && unconditional_target(instr).is_none()
+                });
+
+            self.loops.push(Loop { header, exit, continues: true });
+            match test {
+                Some(t) => {
+                    let cond = self.condition(self.instr(t)).negate();
+                    self.line(header, depth, format!("while ({}) {{", cond));
+                    let start = t + self.instr(t).size();
+                    self.emit(start, back, depth + 1, None);
+                },
+                None => {
+                    self.line(header, depth, "while (1) {".to_string());
+                    self.emit(header, back, depth + 1, Some(header));
+                },
+            }
+            self.line(back, depth, "}".to_string());
+        } else {
+            self.loops.push(Loop { header, exit, continues: false });
+            self.line(header, depth, "do {".to_string());
+            self.emit(header, back, depth + 1, Some(header));
+            let cond = self.condition(self.instr(back));
+            self.line(back, depth, format!("}} while ({});", cond));
+        }
+        self.loops.pop();
+
+        exit
+    }
+
+    /// Emits a jump, structuring it into an if or if/else when it skips
+    /// forward within `..hi`. Returns where to carry on from if it did.
+    fn emit_jump(&mut self, instr: &'a Instruction, hi: usize, depth: usize) -> Option<usize> {
+        let addr = instr.addr;
+        let next = addr + instr.size();
+        if never_taken(instr) {
+            return None;
+        }
+
+        let target = match instr.params[1] {
+            Param::Immediate(t) if t >= 0 => t as usize,
+            _ => {
+                let text = format!("goto *{};", self.operand(instr, 1));
+                self.line(addr, depth, text);
+                return None;
+            },
+        };
+
+        let always = unconditional_target(instr).is_some();
+        let cond = if always { None } else { Some(self.condition(instr)) };
+        let guarded = |text: &str| match &cond {
+            Some(c) => format!("if ({}) {}", c, text),
+            None => text.to_string(),
+        };
+
+        // Leaving or restarting the innermost loop.
+        if let Some(l) = self.loops.last() {
+            let text = if target == l.exit {
+                Some("break;")
+            } else if target == l.header && l.continues {
+                Some("continue;")
+            } else {
+                None
+            };
+            if let Some(text) = text {
+                self.line(addr, depth, guarded(text));
+                return None;
+            }
+        }
+
+        if target == next {
+            return None;
+        }
+
+        let cond = match cond {
+            Some(c) if target > next && target <= hi => c.negate(),
+            _ => {
+                self.gotos.insert(target);
+                self.line(addr, depth, guarded(&format!("goto L_{};", target)));
+                return None;
+            },
+        };
+
+        // The skipped code ending in a jump over more code is an if/else.
+        let last = self.func.body.range(next..target).next_back().map(|&a| self.instr(a));
+        let join = last
+            .filter(|l| !self.calls.contains_key(&l.addr) && !self.consumed.contains(&l.addr))
+            .and_then(|l| match unconditional_target(l) {
+                Some(Param::Immediate(j)) if j as usize > target && j as usize <= hi => Some((l.addr, j as usize)),
+                _ => None,
+            })
+            .filter(|&(_, j)| self.loops.last().is_none_or(|l| j != l.exit));
+
+        self.line(addr, depth, format!("if ({}) {{", cond));
+        match join {
+            Some((jump, j)) => {
+                self.emit(next, jump, depth + 1, None);
+                self.line(jump, depth, "} else {".to_string());
+                self.emit(target, j, depth + 1, None);
+                self.line(j - 1, depth, "}".to_string());
+                Some(j)
+            },
+            None => {
+                self.emit(next, target, depth + 1, None);
+                self.line(target - 1, depth, "}".to_string());
+                Some(target)
+            },
+        }
+    }
+
+    /// What makes a conditional jump jump.
+    fn condition(&self, instr: &Instruction) -> Condition {
+        if let Some(cond) = self.conditions.get(&instr.addr) {
+            return cond.clone();
+        }
+
+        Condition {
+            lhs: self.operand(instr, 0),
+            op: if instr.opcode == Opcode::JumpIfTrue { "!=" } else { "==" },
+            rhs: "0".to_string(),
+        }
+    }
+
+    fn call(&self, call: &Call) -> String {
+        let name = self.functions.get(&call.target)
+            .map_or_else(|| format!("f_{}", call.target), |f| f.name());
+        let count = call.args.keys().next_back().copied().unwrap_or(0);
+
+        let args: Vec<String> = (1..=count)
+            .map(|k| match call.args.get(&k) {
+                Some(&a) => self.expression(self.instr(a)),
+                None => self.func.slot(k),
+            })
+            .collect();
+
+        format!("{}({});", name, args.join(", "))
+    }
+
+    /// The value an instruction stores.
+    fn expression(&self, instr: &Instruction) -> String {
+        let (a, b) = match instr.sources() {
+            [a, b] => (*a, *b),
+            _ => return "input()".to_string(),
+        };
+        let (x, y) = (self.operand(instr, 0), self.operand(instr, 1));
+
+        match (instr.opcode, a, b) {
+            (Opcode::Add, _, Param::Immediate(0)) => x,
+            (Opcode::Add, Param::Immediate(0), _) => y,
+            (Opcode::Add, _, Param::Immediate(v)) if v < 0 => format!("{} - {}", x, v.unsigned_abs()),
+            (Opcode::Add, Param::Immediate(v), _) if v < 0 => format!("{} - {}", y, v.unsigned_abs()),
+            (Opcode::Add, ..) => format!("{} + {}", x, y),
+            (Opcode::Mul, _, Param::Immediate(0)) | (Opcode::Mul, Param::Immediate(0), _) => "0".to_string(),
+            (Opcode::Mul, _, Param::Immediate(1)) => x,
+            (Opcode::Mul, Param::Immediate(1), _) => y,
+            (Opcode::Mul, _, Param::Immediate(-1)) => format!("-{}", x),
+            (Opcode::Mul, Param::Immediate(-1), _) => format!("-{}", y),
+            (Opcode::Mul, ..) => format!("{} * {}", x, y),
+            (Opcode::LessThan, ..) => format!("{} < {}", x, y),
+            _ => format!("{} == {}", x, y),
+        }
+    }
+
+    /// Names an instruction's parameter: `g12` for data, `code[12]` for
+    /// cells inside instructions, and the function's own names for stack
+    /// cells. Parameters the program overwrites are shown as the cell
+    /// they are patched from, dereferenced for position parameters.
+    fn operand(&self, instr: &Instruction, i: usize) -> String {
+        let param = instr.params[i];
+        let cell = instr.addr + 1 + i;
+        if self.writes.contains(&cell) {
+            return match param {
+                Param::Position(_) => format!("mem[code[{}]]", cell),
+                Param::Immediate(_) => format!("code[{}]", cell),
+                Param::Relative(_) => format!("stack[rb + code[{}]]", cell),
+            };
+        }
+
+        match param {
+            Param::Immediate(v) => v.to_string(),
+            Param::Relative(o) => self.func.slot(o),
+            Param::Position(a) if a < 0 => format!("mem[{}]", a),
+            Param::Position(a) if self.dis.is_code(a as usize) => format!("code[{}]", a),
+            Param::Position(a) => format!("g{}", a),
+        }
+    }
+}
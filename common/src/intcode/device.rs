@@ -0,0 +1,242 @@
+//! Peripherals driven by a machine's output and feeding its input,
+//! such as day 11's hull-painting robot and day 13's arcade screen.
+
+use std::fmt;
+
+use crate::grid::{Direction, Point, SparseGrid};
+use super::event::Event;
+use super::vm::{IntcodeVM, VmError};
+
+/// A peripheral attached to a machine through a `Bus`.
+pub trait Device {
+
+    /// The number of output values making up one message to the device.
+    fn arity(&self) -> usize;
+
+    /// Handle one complete message.
+    fn receive(&mut self, values: &[i64]);
+
+    /// Provide the next input value, if the device has one.
+    fn provide(&mut self) -> Option<i64> {
+        None
+    }
+}
+
+/// Why a `Bus` stopped before the program halted.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BusError {
+    /// The program needed input that no device could provide.
+    NoInput,
+    /// The machine itself stopped on an error.
+    Vm(VmError),
+}
+
+impl fmt::Display for BusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BusError::NoInput => write!(f, "program needs input no device can provide"),
+            BusError::Vm(e) => write!(f, "machine error: {:?}", e),
+        }
+    }
+}
+
+impl std::error::Error for BusError {}
+
+/// Connects devices to a machine, grouping its output into messages of
+/// the devices' arity and asking the devices for input when it's needed.
+/// Every message goes to every device; input comes from the first device
+/// that has some, in the order they were attached.
+pub struct Bus<'a> {
+    vm: &'a mut IntcodeVM,
+    devices: Vec<&'a mut dyn Device>,
+    buffer: Vec<i64>,
+}
+
+impl<'a> Bus<'a> {
+
+    /// Create a bus for a machine with its program already loaded.
+    pub fn new(vm: &'a mut IntcodeVM) -> Bus<'a> {
+        Bus {
+            vm,
+            devices: Vec::new(),
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Attach a device. All devices on a bus must share the same arity.
+    pub fn attach(mut self, device: &'a mut dyn Device) -> Bus<'a> {
+        if let Some(first) = self.devices.first() {
+            assert_eq!(first.arity(), device.arity(), "devices on a bus must share an arity");
+        }
+        self.devices.push(device);
+
+        self
+    }
+
+    /// Run the machine until it halts.
+    /// Any incomplete message left when it halts is dropped.
+    pub fn run(&mut self) -> Result<(), BusError> {
+        let io_handle = self.vm.io();
+
+        loop {
+            let msg = self.vm.step();
+
+            while let Some(value) = io_handle.recv() {
+                self.buffer.push(value);
+                self.dispatch();
+            }
+
+            match msg {
+                Some(Event::Halted) => return Ok(()),
+                Some(Event::EndOfInput) => return Err(BusError::NoInput),
+                Some(Event::Error(e)) => return Err(BusError::Vm(e)),
+                Some(Event::NeedInput) => {
+                    let input = self.devices.iter_mut().find_map(|d| d.provide());
+                    match input {
+                        Some(value) => io_handle.send(value),
+                        None => return Err(BusError::NoInput),
+                    }
+                },
+                // Output was just drained, so the next step has room.
+                Some(_) | None => {},
+            }
+        }
+    }
+
+    /// Hands the buffered message to every device once it is complete.
+    fn dispatch(&mut self) {
+        let arity = self.devices.first().map_or(1, |d| d.arity());
+        if self.buffer.len() < arity {
+            return;
+        }
+
+        for device in self.devices.iter_mut() {
+            device.receive(&self.buffer);
+        }
+        self.buffer.clear();
+    }
+}
+
+/// A 2D screen drawn with `(x, y, tile)` messages.
+/// The message `(-1, 0, score)` sets the score instead of a tile.
+#[derive(Debug, Clone, Default)]
+pub struct Screen {
+    tiles: SparseGrid<i64>,
+    score: Option<i64>,
+}
+
+impl Screen {
+
+    /// Create a blank screen.
+    pub fn new() -> Screen {
+        Screen::default()
+    }
+
+    /// The tile at `(x, y)`, 0 if never drawn.
+    pub fn tile(&self, x: i64, y: i64) -> i64 {
+        *self.tiles.get(Point::new(x, y)).unwrap_or(&0)
+    }
+
+    /// Every tile drawn.
+    pub fn tiles(&self) -> &SparseGrid<i64> {
+        &self.tiles
+    }
+
+    /// The last score displayed, if any.
+    pub fn score(&self) -> Option<i64> {
+        self.score
+    }
+
+    /// Count the tiles of a given kind.
+    pub fn count(&self, tile: i64) -> usize {
+        self.tiles.values().filter(|&&t| t == tile).count()
+    }
+
+    /// The position of some tile of a given kind.
+    pub fn find(&self, tile: i64) -> Option<Point> {
+        self.tiles.iter()
+            .find(|(_, &t)| t == tile)
+            .map(|(pos, _)| pos)
+    }
+
+    /// Draw the screen as text, with `glyph` picking a character per tile.
+    pub fn render<F: Fn(i64) -> char>(&self, glyph: F) -> String {
+        self.tiles.render(|t| glyph(*t.unwrap_or(&0)))
+    }
+}
+
+impl Device for Screen {
+    fn arity(&self) -> usize {
+        3
+    }
+
+    fn receive(&mut self, values: &[i64]) {
+        match (values[0], values[1]) {
+            (-1, 0) => self.score = Some(values[2]),
+            (x, y) => {
+                self.tiles.insert(Point::new(x, y), values[2]);
+            },
+        }
+    }
+}
+
+/// A robot on a grid, reporting the color under itself and receiving
+/// `(paint, turn)` messages: paint the current panel, turn left (0) or
+/// right (1), then move forward one panel.
+///
+/// Y grows downwards, as everywhere in `grid`, so moving up makes y
+/// smaller. Before the robot used that module, y grew upwards, and the
+/// positions it reported had the opposite sign of y to now.
+#[derive(Debug, Clone)]
+pub struct Turtle {
+    panels: SparseGrid<i64>,
+    position: Point,
+    facing: Direction,
+}
+
+impl Turtle {
+
+    /// Create a robot at the origin, facing up, standing on `start_color`.
+    pub fn new(start_color: i64) -> Turtle {
+        let mut panels = SparseGrid::new();
+        panels.insert(Point::origin(), start_color);
+
+        Turtle {
+            panels,
+            position: Point::origin(),
+            facing: Direction::Up,
+        }
+    }
+
+    /// Every panel visited or painted, with its color.
+    pub fn panels(&self) -> &SparseGrid<i64> {
+        &self.panels
+    }
+
+    /// Where the robot is standing, with y growing downwards.
+    pub fn position(&self) -> Point {
+        self.position
+    }
+
+    /// Draw the panels as text, top row first.
+    pub fn render<F: Fn(i64) -> char>(&self, glyph: F) -> String {
+        self.panels.render(|c| glyph(*c.unwrap_or(&0)))
+    }
+}
+
+impl Device for Turtle {
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn receive(&mut self, values: &[i64]) {
+        self.panels.insert(self.position, values[0]);
+
+        self.facing = if values[1] == 0 { self.facing.turn_left() } else { self.facing.turn_right() };
+        self.position = self.position.step(self.facing);
+    }
+
+    fn provide(&mut self) -> Option<i64> {
+        Some(*self.panels.get(self.position).unwrap_or(&0))
+    }
+}
@@ -0,0 +1,155 @@
+//! Comparing machine states, to find out what a program stores where.
+
+use std::fmt;
+
+use super::image::VmState;
+
+/// A memory cell whose value differs between two states.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellChange {
+    pub addr: usize,
+    pub old: i64,
+    pub new: i64,
+}
+
+impl CellChange {
+
+    /// How much the cell's value changed by.
+    pub fn delta(&self) -> i64 {
+        self.new.wrapping_sub(self.old)
+    }
+}
+
+/// Every difference between an earlier and a later state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryDiff {
+    pub changes: Vec<CellChange>,
+    pub instr_pointer: (usize, usize),
+    pub relative_base: (i64, i64),
+}
+
+impl MemoryDiff {
+
+    /// Whether memory is identical in both states.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Addresses of cells that changed by exactly `delta`.
+    pub fn changed_by(&self, delta: i64) -> Vec<usize> {
+        self.changes.iter()
+            .filter(|c| c.delta() == delta)
+            .map(|c| c.addr)
+            .collect()
+    }
+}
+
+impl fmt::Display for MemoryDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (ip_old, ip_new) = self.instr_pointer;
+        let (rb_old, rb_new) = self.relative_base;
+        writeln!(f, "ip: {} -> {}, rb: {} -> {}", ip_old, ip_new, rb_old, rb_new)?;
+
+        writeln!(f, "{:>6} | {:>20} | {:>20} | {:>20}", "addr", "old", "new", "delta")?;
+        writeln!(f, "{:-<7}+{:-<22}+{:-<22}+{:-<21}", "", "", "", "")?;
+        for c in &self.changes {
+            writeln!(f, "{:>6} | {:>20} | {:>20} | {:>+20}", c.addr, c.old, c.new, c.delta())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl VmState {
+
+    /// Compare this (earlier) state against a `later` one.
+    pub fn diff(&self, later: &VmState) -> MemoryDiff {
+        let len = self.memory.len().max(later.memory.len());
+        let cell = |m: &[i64], i: usize| m.get(i).copied().unwrap_or(0);
+
+        let changes = (0..len)
+            .map(|addr| CellChange {
+                addr,
+                old: cell(&self.memory, addr),
+                new: cell(&later.memory, addr),
+            })
+            .filter(|c| c.old != c.new)
+            .collect();
+
+        MemoryDiff {
+            changes,
+            instr_pointer: (self.instr_pointer, later.instr_pointer),
+            relative_base: (self.relative_base, later.relative_base),
+        }
+    }
+}
+
+/// Narrows down which cells hold a value of interest, by repeatedly
+/// observing states and discarding cells that don't behave as expected.
+pub struct MemorySearch {
+    candidates: Vec<usize>,
+    last: VmState,
+}
+
+impl MemorySearch {
+
+    /// Start a search with every cell of `state` as a candidate.
+    pub fn new(state: &VmState) -> MemorySearch {
+        MemorySearch {
+            candidates: (0..state.memory.len()).collect(),
+            last: state.clone(),
+        }
+    }
+
+    /// Addresses still under consideration.
+    pub fn candidates(&self) -> &[usize] {
+        &self.candidates
+    }
+
+    /// Keep cells that currently hold `value`.
+    pub fn equal_to(&mut self, state: &VmState, value: i64) -> &[usize] {
+        self.narrow(state, |_, new| new == value)
+    }
+
+    /// Keep cells that changed by exactly `delta` since the last observation.
+    pub fn changed_by(&mut self, state: &VmState, delta: i64) -> &[usize] {
+        self.narrow(state, |old, new| new.wrapping_sub(old) == delta)
+    }
+
+    /// Keep cells that changed since the last observation.
+    pub fn changed(&mut self, state: &VmState) -> &[usize] {
+        self.narrow(state, |old, new| old != new)
+    }
+
+    /// Keep cells that did not change since the last observation.
+    pub fn unchanged(&mut self, state: &VmState) -> &[usize] {
+        self.narrow(state, |old, new| old == new)
+    }
+
+    fn narrow<F: Fn(i64, i64) -> bool>(&mut self, state: &VmState, keep: F) -> &[usize] {
+        let last = &self.last.memory;
+        let cell = |m: &[i64], i: usize| m.get(i).copied().unwrap_or(0);
+        self.candidates.retain(|&a| keep(cell(last, a), cell(&state.memory, a)));
+        self.last = state.clone();
+
+        &self.candidates
+    }
+}
+
+/// Find cells whose value moved in step with `values` across `states`,
+/// i.e. every change in the cell matched the change in the tracked value.
+/// `states` and `values` must be the same length.
+pub fn find_tracking_cells(states: &[VmState], values: &[i64]) -> Vec<usize> {
+    assert_eq!(states.len(), values.len(), "need one value per state");
+
+    let mut search = match states.first() {
+        Some(first) => MemorySearch::new(first),
+        None => return Vec::new(),
+    };
+
+    for (state, pair) in states[1..].iter().zip(values.windows(2)) {
+        search.changed_by(state, pair[1].wrapping_sub(pair[0]));
+    }
+
+    search.candidates
+}
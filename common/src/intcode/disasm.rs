@@ -0,0 +1,467 @@
+//! Decoding programs back into instructions, and working out which
+//! cells are code by following the paths execution can take.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// An instruction's operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    Add,
+    Mul,
+    Input,
+    Output,
+    JumpIfTrue,
+    JumpIfFalse,
+    LessThan,
+    Equals,
+    AdjustBase,
+    Halt,
+}
+
+impl Opcode {
+
+    /// Decode the low two digits of an instruction.
+    pub fn from_value(value: i64) -> Option<Opcode> {
+        match value % 100 {
+            1 => Some(Opcode::Add),
+            2 => Some(Opcode::Mul),
+            3 => Some(Opcode::Input),
+            4 => Some(Opcode::Output),
+            5 => Some(Opcode::JumpIfTrue),
+            6 => Some(Opcode::JumpIfFalse),
+            7 => Some(Opcode::LessThan),
+            8 => Some(Opcode::Equals),
+            9 => Some(Opcode::AdjustBase),
+            99 => Some(Opcode::Halt),
+            _ => None,
+        }
+    }
+
+    pub fn value(self) -> i64 {
+        match self {
+            Opcode::Add => 1,
+            Opcode::Mul => 2,
+            Opcode::Input => 3,
+            Opcode::Output => 4,
+            Opcode::JumpIfTrue => 5,
+            Opcode::JumpIfFalse => 6,
+            Opcode::LessThan => 7,
+            Opcode::Equals => 8,
+            Opcode::AdjustBase => 9,
+            Opcode::Halt => 99,
+        }
+    }
+
+    /// The number of parameters taken.
+    pub fn arity(self) -> usize {
+        match self {
+            Opcode::Add | Opcode::Mul | Opcode::LessThan | Opcode::Equals => 3,
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse => 2,
+            Opcode::Input | Opcode::Output | Opcode::AdjustBase => 1,
+            Opcode::Halt => 0,
+        }
+    }
+
+    /// Whether the last parameter is written to.
+    pub fn writes(self) -> bool {
+        matches!(self, Opcode::Add | Opcode::Mul | Opcode::LessThan | Opcode::Equals | Opcode::Input)
+    }
+
+    /// The mnemonic used in listings.
+    pub fn mnemonic(self) -> &'static str {
+        match self {
+            Opcode::Add => "ADD",
+            Opcode::Mul => "MUL",
+            Opcode::Input => "IN",
+            Opcode::Output => "OUT",
+            Opcode::JumpIfTrue => "JNZ",
+            Opcode::JumpIfFalse => "JZ",
+            Opcode::LessThan => "LT",
+            Opcode::Equals => "EQ",
+            Opcode::AdjustBase => "ARB",
+            Opcode::Halt => "HALT",
+        }
+    }
+}
+
+/// An instruction parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Param {
+    /// The cell at an address.
+    Position(i64),
+    /// The value itself.
+    Immediate(i64),
+    /// The cell at an offset from the relative base.
+    Relative(i64),
+}
+
+impl Param {
+
+    fn decode(mode: i64, value: i64) -> Option<Param> {
+        match mode {
+            0 => Some(Param::Position(value)),
+            1 => Some(Param::Immediate(value)),
+            2 => Some(Param::Relative(value)),
+            _ => None,
+        }
+    }
+
+    pub fn mode(self) -> i64 {
+        match self {
+            Param::Position(_) => 0,
+            Param::Immediate(_) => 1,
+            Param::Relative(_) => 2,
+        }
+    }
+
+    /// The raw value stored in the instruction.
+    pub fn raw(self) -> i64 {
+        match self {
+            Param::Position(v) | Param::Immediate(v) | Param::Relative(v) => v,
+        }
+    }
+
+    /// The address referred to, for position parameters.
+    pub fn address(self) -> Option<usize> {
+        match self {
+            Param::Position(v) if v >= 0 => Some(v as usize),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Param {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Param::Position(v) => write!(f, "[{}]", v),
+            Param::Immediate(v) => write!(f, "#{}", v),
+            Param::Relative(v) if *v < 0 => write!(f, "[rb - {}]", v.unsigned_abs()),
+            Param::Relative(v) => write!(f, "[rb + {}]", v),
+        }
+    }
+}
+
+/// A decoded instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub addr: usize,
+    pub opcode: Opcode,
+    pub params: Vec<Param>,
+}
+
+impl Instruction {
+
+    /// Decode the instruction at `addr`, if it is a valid one that fits.
+    /// Like the machine, mode digits beyond the last parameter are ignored.
+    pub fn decode(memory: &[i64], addr: usize) -> Option<Instruction> {
+        let value = *memory.get(addr)?;
+        if value < 0 {
+            return None;
+        }
+
+        let opcode = Opcode::from_value(value)?;
+
+        let mut params = Vec::with_capacity(opcode.arity());
+        let mut modes = value / 100;
+        for i in 0..opcode.arity() {
+            params.push(Param::decode(modes % 10, *memory.get(addr + 1 + i)?)?);
+            modes /= 10;
+        }
+
+        Some(Instruction {
+            addr,
+            opcode,
+            params,
+        })
+    }
+
+    /// The number of cells taken.
+    pub fn size(&self) -> usize {
+        1 + self.params.len()
+    }
+
+    /// Encode back into cells.
+    pub fn encode(&self) -> Vec<i64> {
+        let modes = self.params.iter()
+            .rev()
+            .fold(0, |acc, p| acc * 10 + p.mode());
+
+        let mut out = vec![modes * 100 + self.opcode.value()];
+        out.extend(self.params.iter().map(|p| p.raw()));
+
+        out
+    }
+
+    /// The parameter written to, if any.
+    pub fn destination(&self) -> Option<Param> {
+        if self.opcode.writes() {
+            self.params.last().copied()
+        } else {
+            None
+        }
+    }
+
+    /// The parameters read from.
+    pub fn sources(&self) -> &[Param] {
+        if self.opcode.writes() {
+            &self.params[..self.params.len() - 1]
+        } else {
+            &self.params
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.opcode.mnemonic())?;
+        for (i, p) in self.params.iter().enumerate() {
+            write!(f, "{}{}", if i == 0 { " " } else { ", " }, p)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// What could be worked out about a program without running it.
+///
+/// Instructions are found by following execution from address 0.
+/// Jumps to a computed address can't be followed directly, so when a
+/// program has any, every immediate value that is a valid address is
+/// also assumed to be a possible target, as return addresses are
+/// usually pushed as immediates.
+///
+/// An instruction the program writes before running it could be any
+/// instruction, so execution is followed from each address it might
+/// end at or jump to, and its parameters are taken as possibly being
+/// addresses read and written.
+#[derive(Debug, Clone)]
+pub struct Disassembly {
+    program: Vec<i64>,
+    instructions: BTreeMap<usize, Instruction>,
+    code: Vec<bool>,
+    dynamic_jumps: bool,
+    relative: bool,
+    invalid: BTreeSet<usize>,
+    rewritten: BTreeSet<usize>,
+    reads: BTreeSet<usize>,
+    writes: BTreeSet<usize>,
+}
+
+impl Disassembly {
+
+    /// Analyze a program.
+    pub fn new(program: &[i64]) -> Disassembly {
+        let mut dis = Disassembly {
+            program: program.to_vec(),
+            instructions: BTreeMap::new(),
+            code: vec![false; program.len()],
+            dynamic_jumps: false,
+            relative: false,
+            invalid: BTreeSet::new(),
+            rewritten: BTreeSet::new(),
+            reads: BTreeSet::new(),
+            writes: BTreeSet::new(),
+        };
+
+        dis.explore(vec![0]);
+
+        // Retry from everywhere code written at run time could lead, and
+        // with every immediate as a possible jump target if there are
+        // computed jumps, until no new instructions turn up.
+        let mut tried = BTreeSet::new();
+        loop {
+            let rewritten: Vec<usize> = dis.invalid.iter()
+                .filter(|a| dis.writes.contains(a) && !dis.rewritten.contains(a))
+                .copied()
+                .collect();
+
+            let mut pending = Vec::new();
+            for addr in rewritten {
+                pending.extend(dis.rewrite(addr));
+            }
+
+            if dis.dynamic_jumps {
+                pending.extend(dis.instructions.values()
+                    .flat_map(|i| i.params.iter())
+                    .filter_map(|p| match p {
+                        Param::Immediate(v) => dis.address(*v),
+                        _ => None,
+                    })
+                    .filter(|&a| tried.insert(a)));
+            }
+
+            if pending.is_empty() {
+                break;
+            }
+            dis.explore(pending);
+        }
+
+        dis
+    }
+
+    /// `value` as an address within the program, if it is one.
+    fn address(&self, value: i64) -> Option<usize> {
+        if value >= 0 && (value as usize) < self.program.len() {
+            Some(value as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Records the instruction at `addr` as one written at run time,
+    /// returning where execution might go after it. Its parameters could
+    /// be used in any mode, so each is a possible address to read, write
+    /// or jump to, as is the value at that address.
+    fn rewrite(&mut self, addr: usize) -> Vec<usize> {
+        self.rewritten.insert(addr);
+
+        let mut next: Vec<usize> = (addr + 1..=addr + 4)
+            .filter(|&a| a < self.program.len())
+            .collect();
+
+        let params: Vec<usize> = self.program[addr + 1..self.program.len().min(addr + 4)].iter()
+            .filter_map(|&v| self.address(v))
+            .collect();
+        for a in params {
+            self.reads.insert(a);
+            self.writes.insert(a);
+            next.push(a);
+            next.extend(self.address(self.program[a]));
+        }
+
+        next
+    }
+
+    /// Follows execution from each of `pending`, recording instructions.
+    fn explore(&mut self, mut pending: Vec<usize>) {
+        while let Some(addr) = pending.pop() {
+            if !self.instructions.contains_key(&addr) {
+                self.visit(addr, &mut pending);
+            }
+        }
+    }
+
+    fn visit(&mut self, addr: usize, pending: &mut Vec<usize>) {
+        let instr = match Instruction::decode(&self.program, addr) {
+            Some(i) => i,
+            None => {
+                self.invalid.insert(addr);
+                return;
+            },
+        };
+
+        for cell in addr..addr + instr.size() {
+            self.code[cell] = true;
+        }
+        for p in instr.sources() {
+            if let Some(a) = p.address() {
+                self.reads.insert(a);
+            }
+        }
+        if let Some(a) = instr.destination().and_then(|p| p.address()) {
+            self.writes.insert(a);
+        }
+        self.relative |= instr.params.iter().any(|p| matches!(p, Param::Relative(_)));
+
+        let next = addr + instr.size();
+        match instr.opcode {
+            Opcode::Halt => {},
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse => {
+                let taken = match instr.params[0] {
+                    Param::Immediate(v) => Some((v != 0) == (instr.opcode == Opcode::JumpIfTrue)),
+                    _ => None,
+                };
+
+                if taken != Some(false) {
+                    match instr.params[1] {
+                        Param::Immediate(t) if t >= 0 => pending.push(t as usize),
+                        Param::Immediate(_) => {},
+                        _ => self.dynamic_jumps = true,
+                    }
+                }
+                if taken != Some(true) {
+                    pending.push(next);
+                }
+            },
+            _ => pending.push(next),
+        }
+
+        self.instructions.insert(addr, instr);
+    }
+
+    /// The program analyzed.
+    pub fn program(&self) -> &[i64] {
+        &self.program
+    }
+
+    /// Every reachable instruction, by address.
+    pub fn instructions(&self) -> &BTreeMap<usize, Instruction> {
+        &self.instructions
+    }
+
+    /// Whether the cell at `addr` is part of a reachable instruction.
+    pub fn is_code(&self, addr: usize) -> bool {
+        self.code.get(addr).copied().unwrap_or(false)
+    }
+
+    /// Whether any jump goes to a computed address.
+    pub fn has_dynamic_jumps(&self) -> bool {
+        self.dynamic_jumps
+    }
+
+    /// Whether any instruction uses relative addressing,
+    /// in which case reads and writes can't all be known.
+    pub fn uses_relative(&self) -> bool {
+        self.relative
+    }
+
+    /// Addresses execution reaches that don't hold a valid instruction.
+    /// Usually the program writes one there before getting that far.
+    pub fn invalid(&self) -> &BTreeSet<usize> {
+        &self.invalid
+    }
+
+    /// The addresses in `invalid` that the program writes to, where it
+    /// is assumed to put an instruction before running it.
+    pub fn rewritten(&self) -> &BTreeSet<usize> {
+        &self.rewritten
+    }
+
+    /// Addresses read through position parameters.
+    pub fn reads(&self) -> &BTreeSet<usize> {
+        &self.reads
+    }
+
+    /// Addresses written through position parameters.
+    pub fn writes(&self) -> &BTreeSet<usize> {
+        &self.writes
+    }
+
+    /// Whether the program writes into its own code. Only writes through
+    /// position parameters are known; relative writes are assumed to
+    /// stay out of code.
+    pub fn is_self_modifying(&self) -> bool {
+        self.writes.iter().any(|&a| self.is_code(a) || self.invalid.contains(&a))
+    }
+}
+
+impl fmt::Display for Disassembly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut addr = 0;
+        while addr < self.program.len() {
+            match self.instructions.get(&addr) {
+                Some(instr) => {
+                    writeln!(f, "{:>6}: {}", addr, instr)?;
+                    addr += instr.size();
+                },
+                None => {
+                    writeln!(f, "{:>6}: DATA {}", addr, self.program[addr])?;
+                    addr += 1;
+                },
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+//! Events describing what a machine is doing. Every subscriber gets its
+//! own copy of each event in a buffer of limited size, so observers
+//! don't take events from each other and one that stops reading can't
+//! grow without bound.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+use super::vm::{SelfModification, VmError};
+
+/// The number of events a subscription holds unless told otherwise.
+pub const DEFAULT_SUBSCRIPTION_CAPACITY: usize = 1024;
+
+/// Something that happened while stepping a machine.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// The first instruction since loading or resetting is about to run.
+    Started,
+    /// Input is needed; the instruction is retried on the next step.
+    NeedInput,
+    /// Input is needed but has been closed, so none will come.
+    EndOfInput,
+    /// A value was output.
+    Output(i64),
+    /// The output queue is at its capacity; the output is retried
+    /// on the next step.
+    OutputFull,
+    /// The program ran its halt instruction.
+    Halted,
+    /// The machine stopped on an error.
+    Error(VmError),
+    /// The instruction at the address is a breakpoint and wasn't run.
+    /// Stepping again runs it.
+    BreakpointHit(usize),
+    /// A write landed on code, under the `Report` self-modify policy.
+    SelfModified(SelfModification),
+}
+
+impl Event {
+
+    /// Whether the machine can't make progress until something outside
+    /// it changes.
+    pub fn is_stall(&self) -> bool {
+        matches!(self, Event::NeedInput | Event::EndOfInput | Event::OutputFull)
+    }
+}
+
+struct Buffer {
+    events: VecDeque<Event>,
+    capacity: usize,
+    missed: usize,
+}
+
+/// A machine's events, from the moment of subscribing.
+/// Once the buffer is full the oldest events are dropped.
+pub struct Subscription(Arc<Mutex<Buffer>>);
+
+impl Subscription {
+
+    fn lock(&self) -> MutexGuard<'_, Buffer> {
+        self.0.lock().unwrap()
+    }
+
+    /// Take the oldest event not yet taken, if there is one.
+    pub fn recv(&self) -> Option<Event> {
+        self.lock().events.pop_front()
+    }
+
+    /// Take every event not yet taken.
+    pub fn drain(&self) -> Vec<Event> {
+        self.lock().events.drain(..).collect()
+    }
+
+    /// Get the number of events waiting to be taken.
+    pub fn len(&self) -> usize {
+        self.lock().events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().events.is_empty()
+    }
+
+    /// The number of events dropped because the buffer was full.
+    pub fn missed(&self) -> usize {
+        self.lock().missed
+    }
+}
+
+/// The subscriptions to a machine. Dropped subscriptions are forgotten
+/// the next time an event is sent.
+#[derive(Default)]
+pub(super) struct Subscribers(Vec<Weak<Mutex<Buffer>>>);
+
+impl Subscribers {
+
+    pub(super) fn subscribe(&mut self, capacity: usize) -> Subscription {
+        assert!(capacity > 0, "subscription capacity must be at least 1");
+
+        let buffer = Arc::new(Mutex::new(Buffer {
+            events: VecDeque::new(),
+            capacity,
+            missed: 0,
+        }));
+        self.0.push(Arc::downgrade(&buffer));
+
+        Subscription(buffer)
+    }
+
+    /// Hand a copy of `event` to every subscription still alive.
+    pub(super) fn send(&mut self, event: &Event) {
+        self.0.retain(|buffer| {
+            let buffer = match buffer.upgrade() {
+                Some(buffer) => buffer,
+                None => return false,
+            };
+
+            let mut buffer = buffer.lock().unwrap();
+            if buffer.events.len() >= buffer.capacity {
+                buffer.events.pop_front();
+                buffer.missed += 1;
+            }
+            buffer.events.push_back(event.clone());
+
+            true
+        });
+    }
+}
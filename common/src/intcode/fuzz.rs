@@ -0,0 +1,640 @@
+//! Differential fuzzing: random well-formed programs are run on the
+//! virtual machine and on a small reference interpreter written straight
+//! from the puzzle descriptions, and the two must end up in the same
+//! state. A program they disagree on is shrunk to a minimal one.
+//!
+//! Everything is seeded, so runs are repeatable and work offline.
+//! `INTCODE_FUZZ_CASES` and `INTCODE_FUZZ_SEED` change how many
+//! programs are tried and where the sequence starts.
+
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+
+use super::event::Event;
+use super::vm::{IntcodeVM, MEMORY_SIZE};
+
+/// Steps allowed before a program is assumed not to halt.
+const MAX_STEPS: usize = 10_000;
+
+/// A xorshift generator; plenty for picking instructions.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in `lo..=hi`.
+    fn range(&mut self, lo: i64, hi: i64) -> i64 {
+        lo + (self.next() % (hi - lo + 1) as u64) as i64
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn chance(&mut self, percent: u64) -> bool {
+        self.next() % 100 < percent
+    }
+}
+
+/// A value read by an instruction. Positions index the data cells
+/// placed after the code; relative offsets start out pointing there too.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Src {
+    Position(usize),
+    Immediate(i64),
+    Relative(i64),
+}
+
+/// A cell written by an instruction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Dst {
+    Position(usize),
+    Relative(i64),
+}
+
+/// A piece of a generated program. Jumps only come from `Skip` and
+/// `Loop`, so every target is the start of an instruction.
+#[derive(Debug, Clone, PartialEq)]
+enum Op {
+    /// Add, multiply, less than or equals.
+    Arith(i64, Src, Src, Dst),
+    Input(Dst),
+    Output(Src),
+    AdjustBase(i64),
+    /// Jumps over the body when the condition is non-zero, or zero.
+    Skip(Src, bool, Vec<Op>),
+    /// Runs the body a number of times, counting down a cell
+    /// of its own past the data cells.
+    Loop(i64, Vec<Op>),
+}
+
+impl Op {
+    fn size(&self) -> usize {
+        match self {
+            Op::Arith(..) => 4,
+            Op::Input(_) | Op::Output(_) | Op::AdjustBase(_) => 2,
+            Op::Skip(_, _, body) => 3 + size(body),
+            Op::Loop(_, body) => 4 + size(body) + 4 + 3,
+        }
+    }
+}
+
+fn size(ops: &[Op]) -> usize {
+    ops.iter().map(Op::size).sum()
+}
+
+/// A generated program along with what it starts with.
+#[derive(Debug, Clone, PartialEq)]
+struct Case {
+    ops: Vec<Op>,
+    data: Vec<i64>,
+    inputs: Vec<i64>,
+}
+
+impl Case {
+
+    fn generate(rng: &mut Rng) -> Case {
+        let data: Vec<i64> = (0..rng.range(1, 8)).map(|_| rng.range(-5, 20)).collect();
+        let len = rng.range(1, 12) as usize;
+        let ops = generate_block(rng, data.len(), len, 0);
+        let inputs = (0..rng.range(0, 6)).map(|_| rng.range(-10, 10)).collect();
+
+        Case { ops, data, inputs }
+    }
+
+    /// Lays the program out as cells: moving the relative base onto the
+    /// data, the code, a halt, the data, then the loop counters.
+    fn program(&self) -> Vec<i64> {
+        let data_start = 2 + size(&self.ops) + 1;
+        let mut out = vec![109, data_start as i64];
+        let mut counters = data_start + self.data.len();
+
+        compile(&self.ops, data_start, &mut counters, &mut out);
+        out.push(99);
+        out.extend(&self.data);
+        out.resize(counters, 0);
+
+        out
+    }
+
+    /// Smaller variations on this case, for shrinking.
+    fn shrinks(&self) -> Vec<Case> {
+        let mut out: Vec<Case> = shrink_ops(&self.ops)
+            .into_iter()
+            .map(|ops| Case { ops, ..self.clone() })
+            .collect();
+
+        if !self.inputs.is_empty() {
+            let inputs = self.inputs[..self.inputs.len() - 1].to_vec();
+            out.push(Case { inputs, ..self.clone() });
+        }
+        for i in 0..self.data.len() {
+            if self.data[i] != 0 {
+                let mut data = self.data.clone();
+                data[i] = 0;
+                out.push(Case { data, ..self.clone() });
+            }
+        }
+
+        out
+    }
+}
+
+fn generate_src(rng: &mut Rng, data: usize) -> Src {
+    match rng.below(3) {
+        0 => Src::Position(rng.below(data)),
+        1 => Src::Immediate(rng.range(-10, 10)),
+        _ => Src::Relative(rng.below(data) as i64),
+    }
+}
+
+fn generate_dst(rng: &mut Rng, data: usize) -> Dst {
+    if rng.chance(50) {
+        Dst::Position(rng.below(data))
+    } else {
+        Dst::Relative(rng.below(data) as i64)
+    }
+}
+
+fn generate_block(rng: &mut Rng, data: usize, len: usize, depth: usize) -> Vec<Op> {
+    (0..len)
+        .map(|_| {
+            let roll = rng.below(100);
+            match roll {
+                _ if roll < 10 && depth < 2 => {
+                    let len = rng.range(1, 4) as usize;
+                    let body = generate_block(rng, data, len, depth + 1);
+                    Op::Skip(generate_src(rng, data), rng.chance(50), body)
+                },
+                _ if roll < 20 && depth < 2 => {
+                    let len = rng.range(1, 4) as usize;
+                    let body = generate_block(rng, data, len, depth + 1);
+                    Op::Loop(rng.range(1, 4), body)
+                },
+                _ if roll < 28 => Op::Input(generate_dst(rng, data)),
+                _ if roll < 43 => Op::Output(generate_src(rng, data)),
+                _ if roll < 50 => Op::AdjustBase(rng.range(-2, 2)),
+                _ => {
+                    let opcode = [1, 2, 7, 8][rng.below(4)];
+                    Op::Arith(opcode, generate_src(rng, data), generate_src(rng, data), generate_dst(rng, data))
+                },
+            }
+        })
+        .collect()
+}
+
+fn encode_src(src: Src, data_start: usize) -> (i64, i64) {
+    match src {
+        Src::Position(i) => (0, (data_start + i) as i64),
+        Src::Immediate(v) => (1, v),
+        Src::Relative(o) => (2, o),
+    }
+}
+
+fn encode_dst(dst: Dst, data_start: usize) -> (i64, i64) {
+    match dst {
+        Dst::Position(i) => (0, (data_start + i) as i64),
+        Dst::Relative(o) => (2, o),
+    }
+}
+
+fn compile(ops: &[Op], data_start: usize, counters: &mut usize, out: &mut Vec<i64>) {
+    for op in ops {
+        match op {
+            Op::Arith(opcode, a, b, d) => {
+                let (ma, a) = encode_src(*a, data_start);
+                let (mb, b) = encode_src(*b, data_start);
+                let (md, d) = encode_dst(*d, data_start);
+                out.extend([md * 10000 + mb * 1000 + ma * 100 + opcode, a, b, d]);
+            },
+            Op::Input(d) => {
+                let (md, d) = encode_dst(*d, data_start);
+                out.extend([md * 100 + 3, d]);
+            },
+            Op::Output(s) => {
+                let (ms, s) = encode_src(*s, data_start);
+                out.extend([ms * 100 + 4, s]);
+            },
+            Op::AdjustBase(n) => out.extend([109, *n]),
+            Op::Skip(cond, when, body) => {
+                let (mc, c) = encode_src(*cond, data_start);
+                let opcode = if *when { 5 } else { 6 };
+                let after = out.len() + 3 + size(body);
+                out.extend([1000 + mc * 100 + opcode, c, after as i64]);
+                compile(body, data_start, counters, out);
+            },
+            Op::Loop(count, body) => {
+                let counter = *counters as i64;
+                *counters += 1;
+
+                out.extend([1101, *count, 0, counter]);
+                let start = out.len() as i64;
+                compile(body, data_start, counters, out);
+                out.extend([1001, counter, -1, counter]);
+                out.extend([1005, counter, start]);
+            },
+        }
+    }
+}
+
+/// Every way of making one piece of `ops` simpler.
+fn shrink_ops(ops: &[Op]) -> Vec<Vec<Op>> {
+    let mut out = Vec::new();
+    let replace = |i: usize, with: Vec<Op>| {
+        let mut v = ops[..i].to_vec();
+        v.extend(with);
+        v.extend_from_slice(&ops[i + 1..]);
+        v
+    };
+    let simpler = |src: Src| match src {
+        Src::Immediate(0) => None,
+        _ => Some(Src::Immediate(0)),
+    };
+
+    for (i, op) in ops.iter().enumerate() {
+        out.push(replace(i, vec![]));
+
+        match op {
+            Op::Skip(cond, when, body) => {
+                out.push(replace(i, body.clone()));
+                out.extend(shrink_ops(body).into_iter().map(|b| replace(i, vec![Op::Skip(*cond, *when, b)])));
+            },
+            Op::Loop(count, body) => {
+                out.push(replace(i, body.clone()));
+                if *count > 1 {
+                    out.push(replace(i, vec![Op::Loop(1, body.clone())]));
+                }
+                out.extend(shrink_ops(body).into_iter().map(|b| replace(i, vec![Op::Loop(*count, b)])));
+            },
+            Op::Arith(opcode, a, b, d) => {
+                if let Some(a) = simpler(*a) {
+                    out.push(replace(i, vec![Op::Arith(*opcode, a, *b, *d)]));
+                }
+                if let Some(b) = simpler(*b) {
+                    out.push(replace(i, vec![Op::Arith(*opcode, *a, b, *d)]));
+                }
+            },
+            Op::Output(s) => {
+                if let Some(s) = simpler(*s) {
+                    out.push(replace(i, vec![Op::Output(s)]));
+                }
+            },
+            Op::Input(_) | Op::AdjustBase(_) => {},
+        }
+    }
+
+    out
+}
+
+/// Repeatedly takes the first simpler case that still fails,
+/// until none do.
+fn shrink<F: Fn(&Case) -> bool>(mut case: Case, fails: F) -> Case {
+    'outer: loop {
+        for candidate in case.shrinks() {
+            if fails(&candidate) {
+                case = candidate;
+                continue 'outer;
+            }
+        }
+
+        return case;
+    }
+}
+
+/// Why a run stopped.
+#[derive(Debug, Clone, PartialEq)]
+enum Stop {
+    Halted,
+    NeedInput,
+    StepLimit,
+    /// Something the puzzles leave undefined, like a bad opcode,
+    /// an address out of range or overflow.
+    Fault(String),
+}
+
+/// Everything observable once a run stops.
+#[derive(Debug, Clone, PartialEq)]
+struct Outcome {
+    stop: Stop,
+    outputs: Vec<i64>,
+    memory: Vec<i64>,
+    instr_pointer: usize,
+    relative_base: i64,
+}
+
+impl Outcome {
+
+    /// The first way two outcomes differ.
+    fn difference(&self, other: &Outcome) -> Option<String> {
+        if self.stop != other.stop {
+            return Some(format!("stopped with {:?} and {:?}", self.stop, other.stop));
+        }
+        if self.outputs != other.outputs {
+            return Some(format!("output {:?} and {:?}", self.outputs, other.outputs));
+        }
+        // Where the pointer is left after halting doesn't matter,
+        // but a machine waiting for input must resume in the right place.
+        if self.stop != Stop::Halted && self.instr_pointer != other.instr_pointer {
+            return Some(format!("instruction pointer {} and {}", self.instr_pointer, other.instr_pointer));
+        }
+        if self.relative_base != other.relative_base {
+            return Some(format!("relative base {} and {}", self.relative_base, other.relative_base));
+        }
+
+        let cells = self.memory.iter().zip(&other.memory).enumerate();
+        for (addr, (a, b)) in cells {
+            if a != b {
+                return Some(format!("memory at {}: {} and {}", addr, a, b));
+            }
+        }
+
+        None
+    }
+}
+
+/// The machine as the puzzles describe it, favouring
+/// obviousness over speed.
+struct Reference {
+    memory: Vec<i64>,
+    ip: usize,
+    rb: i64,
+    inputs: VecDeque<i64>,
+    outputs: Vec<i64>,
+}
+
+impl Reference {
+
+    fn new(program: &[i64], inputs: &[i64]) -> Reference {
+        let mut memory = vec![0; MEMORY_SIZE];
+        memory[..program.len()].copy_from_slice(program);
+
+        Reference {
+            memory,
+            ip: 0,
+            rb: 0,
+            inputs: inputs.iter().copied().collect(),
+            outputs: Vec::new(),
+        }
+    }
+
+    fn run(mut self, max_steps: usize) -> Outcome {
+        let mut stop = Stop::StepLimit;
+        for _ in 0..max_steps {
+            match self.step() {
+                Ok(None) => {},
+                Ok(Some(s)) => {
+                    stop = s;
+                    break;
+                },
+                Err(e) => {
+                    stop = Stop::Fault(e);
+                    break;
+                },
+            }
+        }
+
+        Outcome {
+            stop,
+            outputs: self.outputs,
+            memory: self.memory,
+            instr_pointer: self.ip,
+            relative_base: self.rb,
+        }
+    }
+
+    fn cell(&self, addr: i64) -> Result<usize, String> {
+        if addr < 0 || addr as usize >= MEMORY_SIZE {
+            return Err(format!("address {} out of range", addr));
+        }
+
+        Ok(addr as usize)
+    }
+
+    /// The address parameter `n` of the current instruction refers to.
+    fn address(&self, n: usize) -> Result<usize, String> {
+        let instr = self.memory[self.ip];
+        let raw = self.memory[self.cell(self.ip as i64 + n as i64)?];
+        match instr / [100, 1000, 10000][n - 1] % 10 {
+            0 => self.cell(raw),
+            2 => self.cell(self.rb + raw),
+            m => Err(format!("mode {} has no address", m)),
+        }
+    }
+
+    fn read(&self, n: usize) -> Result<i64, String> {
+        let instr = self.memory[self.ip];
+        if instr / [100, 1000, 10000][n - 1] % 10 == 1 {
+            return Ok(self.memory[self.cell(self.ip as i64 + n as i64)?]);
+        }
+
+        Ok(self.memory[self.address(n)?])
+    }
+
+    fn write(&mut self, n: usize, value: i64) -> Result<(), String> {
+        let addr = self.address(n)?;
+        self.memory[addr] = value;
+
+        Ok(())
+    }
+
+    fn step(&mut self) -> Result<Option<Stop>, String> {
+        let instr = self.memory[self.cell(self.ip as i64)?];
+        if instr < 0 {
+            return Err(format!("negative instruction {}", instr));
+        }
+
+        let overflow = || "overflow".to_string();
+        match instr % 100 {
+            1 => {
+                let v = self.read(1)?.checked_add(self.read(2)?).ok_or_else(overflow)?;
+                self.write(3, v)?;
+                self.ip += 4;
+            },
+            2 => {
+                let v = self.read(1)?.checked_mul(self.read(2)?).ok_or_else(overflow)?;
+                self.write(3, v)?;
+                self.ip += 4;
+            },
+            3 => {
+                let v = match self.inputs.pop_front() {
+                    Some(v) => v,
+                    None => return Ok(Some(Stop::NeedInput)),
+                };
+                self.write(1, v)?;
+                self.ip += 2;
+            },
+            4 => {
+                let v = self.read(1)?;
+                self.outputs.push(v);
+                self.ip += 2;
+            },
+            5 | 6 => {
+                let jump = (self.read(1)? != 0) == (instr % 100 == 5);
+                let target = self.read(2)?;
+                self.ip = if jump { self.cell(target)? } else { self.ip + 3 };
+            },
+            7 => {
+                let v = (self.read(1)? < self.read(2)?) as i64;
+                self.write(3, v)?;
+                self.ip += 4;
+            },
+            8 => {
+                let v = (self.read(1)? == self.read(2)?) as i64;
+                self.write(3, v)?;
+                self.ip += 4;
+            },
+            9 => {
+                self.rb = self.rb.checked_add(self.read(1)?).ok_or_else(overflow)?;
+                self.ip += 2;
+            },
+            99 => return Ok(Some(Stop::Halted)),
+            op => return Err(format!("unknown opcode {}", op)),
+        }
+
+        Ok(None)
+    }
+}
+
+/// Runs a program on the virtual machine, stepping it
+/// exactly as the reference interpreter counts steps.
+fn run_vm(program: &[i64], inputs: &[i64], max_steps: usize) -> Outcome {
+    let mut vm = IntcodeVM::new().with_program_slice(program);
+    let io = vm.io();
+    for &input in inputs {
+        io.send(input);
+    }
+
+    let mut stop = Stop::StepLimit;
+    for _ in 0..max_steps {
+        match vm.step() {
+            Some(Event::Halted) => {
+                stop = Stop::Halted;
+                break;
+            },
+            Some(Event::NeedInput) => {
+                stop = Stop::NeedInput;
+                break;
+            },
+            Some(Event::Error(e)) => {
+                stop = Stop::Fault(format!("{:?}", e));
+                break;
+            },
+            _ => {},
+        }
+    }
+
+    Outcome {
+        stop,
+        outputs: io.dump(),
+        memory: vm.dump_memory(0..MEMORY_SIZE),
+        instr_pointer: vm.instr_pointer(),
+        relative_base: vm.relative_base(),
+    }
+}
+
+/// How the machine disagrees with the reference on a program, if it does.
+/// Programs whose behavior isn't defined, or that don't stop, are skipped.
+fn disagreement(program: &[i64], inputs: &[i64]) -> Option<String> {
+    let expected = Reference::new(program, inputs).run(MAX_STEPS);
+    if matches!(expected.stop, Stop::Fault(_) | Stop::StepLimit) {
+        return None;
+    }
+
+    let actual = panic::catch_unwind(AssertUnwindSafe(|| run_vm(program, inputs, MAX_STEPS)));
+    match actual {
+        Ok(actual) => expected.difference(&actual),
+        Err(_) => Some("the machine panicked".to_string()),
+    }
+}
+
+fn env_or(name: &str, default: u64) -> u64 {
+    std::env::var(name).ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+#[test]
+fn reference_runs_examples() {
+    // Day 9's quine, and day 5's comparison against 8.
+    let quine = [109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99];
+    let outcome = Reference::new(&quine, &[]).run(MAX_STEPS);
+    assert_eq!(Stop::Halted, outcome.stop);
+    assert_eq!(quine.to_vec(), outcome.outputs);
+
+    let program = [3,9,8,9,10,9,4,9,99,-1,8];
+    assert_eq!(vec![1], Reference::new(&program, &[8]).run(MAX_STEPS).outputs);
+    assert_eq!(vec![0], Reference::new(&program, &[7]).run(MAX_STEPS).outputs);
+    assert_eq!(Stop::NeedInput, Reference::new(&program, &[]).run(MAX_STEPS).stop);
+
+    assert_eq!(None, disagreement(&quine, &[]));
+    assert_eq!(None, disagreement(&program, &[8]));
+}
+
+#[test]
+fn machine_matches_reference() {
+    let cases = env_or("INTCODE_FUZZ_CASES", 500);
+    let seed = env_or("INTCODE_FUZZ_SEED", 1);
+
+    let mut checked = 0;
+    for n in seed..seed + cases {
+        let case = Case::generate(&mut Rng::new(n));
+        let program = case.program();
+        let expected = Reference::new(&program, &case.inputs).run(MAX_STEPS);
+        if matches!(expected.stop, Stop::Fault(_) | Stop::StepLimit) {
+            continue;
+        }
+
+        checked += 1;
+        if disagreement(&program, &case.inputs).is_none() {
+            continue;
+        }
+
+        let minimal = shrink(case, |c| disagreement(&c.program(), &c.inputs).is_some());
+        let program = minimal.program();
+        panic!(
+            "seed {}: {}\nprogram: {:?}\ninputs: {:?}",
+            n,
+            disagreement(&program, &minimal.inputs).unwrap(),
+            program,
+            minimal.inputs,
+        );
+    }
+
+    // Most programs should be well-defined enough to compare.
+    assert!(checked * 2 > cases, "only {} of {} programs checked", checked, cases);
+}
+
+#[test]
+fn shrinking_finds_minimal_programs() {
+    let outputs = |c: &Case| Reference::new(&c.program(), &c.inputs).run(MAX_STEPS).outputs;
+
+    // The smallest program that outputs anything outputs a constant.
+    let case = (1..)
+        .map(|n| Case::generate(&mut Rng::new(n)))
+        .find(|c| size(&c.ops) > 20 && !outputs(c).is_empty())
+        .unwrap();
+    let minimal = shrink(case, |c| !outputs(c).is_empty());
+    assert_eq!(vec![Op::Output(Src::Immediate(0))], minimal.ops);
+    assert!(minimal.inputs.is_empty());
+
+    // Loops shrink to a single pass, and bodies to what matters.
+    let case = Case {
+        ops: vec![
+            Op::Input(Dst::Position(0)),
+            Op::Loop(3, vec![Op::AdjustBase(1), Op::Output(Src::Relative(0)), Op::Output(Src::Immediate(4))]),
+        ],
+        data: vec![1, 2, 3, 4],
+        inputs: vec![5],
+    };
+    let minimal = shrink(case, |c| outputs(c).len() >= 2);
+    assert_eq!(vec![Op::Output(Src::Immediate(0)), Op::Output(Src::Immediate(0))], minimal.ops);
+}
@@ -0,0 +1,525 @@
+//! A GDB Remote Serial Protocol stub, so Intcode programs can be debugged
+//! with an off-the-shelf debugger front-end.
+//!
+//! The debugger sees memory as bytes, with each cell stored as eight
+//! little-endian bytes; cell `n` lives at address `n * 8`. There are two
+//! 64-bit registers: `ip` (as a byte address, like every other address)
+//! and `rb`, the relative base as-is.
+
+use std::collections::HashSet;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+use super::event::Event;
+use super::vm::IntcodeVM;
+
+/// Bytes per memory cell, as seen by the debugger.
+const CELL: usize = 8;
+
+/// Instructions executed between checks for an interrupt while continuing.
+const INTERRUPT_INTERVAL: usize = 4096;
+
+const TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+  <feature name="org.intcode.core">
+    <reg name="ip" bitsize="64" type="code_ptr" regnum="0"/>
+    <reg name="rb" bitsize="64" type="int64" regnum="1"/>
+  </feature>
+</target>
+"#;
+
+/// A connection to a debugger.
+pub trait RemoteStream: Read + Write {
+
+    /// Whether the debugger has sent an interrupt (Ctrl-C) while the
+    /// program is running. Must not block.
+    fn interrupted(&mut self) -> bool {
+        false
+    }
+}
+
+impl RemoteStream for TcpStream {
+    fn interrupted(&mut self) -> bool {
+        let mut byte = [0];
+        let _ = self.set_nonblocking(true);
+        let peeked = self.peek(&mut byte);
+        let _ = self.set_nonblocking(false);
+
+        if let Ok(1) = peeked {
+            if byte[0] == 0x03 {
+                let _ = self.read(&mut byte);
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(unix)]
+impl RemoteStream for std::os::unix::net::UnixStream {
+    fn interrupted(&mut self) -> bool {
+        let mut byte = [0];
+        let _ = self.set_nonblocking(true);
+        let read = self.read(&mut byte);
+        let _ = self.set_nonblocking(false);
+
+        // Anything but an interrupt is dropped; debuggers don't send
+        // other packets while waiting for a stop reply.
+        matches!(read, Ok(1)) && byte[0] == 0x03
+    }
+}
+
+/// Why the program stopped running.
+enum Stop {
+    /// Single step finished, breakpoint hit, or waiting on input.
+    Trap,
+    /// Stopped by the debugger.
+    Interrupt,
+    /// The machine refused to continue.
+    Fault,
+    /// The program halted.
+    Exited,
+}
+
+impl Stop {
+    fn reply(&self) -> &'static str {
+        match self {
+            Stop::Trap => "S05",
+            Stop::Interrupt => "S02",
+            Stop::Fault => "S0b",
+            Stop::Exited => "W00",
+        }
+    }
+}
+
+/// Serves a single debugger session for an `IntcodeVM`.
+pub struct GdbStub {
+    vm: IntcodeVM,
+    breakpoints: HashSet<usize>,
+    no_ack: bool,
+    exited: bool,
+}
+
+impl GdbStub {
+
+    /// Wrap a machine, which should already have its program loaded.
+    pub fn new(vm: IntcodeVM) -> GdbStub {
+        GdbStub {
+            vm,
+            breakpoints: HashSet::new(),
+            no_ack: false,
+            exited: false,
+        }
+    }
+
+    /// Get a reference to the wrapped machine.
+    pub fn vm(&self) -> &IntcodeVM {
+        &self.vm
+    }
+
+    /// Get a mutable reference to the wrapped machine.
+    pub fn vm_mut(&mut self) -> &mut IntcodeVM {
+        &mut self.vm
+    }
+
+    /// Unwrap the machine once debugging is done.
+    pub fn into_vm(self) -> IntcodeVM {
+        self.vm
+    }
+
+    /// Wait for a single debugger to connect over TCP, then serve it.
+    pub fn listen_tcp<A: ToSocketAddrs>(&mut self, addr: A) -> io::Result<()> {
+        let listener = TcpListener::bind(addr)?;
+        let (stream, _) = listener.accept()?;
+        stream.set_nodelay(true)?;
+
+        self.serve(stream)
+    }
+
+    /// Serve a debugger over `stream` until it kills or detaches,
+    /// or closes the connection.
+    pub fn serve<S: RemoteStream>(&mut self, mut stream: S) -> io::Result<()> {
+        self.no_ack = false;
+
+        while let Some(packet) = read_packet(&mut stream, self.no_ack)? {
+            let packet = match packet {
+                Packet::Data(data) => data,
+                Packet::Interrupt => {
+                    write_packet(&mut stream, Stop::Interrupt.reply())?;
+                    continue;
+                },
+            };
+
+            match self.handle(&packet, &mut stream) {
+                Some(reply) => write_packet(&mut stream, &reply)?,
+                None => return Ok(()),
+            }
+
+            if packet == "QStartNoAckMode" {
+                self.no_ack = true;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handle one packet, returning the reply,
+    /// or `None` if the session is over.
+    fn handle<S: RemoteStream>(&mut self, packet: &str, stream: &mut S) -> Option<String> {
+        let split = packet.char_indices().nth(1).map_or(packet.len(), |(i, _)| i);
+        let (cmd, args) = packet.split_at(split);
+
+        let reply = match cmd {
+            "?" => self.stop_reply(Stop::Trap),
+            "g" => {
+                let ip = (self.vm.instr_pointer() * CELL) as u64;
+                format!("{}{}", hex_u64(ip), hex_u64(self.vm.relative_base() as u64))
+            },
+            "G" => match (parse_hex_u64(args.get(..16)), parse_hex_u64(args.get(16..32))) {
+                (Some(ip), Some(rb)) if self.in_memory(ip) => {
+                    self.vm.set_instr_pointer(ip as usize / CELL);
+                    self.vm.set_relative_base(rb as i64);
+                    "OK".to_string()
+                },
+                _ => "E01".to_string(),
+            },
+            "p" => match usize::from_str_radix(args, 16) {
+                Ok(0) => hex_u64((self.vm.instr_pointer() * CELL) as u64),
+                Ok(1) => hex_u64(self.vm.relative_base() as u64),
+                _ => "E01".to_string(),
+            },
+            "P" => {
+                let mut parts = args.splitn(2, '=');
+                let reg = parts.next().and_then(|r| usize::from_str_radix(r, 16).ok());
+                match (reg, parse_hex_u64(parts.next())) {
+                    (Some(0), Some(v)) if self.in_memory(v) => {
+                        self.vm.set_instr_pointer(v as usize / CELL);
+                        "OK".to_string()
+                    },
+                    (Some(1), Some(v)) => {
+                        self.vm.set_relative_base(v as i64);
+                        "OK".to_string()
+                    },
+                    _ => "E01".to_string(),
+                }
+            },
+            "m" => match parse_addr_len(args) {
+                Some((addr, len)) => self.read_bytes(addr, len)
+                    .map(|bytes| bytes.iter().map(|b| format!("{:02x}", b)).collect())
+                    .unwrap_or_else(|| "E01".to_string()),
+                None => "E01".to_string(),
+            },
+            "M" => {
+                let mut parts = args.splitn(2, ':');
+                let range = parts.next().and_then(parse_addr_len);
+                let data = parts.next().and_then(parse_hex_bytes);
+                match (range, data) {
+                    (Some((addr, len)), Some(data)) if data.len() == len && self.write_bytes(addr, &data) =>
+                        "OK".to_string(),
+                    _ => "E01".to_string(),
+                }
+            },
+            "s" => {
+                let stop = self.step();
+                self.stop_reply(stop)
+            },
+            "c" => {
+                let stop = self.resume(stream);
+                self.stop_reply(stop)
+            },
+            "Z" | "z" => self.breakpoint(cmd == "Z", args),
+            "H" => "OK".to_string(),
+            "k" => return None,
+            "D" => {
+                // Acknowledge before closing.
+                let _ = write_packet(stream, "OK");
+                return None;
+            },
+            _ => self.query(packet),
+        };
+
+        Some(reply)
+    }
+
+    /// Whether the byte address `addr` falls within the machine's memory.
+    fn in_memory(&self, addr: u64) -> bool {
+        addr / (CELL as u64) < self.vm.memory_size() as u64
+    }
+
+    /// Replies to general queries; unsupported ones get an empty reply.
+    fn query(&self, packet: &str) -> String {
+        if packet.starts_with("qSupported") {
+            return "PacketSize=4000;qXfer:features:read+;QStartNoAckMode+".to_string();
+        }
+
+        if let Some(range) = packet.strip_prefix("qXfer:features:read:target.xml:") {
+            return match parse_addr_len(range) {
+                Some((offset, len)) if offset <= TARGET_XML.len() => {
+                    let end = (offset + len).min(TARGET_XML.len());
+                    let marker = if end == TARGET_XML.len() { 'l' } else { 'm' };
+                    format!("{}{}", marker, &TARGET_XML[offset..end])
+                },
+                _ => "E01".to_string(),
+            };
+        }
+
+        match packet {
+            "QStartNoAckMode" => "OK",
+            "qAttached" => "1",
+            "qC" => "QC1",
+            "qfThreadInfo" => "m1",
+            "qsThreadInfo" => "l",
+            _ => "",
+        }.to_string()
+    }
+
+    /// Adds or removes a software breakpoint.
+    fn breakpoint(&mut self, insert: bool, args: &str) -> String {
+        let mut parts = args.split(',');
+        let kind = parts.next();
+        let addr = parts.next().and_then(|a| usize::from_str_radix(a, 16).ok());
+
+        match (kind, addr) {
+            (Some("0"), Some(addr)) => {
+                if insert {
+                    self.breakpoints.insert(addr / CELL);
+                } else {
+                    self.breakpoints.remove(&(addr / CELL));
+                }
+
+                "OK".to_string()
+            },
+            // Only software breakpoints are supported.
+            _ => String::new(),
+        }
+    }
+
+    fn stop_reply(&self, stop: Stop) -> String {
+        if self.exited {
+            Stop::Exited.reply().to_string()
+        } else {
+            stop.reply().to_string()
+        }
+    }
+
+    /// Execute a single instruction.
+    fn step(&mut self) -> Stop {
+        if self.exited {
+            return Stop::Exited;
+        }
+
+        match self.vm.step() {
+            Some(Event::Halted) => {
+                self.exited = true;
+                Stop::Exited
+            },
+            Some(Event::Error(_)) => Stop::Fault,
+            _ => Stop::Trap,
+        }
+    }
+
+    /// Run until a breakpoint, a halt, missing input or an interrupt.
+    fn resume<S: RemoteStream>(&mut self, stream: &mut S) -> Stop {
+        let mut steps = 0;
+
+        loop {
+            match self.vm.step() {
+                Some(Event::Halted) => {
+                    self.exited = true;
+                    return Stop::Exited;
+                },
+                Some(Event::Error(_)) => return Stop::Fault,
+                Some(e) if e.is_stall() => {
+                    return Stop::Trap;
+                },
+                _ => {},
+            }
+
+            if self.breakpoints.contains(&self.vm.instr_pointer()) {
+                return Stop::Trap;
+            }
+
+            steps += 1;
+            if steps % INTERRUPT_INTERVAL == 0 && stream.interrupted() {
+                return Stop::Interrupt;
+            }
+        }
+    }
+
+    /// Reads `len` bytes of memory starting at byte address `addr`.
+    fn read_bytes(&self, addr: usize, len: usize) -> Option<Vec<u8>> {
+        let end = addr.checked_add(len)?;
+        if end > self.vm.memory_size() * CELL {
+            return None;
+        }
+        if len == 0 {
+            return Some(Vec::new());
+        }
+
+        let cells = self.vm.dump_memory(addr / CELL..end.div_ceil(CELL));
+        let bytes: Vec<u8> = cells.iter().flat_map(|c| c.to_le_bytes().to_vec()).collect();
+        let start = addr % CELL;
+
+        Some(bytes[start..start + len].to_vec())
+    }
+
+    /// Writes `data` to memory starting at byte address `addr`.
+    fn write_bytes(&mut self, addr: usize, data: &[u8]) -> bool {
+        let mut bytes = match self.read_bytes(addr - addr % CELL, round_up(addr % CELL + data.len())) {
+            Some(bytes) => bytes,
+            None => return false,
+        };
+
+        let start = addr % CELL;
+        bytes[start..start + data.len()].copy_from_slice(data);
+
+        let cells: Vec<i64> = bytes.chunks(CELL)
+            .map(|c| {
+                let mut buf = [0; CELL];
+                buf.copy_from_slice(c);
+                i64::from_le_bytes(buf)
+            })
+            .collect();
+        self.vm.patch_memory(addr / CELL, &cells);
+
+        true
+    }
+}
+
+/// Rounds `len` up to a whole number of cells' worth of bytes.
+fn round_up(len: usize) -> usize {
+    len.div_ceil(CELL) * CELL
+}
+
+enum Packet {
+    Data(String),
+    Interrupt,
+}
+
+/// Reads the next packet, acknowledging it unless in no-ack mode.
+/// Returns `None` once the stream is closed.
+fn read_packet<S: Read + Write>(stream: &mut S, no_ack: bool) -> io::Result<Option<Packet>> {
+    let mut byte = [0];
+
+    loop {
+        // Skip acks and noise until the start of a packet.
+        loop {
+            if stream.read(&mut byte)? == 0 {
+                return Ok(None);
+            }
+
+            match byte[0] {
+                b'$' => break,
+                0x03 => return Ok(Some(Packet::Interrupt)),
+                _ => {},
+            }
+        }
+
+        let mut data = Vec::new();
+        loop {
+            if stream.read(&mut byte)? == 0 {
+                return Ok(None);
+            }
+
+            match byte[0] {
+                b'#' => break,
+                b => data.push(b),
+            }
+        }
+
+        let mut checksum = [0; 2];
+        stream.read_exact(&mut checksum)?;
+        let expected = std::str::from_utf8(&checksum).ok()
+            .and_then(|c| u8::from_str_radix(c, 16).ok());
+
+        if no_ack || expected == Some(checksum_of(&data)) {
+            if !no_ack {
+                stream.write_all(b"+")?;
+            }
+
+            return Ok(Some(Packet::Data(unescape(&data))));
+        }
+
+        // Ask for a retransmission.
+        stream.write_all(b"-")?;
+    }
+}
+
+/// Sends a packet. Acknowledgements from the debugger are skipped by
+/// `read_packet`, so a corrupted reply is not retransmitted.
+fn write_packet<S: Write>(stream: &mut S, data: &str) -> io::Result<()> {
+    let mut escaped = Vec::with_capacity(data.len());
+    for &b in data.as_bytes() {
+        match b {
+            b'$' | b'#' | b'}' | b'*' => escaped.extend_from_slice(&[b'}', b ^ 0x20]),
+            b => escaped.push(b),
+        }
+    }
+
+    let mut packet = Vec::with_capacity(escaped.len() + 4);
+    packet.push(b'$');
+    packet.extend_from_slice(&escaped);
+    packet.extend_from_slice(format!("#{:02x}", checksum_of(&escaped)).as_bytes());
+
+    stream.write_all(&packet)?;
+    stream.flush()
+}
+
+fn checksum_of(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, b| sum.wrapping_add(*b))
+}
+
+fn unescape(data: &[u8]) -> String {
+    let mut out = Vec::with_capacity(data.len());
+    let mut bytes = data.iter();
+    while let Some(&b) = bytes.next() {
+        if b == b'}' {
+            if let Some(&next) = bytes.next() {
+                out.push(next ^ 0x20);
+            }
+        } else {
+            out.push(b);
+        }
+    }
+
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// Formats a register value as little-endian hex bytes.
+fn hex_u64(v: u64) -> String {
+    v.to_le_bytes().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Parses little-endian hex bytes into a register value.
+fn parse_hex_u64(s: Option<&str>) -> Option<u64> {
+    let bytes = parse_hex_bytes(s?)?;
+    if bytes.len() != 8 {
+        return None;
+    }
+
+    let mut buf = [0; 8];
+    buf.copy_from_slice(&bytes);
+
+    Some(u64::from_le_bytes(buf))
+}
+
+fn parse_hex_bytes(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..s.len()).step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Parses an `addr,length` pair of hex numbers.
+fn parse_addr_len(s: &str) -> Option<(usize, usize)> {
+    let mut parts = s.splitn(2, ',');
+    let addr = usize::from_str_radix(parts.next()?, 16).ok()?;
+    let len = usize::from_str_radix(parts.next()?, 16).ok()?;
+
+    Some((addr, len))
+}
@@ -0,0 +1,222 @@
+//! Snapshots of a machine's memory and registers, and a compact
+//! binary image format for storing them.
+//!
+//! Image layout (all integers little-endian):
+//! - magic `b"ICVM"`
+//! - version `u16`, flags `u16` (reserved, zero)
+//! - instruction pointer `u64`, relative base `i64`
+//! - cell count `u64`
+//! - cells, each a zigzag LEB128 varint; trailing zero cells are omitted
+//! - CRC-32 `u32` of everything before it
+
+use std::fmt;
+use std::io::{Read, Write};
+
+use super::vm::MEMORY_SIZE;
+
+const MAGIC: &[u8; 4] = b"ICVM";
+const VERSION: u16 = 1;
+const HEADER_LEN: usize = 4 + 2 + 2 + 8 + 8 + 8;
+
+/// A snapshot of a machine's memory, instruction pointer and relative base.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VmState {
+    pub memory: Vec<i64>,
+    pub instr_pointer: usize,
+    pub relative_base: i64,
+}
+
+/// An error produced when reading a memory image.
+#[derive(Debug)]
+pub enum ImageError {
+    Io(std::io::Error),
+    BadMagic,
+    UnsupportedVersion(u16),
+    BadChecksum { expected: u32, found: u32 },
+    Truncated,
+    TooLarge(u64),
+    /// The instruction pointer is past the end of memory.
+    BadInstrPointer(u64),
+    /// Bytes left over between the last cell and the checksum.
+    TrailingBytes(usize),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::Io(e) => write!(f, "unable to read image: {}", e),
+            ImageError::BadMagic => write!(f, "not an Intcode memory image"),
+            ImageError::UnsupportedVersion(v) => write!(f, "unsupported image version {}", v),
+            ImageError::BadChecksum { expected, found } =>
+                write!(f, "checksum mismatch: expected {:08x}, found {:08x}", expected, found),
+            ImageError::Truncated => write!(f, "image is truncated"),
+            ImageError::TooLarge(n) =>
+                write!(f, "image holds {} cells, more than the {} available", n, MEMORY_SIZE),
+            ImageError::BadInstrPointer(ip) =>
+                write!(f, "instruction pointer {} is outside the {} cells of memory", ip, MEMORY_SIZE),
+            ImageError::TrailingBytes(n) => write!(f, "image has {} unexpected bytes after its cells", n),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+impl From<std::io::Error> for ImageError {
+    fn from(e: std::io::Error) -> ImageError {
+        ImageError::Io(e)
+    }
+}
+
+impl VmState {
+
+    /// Encode the state as a binary memory image.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let cells = &self.memory[..used_len(&self.memory)];
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + cells.len() * 2 + 4);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+        bytes.extend_from_slice(&(self.instr_pointer as u64).to_le_bytes());
+        bytes.extend_from_slice(&self.relative_base.to_le_bytes());
+        bytes.extend_from_slice(&(cells.len() as u64).to_le_bytes());
+
+        for &cell in cells {
+            write_varint(&mut bytes, cell);
+        }
+
+        let checksum = crc32(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+
+        bytes
+    }
+
+    /// Decode a binary memory image.
+    /// Memory is zero-extended to the machine's full size.
+    pub fn from_bytes(bytes: &[u8]) -> Result<VmState, ImageError> {
+        if bytes.len() < 4 || &bytes[..4] != MAGIC {
+            return Err(ImageError::BadMagic);
+        }
+        if bytes.len() < HEADER_LEN + 4 {
+            return Err(ImageError::Truncated);
+        }
+
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != VERSION {
+            return Err(ImageError::UnsupportedVersion(version));
+        }
+
+        let (body, checksum) = bytes.split_at(bytes.len() - 4);
+        let expected = u32::from_le_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]);
+        let found = crc32(body);
+        if expected != found {
+            return Err(ImageError::BadChecksum { expected, found });
+        }
+
+        let instr_pointer = read_u64(&body[8..16]);
+        let relative_base = read_u64(&body[16..24]) as i64;
+        let count = read_u64(&body[24..32]);
+        if count > MEMORY_SIZE as u64 {
+            return Err(ImageError::TooLarge(count));
+        }
+        if instr_pointer >= MEMORY_SIZE as u64 {
+            return Err(ImageError::BadInstrPointer(instr_pointer));
+        }
+
+        let mut memory = vec![0; MEMORY_SIZE];
+        let mut payload = &body[HEADER_LEN..];
+        for cell in memory.iter_mut().take(count as usize) {
+            let (value, len) = read_varint(payload).ok_or(ImageError::Truncated)?;
+            *cell = value;
+            payload = &payload[len..];
+        }
+        if !payload.is_empty() {
+            return Err(ImageError::TrailingBytes(payload.len()));
+        }
+
+        Ok(VmState {
+            memory,
+            instr_pointer: instr_pointer as usize,
+            relative_base,
+        })
+    }
+
+    /// Write the state as a binary memory image.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writer.write_all(&self.to_bytes())
+    }
+
+    /// Read a binary memory image.
+    pub fn read_from<R: Read>(mut reader: R) -> Result<VmState, ImageError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        VmState::from_bytes(&bytes)
+    }
+
+    /// Export every cell of memory in the usual comma-separated program form.
+    pub fn to_text(&self) -> String {
+        join_cells(&self.memory)
+    }
+
+    /// Export memory like `to_text`, but leave off trailing zero cells,
+    /// which loading the program fills back in.
+    pub fn to_text_trimmed(&self) -> String {
+        join_cells(&self.memory[..used_len(&self.memory).max(1)])
+    }
+}
+
+fn join_cells(cells: &[i64]) -> String {
+    cells.iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Number of cells up to and including the last non-zero one.
+fn used_len(memory: &[i64]) -> usize {
+    memory.iter().rposition(|&c| c != 0).map_or(0, |i| i + 1)
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    let mut buf = [0; 8];
+    buf.copy_from_slice(bytes);
+
+    u64::from_le_bytes(buf)
+}
+
+/// Append `value` as a zigzag LEB128 varint.
+fn write_varint(out: &mut Vec<u8>, value: i64) {
+    let mut v = ((value << 1) ^ (value >> 63)) as u64;
+    while v >= 0x80 {
+        out.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+/// Read a zigzag LEB128 varint, returning it and the number of bytes used.
+fn read_varint(bytes: &[u8]) -> Option<(i64, usize)> {
+    let mut v = 0u64;
+    for (i, &b) in bytes.iter().enumerate().take(10) {
+        v |= ((b & 0x7F) as u64) << (7 * i);
+        if b & 0x80 == 0 {
+            return Some((((v >> 1) as i64) ^ -((v & 1) as i64), i + 1));
+        }
+    }
+
+    None
+}
+
+/// CRC-32 (IEEE), computed bitwise since images are small.
+pub(super) fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & 0u32.wrapping_sub(crc & 1));
+        }
+    }
+
+    !crc
+}
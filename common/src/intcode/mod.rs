@@ -1,5 +1,35 @@
 #[cfg(test)]
 mod tests;
+mod async_vm;
+mod device;
+mod diff;
+mod event;
+mod decompile;
+mod disasm;
+#[cfg(test)]
+mod fuzz;
+mod gdb;
+mod image;
+mod optimize;
+mod parse;
+mod queue;
+mod scheduler;
+mod solver;
+mod symbolic;
 mod vm;
 
-pub use vm::{IntcodeVM, Message, default_input};
\ No newline at end of file
+pub use async_vm::{AsyncIOHandle, RunFuture};
+pub use device::{Bus, BusError, Device, Screen, Turtle};
+pub use diff::{find_tracking_cells, CellChange, MemoryDiff, MemorySearch};
+pub use event::{Event, Subscription, DEFAULT_SUBSCRIPTION_CAPACITY};
+pub use decompile::{Decompilation, Function};
+pub use disasm::{Disassembly, Instruction, Opcode, Param};
+pub use gdb::{GdbStub, RemoteStream};
+pub use image::{ImageError, VmState};
+pub use optimize::{Optimized, Optimizer};
+pub use queue::TrySendError;
+pub use parse::{parse_program, parse_program_file, LoadError, ParseError, ParseErrorKind};
+pub use scheduler::{MachineState, Outbox, Scheduler, SchedulerError, VmId};
+pub use solver::{Constraint, LinExpr, Model, Rel, SolveResult, Solver, Var};
+pub use symbolic::{PathEnd, SymbolicExplorer, SymbolicPath};
+pub use vm::{IntcodeVM, SelfModification, SelfModifyPolicy, VmError, default_input};
@@ -0,0 +1,285 @@
+//! Rewriting programs into simpler equivalents: folding arithmetic on
+//! constants, and removing cells that can never be executed or read.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::disasm::{Disassembly, Instruction, Opcode, Param};
+
+/// The result of optimizing a program.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Optimized {
+    pub program: Vec<i64>,
+    /// Instructions rewritten into a simpler form.
+    pub folded: usize,
+    /// Non-zero cells removed as unreachable.
+    pub removed: usize,
+    /// Where each kept cell moved to, if the program was compacted.
+    pub relocations: Option<BTreeMap<usize, usize>>,
+}
+
+/// Rewrites programs, by default keeping every cell at its address so
+/// the result can be patched and inspected like the original.
+#[derive(Debug, Clone, Default)]
+pub struct Optimizer {
+    compact: bool,
+}
+
+impl Optimizer {
+
+    /// Create an optimizer that preserves addresses.
+    pub fn new() -> Optimizer {
+        Optimizer::default()
+    }
+
+    /// Also close the gaps left by removed cells, relocating addresses,
+    /// when the program is simple enough for that to be safe: no relative
+    /// addressing, no computed jumps and no writes into code that runs
+    /// after them.
+    /// Programs that aren't keep every cell, compacted or not.
+    pub fn with_compaction(mut self, compact: bool) -> Optimizer {
+        self.compact = compact;
+        self
+    }
+
+    /// Optimize a program. Folding is local, so it goes ahead even in
+    /// programs whose reads, writes or jumps can't all be known, skipping
+    /// the code they come from. Removing cells isn't, so those programs
+    /// keep every cell.
+    pub fn optimize(&self, program: &[i64]) -> Optimized {
+        let (mut out, folded) = fold(program);
+
+        let dis = Disassembly::new(&out);
+        if unknown(&dis) || modifies_code(&dis) {
+            return Optimized {
+                program: out,
+                folded,
+                removed: 0,
+                relocations: None,
+            };
+        }
+
+        let kept = |a: usize| dis.is_code(a) || dis.reads().contains(&a) || dis.writes().contains(&a);
+        let removed = (0..out.len()).filter(|&a| !kept(a) && out[a] != 0).count();
+
+        if self.compact {
+            let (program, relocations) = compact(&dis, kept);
+            return Optimized {
+                program,
+                folded,
+                removed,
+                relocations: Some(relocations),
+            };
+        }
+
+        for (a, cell) in out.iter_mut().enumerate() {
+            if !kept(a) {
+                *cell = 0;
+            }
+        }
+        while out.len() > 1 && out.last() == Some(&0) {
+            out.pop();
+        }
+
+        Optimized {
+            program: out,
+            folded,
+            removed,
+            relocations: None,
+        }
+    }
+}
+
+/// Whether a program uses relative addressing, computed jumps or code
+/// it writes at run time, so its reads and writes can't all be known.
+fn unknown(dis: &Disassembly) -> bool {
+    !dis.invalid().is_empty() || dis.has_dynamic_jumps() || dis.uses_relative()
+}
+
+/// Whether the program writes into code that may run after the write.
+/// Without jumps, execution only moves forwards, so writes into the
+/// writing instruction or the code before it are as good as data.
+fn modifies_code(dis: &Disassembly) -> bool {
+    let jumps = dis.instructions().values()
+        .any(|i| matches!(i.opcode, Opcode::JumpIfTrue | Opcode::JumpIfFalse));
+    if jumps {
+        return dis.is_self_modifying();
+    }
+
+    dis.instructions().values().any(|i| {
+        i.destination()
+            .and_then(|d| d.address())
+            .is_some_and(|a| a >= i.addr + i.size() && (dis.is_code(a) || dis.invalid().contains(&a)))
+    })
+}
+
+/// Rewrites reachable instructions computing a constant, or doing
+/// nothing, into their simplest form. Instructions the program reads
+/// or writes as data are left alone, as are those whose result would
+/// land in code and those in `unknown_blocks`.
+fn fold(program: &[i64]) -> (Vec<i64>, usize) {
+    let dis = Disassembly::new(program);
+    let mut out = program.to_vec();
+    let mut folded = 0;
+
+    let skipped = unknown_blocks(&dis);
+    let touched: BTreeSet<usize> = dis.reads().union(dis.writes()).copied().collect();
+
+    let instructions = dis.instructions();
+    for instr in instructions.values() {
+        let cells = instr.addr..instr.addr + instr.size();
+        if skipped.contains(&instr.addr) || cells.clone().any(|a| touched.contains(&a)) {
+            continue;
+        }
+
+        // Leave anything sharing cells with code written at run time.
+        let rewritten = dis.rewritten().range(instr.addr.saturating_sub(3)..cells.end).next().is_some();
+        if rewritten {
+            continue;
+        }
+
+        // Leave instructions that overlap another, as jumping into
+        // the middle of this one would run different code.
+        let overlapped = instructions.range(instr.addr + 1..cells.end).next().is_some()
+            || instructions.range(..instr.addr).next_back().is_some_and(|(_, i)| i.addr + i.size() > instr.addr);
+        if overlapped {
+            continue;
+        }
+
+        let dest = match instr.destination() {
+            Some(d) if d.address().is_none_or(|a| !dis.is_code(a) && !dis.invalid().contains(&a)) => d,
+            _ => continue,
+        };
+
+        let replacement = match simplify(instr, dest) {
+            Some(r) => r,
+            None => continue,
+        };
+
+        let cells = replacement.encode();
+        if cells[..] != out[instr.addr..instr.addr + cells.len()] {
+            out[instr.addr..instr.addr + cells.len()].copy_from_slice(&cells);
+            folded += 1;
+        }
+    }
+
+    (out, folded)
+}
+
+/// The instructions in straight runs of code that use relative
+/// addressing or end in a computed jump. Where those go can't be known,
+/// so the instructions around them are left alone, as the likeliest to
+/// be read or jumped into. Elsewhere in the program, relative reads are
+/// assumed to stay out of code, and computed jumps to land on the start
+/// of an instruction.
+fn unknown_blocks(dis: &Disassembly) -> BTreeSet<usize> {
+    let mut skipped = BTreeSet::new();
+    let mut block = Vec::new();
+    let mut unknown = false;
+
+    let mut instructions = dis.instructions().values().peekable();
+    while let Some(instr) = instructions.next() {
+        let jumps = matches!(instr.opcode, Opcode::JumpIfTrue | Opcode::JumpIfFalse);
+
+        block.push(instr.addr);
+        unknown |= instr.params.iter().any(|p| matches!(p, Param::Relative(_)))
+            || (jumps && !matches!(instr.params[1], Param::Immediate(_)));
+
+        let end = instr.addr + instr.size();
+        let ends = jumps || instr.opcode == Opcode::Halt;
+        if ends || instructions.peek().is_none_or(|i| i.addr != end) {
+            if unknown {
+                skipped.extend(block.iter().copied());
+            }
+            block.clear();
+            unknown = false;
+        }
+    }
+
+    skipped
+}
+
+/// The simplest instruction of the same size with the same effect.
+fn simplify(instr: &Instruction, dest: Param) -> Option<Instruction> {
+    let store = |value: i64| Instruction {
+        addr: instr.addr,
+        opcode: Opcode::Add,
+        params: vec![Param::Immediate(value), Param::Immediate(0), dest],
+    };
+
+    let (a, b) = match instr.sources() {
+        [a, b] => (*a, *b),
+        _ => return None,
+    };
+
+    if let (Param::Immediate(x), Param::Immediate(y)) = (a, b) {
+        let value = match instr.opcode {
+            Opcode::Add => x.checked_add(y)?,
+            Opcode::Mul => x.checked_mul(y)?,
+            Opcode::LessThan => (x < y) as i64,
+            Opcode::Equals => (x == y) as i64,
+            _ => return None,
+        };
+
+        return Some(store(value));
+    }
+
+    match (instr.opcode, a, b) {
+        (Opcode::Mul, Param::Immediate(0), _) | (Opcode::Mul, _, Param::Immediate(0)) => Some(store(0)),
+
+        // Copying a cell onto itself does nothing; jump over the write.
+        (Opcode::Add, x, Param::Immediate(0)) | (Opcode::Add, Param::Immediate(0), x) |
+        (Opcode::Mul, x, Param::Immediate(1)) | (Opcode::Mul, Param::Immediate(1), x) if x == dest => {
+            Some(Instruction {
+                addr: instr.addr,
+                opcode: Opcode::JumpIfFalse,
+                params: vec![Param::Immediate(0), Param::Immediate((instr.addr + instr.size()) as i64)],
+            })
+        },
+
+        // Comparing a cell against itself.
+        (Opcode::LessThan, x, y) if x == y => Some(store(0)),
+        (Opcode::Equals, x, y) if x == y => Some(store(1)),
+
+        _ => None,
+    }
+}
+
+/// Packs the kept cells together, fixing up addresses to match.
+fn compact<F: Fn(usize) -> bool>(dis: &Disassembly, kept: F) -> (Vec<i64>, BTreeMap<usize, usize>) {
+    let program = dis.program();
+    let relocations: BTreeMap<usize, usize> = (0..program.len())
+        .filter(|&a| kept(a))
+        .enumerate()
+        .map(|(new, old)| (old, new))
+        .collect();
+
+    // Addresses past the end of the program keep their distance from it.
+    let shift = program.len() - relocations.len();
+    let relocate = |addr: i64| -> i64 {
+        if addr < 0 {
+            addr
+        } else if let Some(&new) = relocations.get(&(addr as usize)) {
+            new as i64
+        } else {
+            addr - shift as i64
+        }
+    };
+
+    let mut out: Vec<i64> = relocations.keys().map(|&old| program[old]).collect();
+    for instr in dis.instructions().values() {
+        let mut moved = instr.clone();
+        for (i, p) in moved.params.iter_mut().enumerate() {
+            let is_target = i == 1 && matches!(instr.opcode, Opcode::JumpIfTrue | Opcode::JumpIfFalse);
+            *p = match *p {
+                Param::Position(a) => Param::Position(relocate(a)),
+                Param::Immediate(t) if is_target => Param::Immediate(relocate(t)),
+                other => other,
+            };
+        }
+
+        let at = relocations[&instr.addr];
+        out[at..at + moved.size()].copy_from_slice(&moved.encode());
+    }
+
+    (out, relocations)
+}
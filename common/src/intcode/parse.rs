@@ -0,0 +1,170 @@
+//! Parsing of Intcode programs from their comma-separated text form.
+//! Whitespace (including newlines) may appear between values, a single
+//! trailing comma is allowed, and `#` starts a comment running to the
+//! end of the line.
+
+use std::fmt;
+use std::num::ParseIntError;
+use std::path::Path;
+
+/// The reason a program failed to parse.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    /// The program contained no values at all.
+    Empty,
+    /// Two commas with nothing between them.
+    EmptyToken,
+    /// Two values separated only by whitespace.
+    MissingSeparator,
+    /// A token that is not a valid `i64`.
+    InvalidValue(String, ParseIntError),
+}
+
+/// An error produced by `parse_program`, pointing at the offending token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// Index of the comma-separated token that failed.
+    pub token: usize,
+    /// Byte offset into the source text.
+    pub offset: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ParseErrorKind::Empty => write!(f, "program contains no values"),
+            ParseErrorKind::EmptyToken =>
+                write!(f, "empty value at token {} (byte {})", self.token, self.offset),
+            ParseErrorKind::MissingSeparator =>
+                write!(f, "missing ',' before token {} (byte {})", self.token, self.offset),
+            ParseErrorKind::InvalidValue(s, e) =>
+                write!(f, "invalid value {:?} at token {} (byte {}): {}", s, self.token, self.offset, e),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// An error produced when loading a program from a file.
+#[derive(Debug)]
+pub enum LoadError {
+    Io(std::io::Error),
+    Parse(ParseError),
+    /// The program has more values than fit in memory.
+    TooLarge { len: usize },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "unable to read program: {}", e),
+            LoadError::Parse(e) => write!(f, "unable to parse program: {}", e),
+            LoadError::TooLarge { len } => write!(f, "program of {} values does not fit in memory", len),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<std::io::Error> for LoadError {
+    fn from(e: std::io::Error) -> LoadError {
+        LoadError::Io(e)
+    }
+}
+
+impl From<ParseError> for LoadError {
+    fn from(e: ParseError) -> LoadError {
+        LoadError::Parse(e)
+    }
+}
+
+/// Parse a program from its text form, returning it as a `Vec<i64>`.
+pub fn parse_program(program: &str) -> Result<Vec<i64>, ParseError> {
+    let mut values = Vec::with_capacity(program.len() / 2);
+    let mut token = 0;
+
+    // Byte span of the value in the current token, and whether
+    // whitespace has been seen since the value started.
+    let mut span: Option<(usize, usize)> = None;
+    let mut gap = false;
+    let mut trailing_comma = false;
+
+    let mut chars = program.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '#' => {
+                // Skip the rest of the line.
+                for (_, c) in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+
+                if span.is_some() {
+                    gap = true;
+                }
+            },
+            ',' => {
+                let (start, end) = span.ok_or(ParseError {
+                    token,
+                    offset: i,
+                    kind: ParseErrorKind::EmptyToken,
+                })?;
+                values.push(parse_value(&program[start..end], token, start)?);
+
+                token += 1;
+                span = None;
+                gap = false;
+                trailing_comma = true;
+            },
+            c if c.is_whitespace() => {
+                if span.is_some() {
+                    gap = true;
+                }
+            },
+            c => {
+                trailing_comma = false;
+                span = match span {
+                    Some(_) if gap => {
+                        return Err(ParseError {
+                            token,
+                            offset: i,
+                            kind: ParseErrorKind::MissingSeparator,
+                        });
+                    },
+                    Some((start, _)) => Some((start, i + c.len_utf8())),
+                    None => Some((i, i + c.len_utf8())),
+                };
+            },
+        }
+    }
+
+    if let Some((start, end)) = span {
+        values.push(parse_value(&program[start..end], token, start)?);
+    } else if !trailing_comma {
+        return Err(ParseError {
+            token,
+            offset: program.len(),
+            kind: ParseErrorKind::Empty,
+        });
+    }
+
+    Ok(values)
+}
+
+/// Read and parse a program from the file at `path`.
+pub fn parse_program_file<P: AsRef<Path>>(path: P) -> Result<Vec<i64>, LoadError> {
+    let program = std::fs::read_to_string(path)?;
+
+    Ok(parse_program(&program)?)
+}
+
+/// Parse a single token's value.
+fn parse_value(s: &str, token: usize, offset: usize) -> Result<i64, ParseError> {
+    s.parse::<i64>().map_err(|e| ParseError {
+        token,
+        offset,
+        kind: ParseErrorKind::InvalidValue(s.to_string(), e),
+    })
+}
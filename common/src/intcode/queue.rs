@@ -0,0 +1,171 @@
+//! The queues carrying a machine's input and output. Unlike a plain
+//! channel they can be looked into without taking anything, closed to
+//! mark the end of the data, and limited in size.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+
+/// Why `IOHandle::try_send` couldn't queue a value, handing it back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrySendError<T> {
+    /// The queue is at its capacity.
+    Full(T),
+    /// The queue has been closed.
+    Closed(T),
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => write!(f, "queue is full"),
+            TrySendError::Closed(_) => write!(f, "queue is closed"),
+        }
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for TrySendError<T> {}
+
+struct State<T> {
+    values: VecDeque<T>,
+    capacity: Option<usize>,
+    closed: bool,
+}
+
+/// A queue shared between a machine and its handles; clones refer
+/// to the same values.
+pub(super) struct Queue<T>(Arc<(Mutex<State<T>>, Condvar)>);
+
+impl<T> Clone for Queue<T> {
+    fn clone(&self) -> Queue<T> {
+        Queue(self.0.clone())
+    }
+}
+
+impl<T> Queue<T> {
+
+    pub(super) fn new() -> Queue<T> {
+        let state = State {
+            values: VecDeque::new(),
+            capacity: None,
+            closed: false,
+        };
+
+        Queue(Arc::new((Mutex::new(state), Condvar::new())))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.0.0.lock().unwrap()
+    }
+
+    /// Limit the number of values held, or lift the limit with `None`.
+    pub(super) fn set_capacity(&self, capacity: Option<usize>) {
+        assert!(capacity != Some(0), "queue capacity must be at least 1");
+        self.lock().capacity = capacity;
+        self.0.1.notify_all();
+    }
+
+    /// Add a value, waiting for room if the queue is full.
+    /// Hands the value back if the queue is or becomes closed.
+    pub(super) fn push(&self, value: T) -> Result<(), T> {
+        let mut state = self.lock();
+        while !state.closed && state.capacity.is_some_and(|c| state.values.len() >= c) {
+            state = self.0.1.wait(state).unwrap();
+        }
+        if state.closed {
+            return Err(value);
+        }
+
+        state.values.push_back(value);
+        self.0.1.notify_all();
+
+        Ok(())
+    }
+
+    /// Add a value if there is room.
+    pub(super) fn try_push(&self, value: T) -> Result<(), TrySendError<T>> {
+        let mut state = self.lock();
+        if state.closed {
+            return Err(TrySendError::Closed(value));
+        }
+        if state.capacity.is_some_and(|c| state.values.len() >= c) {
+            return Err(TrySendError::Full(value));
+        }
+
+        state.values.push_back(value);
+        self.0.1.notify_all();
+
+        Ok(())
+    }
+
+    /// Take the next value, if there is one.
+    pub(super) fn pop(&self) -> Option<T> {
+        let value = self.lock().values.pop_front();
+        if value.is_some() {
+            self.0.1.notify_all();
+        }
+
+        value
+    }
+
+    /// Take the next value, waiting for one unless the queue is closed.
+    pub(super) fn wait_pop(&self) -> Option<T> {
+        let mut state = self.lock();
+        while state.values.is_empty() && !state.closed {
+            state = self.0.1.wait(state).unwrap();
+        }
+
+        let value = state.values.pop_front();
+        self.0.1.notify_all();
+
+        value
+    }
+
+    /// Take every value.
+    pub(super) fn drain(&self) -> Vec<T> {
+        let values = self.lock().values.drain(..).collect();
+        self.0.1.notify_all();
+
+        values
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.lock().values.len()
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.lock().values.is_empty()
+    }
+
+    pub(super) fn is_full(&self) -> bool {
+        let state = self.lock();
+        state.capacity.is_some_and(|c| state.values.len() >= c)
+    }
+
+    /// Stop accepting values. Those already queued can still be taken.
+    pub(super) fn close(&self) {
+        self.lock().closed = true;
+        self.0.1.notify_all();
+    }
+
+    pub(super) fn reopen(&self) {
+        self.lock().closed = false;
+    }
+
+    pub(super) fn is_closed(&self) -> bool {
+        self.lock().closed
+    }
+}
+
+impl<T: Clone> Queue<T> {
+
+    /// The next value, left in the queue.
+    pub(super) fn peek(&self) -> Option<T> {
+        self.lock().values.front().cloned()
+    }
+
+    /// Every value, left in the queue.
+    pub(super) fn snapshot(&self) -> Vec<T> {
+        self.lock().values.iter().cloned().collect()
+    }
+}
@@ -0,0 +1,239 @@
+//! Running several machines together on one thread, such as day 7's
+//! amplifier feedback loop, with outputs delivered in a fixed order.
+
+use std::fmt;
+
+use super::event::Event;
+use super::vm::{IOHandle, IntcodeVM, VmError};
+
+/// Identifies a machine added to a `Scheduler`.
+pub type VmId = usize;
+
+/// What a scheduled machine is doing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MachineState {
+    /// Able to run.
+    Ready,
+    /// Blocked on input that hasn't arrived.
+    Waiting,
+    /// Finished normally.
+    Halted,
+    /// Stopped on an error.
+    Failed(VmError),
+}
+
+/// Why a `Scheduler` stopped before every machine halted.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchedulerError {
+    /// Every machine still running is waiting on input nobody will send.
+    Deadlock(Vec<VmId>),
+    /// A machine stopped on an error.
+    Vm(VmId, VmError),
+}
+
+impl fmt::Display for SchedulerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchedulerError::Deadlock(ids) => write!(f, "deadlock: machines {:?} are all waiting on input", ids),
+            SchedulerError::Vm(id, e) => write!(f, "machine {} failed: {:?}", id, e),
+        }
+    }
+}
+
+impl std::error::Error for SchedulerError {}
+
+/// Input queued by an output callback, delivered once it returns.
+#[derive(Debug, Default)]
+pub struct Outbox(Vec<(VmId, i64)>);
+
+impl Outbox {
+
+    /// Queue `value` as input to machine `id`.
+    pub fn send(&mut self, id: VmId, value: i64) {
+        self.0.push((id, value));
+    }
+}
+
+type OutputFn<'a> = Box<dyn FnMut(i64, &mut Outbox) + 'a>;
+type InputFn<'a> = Box<dyn FnMut() -> Option<i64> + 'a>;
+
+/// Where a machine's output goes.
+enum Route<'a> {
+    Keep,
+    Vm(VmId),
+    Host(OutputFn<'a>),
+}
+
+struct Machine<'a> {
+    vm: &'a mut IntcodeVM,
+    io: IOHandle<i64, i64>,
+    state: MachineState,
+    route: Route<'a>,
+    input: Option<InputFn<'a>>,
+    kept: Vec<i64>,
+}
+
+/// Runs machines round-robin in the order they were added, each until
+/// it blocks on input or produces an output, which is then delivered
+/// before the next machine runs.
+#[derive(Default)]
+pub struct Scheduler<'a> {
+    machines: Vec<Machine<'a>>,
+}
+
+impl<'a> Scheduler<'a> {
+
+    /// Create a scheduler with no machines.
+    pub fn new() -> Scheduler<'a> {
+        Scheduler::default()
+    }
+
+    /// Add a machine with its program already loaded.
+    /// Its output is kept until collected with `take_output`.
+    pub fn add(&mut self, vm: &'a mut IntcodeVM) -> VmId {
+        let io = vm.io();
+        self.machines.push(Machine {
+            vm,
+            io,
+            state: MachineState::Ready,
+            route: Route::Keep,
+            input: None,
+            kept: Vec::new(),
+        });
+
+        self.machines.len() - 1
+    }
+
+    /// Queue `value` as input to machine `id`.
+    pub fn send(&mut self, id: VmId, value: i64) {
+        self.machines[id].io.send(value);
+    }
+
+    /// Feed the output of machine `from` into the input of machine `to`.
+    pub fn connect(&mut self, from: VmId, to: VmId) {
+        self.machines[from].route = Route::Vm(to);
+    }
+
+    /// Hand the output of machine `id` to `f`, which may send input on.
+    pub fn on_output<F: FnMut(i64, &mut Outbox) + 'a>(&mut self, id: VmId, f: F) {
+        self.machines[id].route = Route::Host(Box::new(f));
+    }
+
+    /// Ask `f` for input whenever machine `id` has none queued.
+    /// Returning `None` leaves the machine waiting.
+    pub fn on_input<F: FnMut() -> Option<i64> + 'a>(&mut self, id: VmId, f: F) {
+        self.machines[id].input = Some(Box::new(f));
+    }
+
+    /// What machine `id` is doing.
+    pub fn state(&self, id: VmId) -> &MachineState {
+        &self.machines[id].state
+    }
+
+    /// Collect the output kept for machine `id`.
+    pub fn take_output(&mut self, id: VmId) -> Vec<i64> {
+        std::mem::take(&mut self.machines[id].kept)
+    }
+
+    /// Run every machine until all of them halt.
+    pub fn run(&mut self) -> Result<(), SchedulerError> {
+        loop {
+            let mut progressed = false;
+
+            for id in 0..self.machines.len() {
+                if !self.is_running(id) {
+                    continue;
+                }
+
+                let (ran, output) = self.run_slice(id)?;
+                progressed |= ran;
+
+                if let Some(value) = output {
+                    self.deliver(id, value);
+                }
+            }
+
+            let waiting: Vec<VmId> = (0..self.machines.len())
+                .filter(|&id| self.is_running(id))
+                .collect();
+
+            if waiting.is_empty() {
+                return Ok(());
+            } else if !progressed {
+                return Err(SchedulerError::Deadlock(waiting));
+            }
+        }
+    }
+
+    fn is_running(&self, id: VmId) -> bool {
+        matches!(self.machines[id].state, MachineState::Ready | MachineState::Waiting)
+    }
+
+    /// Steps a machine until it blocks, halts or outputs, returning
+    /// whether it executed anything and the output, if any.
+    fn run_slice(&mut self, id: VmId) -> Result<(bool, Option<i64>), SchedulerError> {
+        let machine = &mut self.machines[id];
+        let mut ran = false;
+
+        loop {
+            match machine.vm.step() {
+                Some(Event::Halted) => {
+                    machine.state = MachineState::Halted;
+                    return Ok((true, None));
+                },
+                // With its input closed the machine can never continue.
+                Some(Event::EndOfInput) => {
+                    machine.state = MachineState::Halted;
+                    return Ok((ran, None));
+                },
+                Some(Event::OutputFull) => {
+                    if let Some(value) = machine.io.recv() {
+                        return Ok((true, Some(value)));
+                    }
+                    machine.state = MachineState::Waiting;
+                    return Ok((ran, None));
+                },
+                Some(Event::Error(e)) => {
+                    machine.state = MachineState::Failed(e.clone());
+                    return Err(SchedulerError::Vm(id, e));
+                },
+                Some(Event::NeedInput) => {
+                    let input = machine.input.as_mut().and_then(|f| f());
+                    match input {
+                        Some(value) => machine.io.send(value),
+                        None => {
+                            machine.state = MachineState::Waiting;
+                            return Ok((ran, None));
+                        },
+                    }
+                },
+                // A breakpoint is reported before its instruction runs,
+                // which the next step does.
+                Some(Event::BreakpointHit(_)) => {},
+                Some(_) | None => {
+                    ran = true;
+                    machine.state = MachineState::Ready;
+
+                    if let Some(value) = machine.io.recv() {
+                        return Ok((true, Some(value)));
+                    }
+                },
+            }
+        }
+    }
+
+    /// Routes one output from machine `id`.
+    fn deliver(&mut self, id: VmId, value: i64) {
+        let mut outbox = Outbox::default();
+
+        match &mut self.machines[id].route {
+            Route::Keep => self.machines[id].kept.push(value),
+            Route::Vm(to) => outbox.send(*to, value),
+            Route::Host(f) => f(value, &mut outbox),
+        }
+
+        for (to, value) in outbox.0 {
+            self.send(to, value);
+        }
+    }
+}
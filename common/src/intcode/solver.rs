@@ -0,0 +1,469 @@
+//! Linear integer expressions over symbolic values, and a small solver
+//! for the constraints symbolic execution puts on them.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// A symbolic value: an input, or the initial contents of a memory cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Var {
+    /// The n-th value read by the program, counting from 0.
+    Input(usize),
+    /// The value a cell held before the program started.
+    Cell(usize),
+}
+
+impl fmt::Display for Var {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Var::Input(n) => write!(f, "in{}", n),
+            Var::Cell(addr) => write!(f, "m[{}]", addr),
+        }
+    }
+}
+
+/// Values for symbolic variables.
+pub type Model = BTreeMap<Var, i64>;
+
+/// A constant plus a sum of variables with integer coefficients.
+/// Arithmetic wraps, as the machine's own would.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LinExpr {
+    constant: i64,
+    terms: BTreeMap<Var, i64>,
+}
+
+impl LinExpr {
+
+    /// An expression with no variables.
+    pub fn constant(value: i64) -> LinExpr {
+        LinExpr {
+            constant: value,
+            terms: BTreeMap::new(),
+        }
+    }
+
+    /// An expression that is just `var`.
+    pub fn var(var: Var) -> LinExpr {
+        let mut terms = BTreeMap::new();
+        terms.insert(var, 1);
+
+        LinExpr {
+            constant: 0,
+            terms,
+        }
+    }
+
+    /// The value of the expression, if it has no variables.
+    pub fn as_constant(&self) -> Option<i64> {
+        if self.terms.is_empty() {
+            Some(self.constant)
+        } else {
+            None
+        }
+    }
+
+    /// The constant part of the expression.
+    pub fn constant_term(&self) -> i64 {
+        self.constant
+    }
+
+    /// Each variable with its coefficient.
+    pub fn terms(&self) -> impl Iterator<Item = (Var, i64)> + '_ {
+        self.terms.iter().map(|(&v, &k)| (v, k))
+    }
+
+    /// Evaluate the expression, if `model` has every variable in it.
+    pub fn eval(&self, model: &Model) -> Option<i64> {
+        self.terms().try_fold(self.constant, |acc, (v, k)| {
+            model.get(&v).map(|&x| acc.wrapping_add(k.wrapping_mul(x)))
+        })
+    }
+}
+
+impl Add for LinExpr {
+    type Output = LinExpr;
+
+    fn add(mut self, other: LinExpr) -> LinExpr {
+        self.constant = self.constant.wrapping_add(other.constant);
+        for (v, k) in other.terms {
+            let sum = self.terms.get(&v).copied().unwrap_or(0).wrapping_add(k);
+            if sum == 0 {
+                self.terms.remove(&v);
+            } else {
+                self.terms.insert(v, sum);
+            }
+        }
+
+        self
+    }
+}
+
+impl Neg for LinExpr {
+    type Output = LinExpr;
+
+    fn neg(self) -> LinExpr {
+        self * -1
+    }
+}
+
+impl Sub for LinExpr {
+    type Output = LinExpr;
+
+    fn sub(self, other: LinExpr) -> LinExpr {
+        self + -other
+    }
+}
+
+impl Mul<i64> for LinExpr {
+    type Output = LinExpr;
+
+    fn mul(mut self, k: i64) -> LinExpr {
+        if k == 0 {
+            return LinExpr::constant(0);
+        }
+
+        self.constant = self.constant.wrapping_mul(k);
+        for coeff in self.terms.values_mut() {
+            *coeff = coeff.wrapping_mul(k);
+        }
+        self.terms.retain(|_, coeff| *coeff != 0);
+
+        self
+    }
+}
+
+impl fmt::Display for LinExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for (v, k) in self.terms() {
+            let sign = if k < 0 { "-" } else { "+" };
+            if first {
+                if k < 0 {
+                    write!(f, "-")?;
+                }
+            } else {
+                write!(f, " {} ", sign)?;
+            }
+            first = false;
+
+            match k.unsigned_abs() {
+                1 => write!(f, "{}", v)?,
+                n => write!(f, "{}*{}", n, v)?,
+            }
+        }
+
+        if first {
+            write!(f, "{}", self.constant)
+        } else if self.constant < 0 {
+            write!(f, " - {}", self.constant.unsigned_abs())
+        } else if self.constant > 0 {
+            write!(f, " + {}", self.constant)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// How an expression compares against zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rel {
+    Eq,
+    Ne,
+    Lt,
+    Ge,
+}
+
+/// `expr rel 0`, e.g. `in0 - 8 == 0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraint {
+    pub expr: LinExpr,
+    pub rel: Rel,
+}
+
+impl Constraint {
+
+    pub fn new(expr: LinExpr, rel: Rel) -> Constraint {
+        Constraint {
+            expr,
+            rel,
+        }
+    }
+
+    /// The constraint that holds exactly when this one doesn't.
+    pub fn negate(&self) -> Constraint {
+        let rel = match self.rel {
+            Rel::Eq => Rel::Ne,
+            Rel::Ne => Rel::Eq,
+            Rel::Lt => Rel::Ge,
+            Rel::Ge => Rel::Lt,
+        };
+
+        Constraint::new(self.expr.clone(), rel)
+    }
+
+    /// Whether the constraint holds, if `model` has every variable in it.
+    pub fn holds(&self, model: &Model) -> Option<bool> {
+        self.expr.eval(model).map(|x| compare(x, self.rel))
+    }
+
+    /// Whether the constraint holds, if it has no variables.
+    pub fn as_constant(&self) -> Option<bool> {
+        self.expr.as_constant().map(|x| compare(x, self.rel))
+    }
+}
+
+fn compare(x: i64, rel: Rel) -> bool {
+    match rel {
+        Rel::Eq => x == 0,
+        Rel::Ne => x != 0,
+        Rel::Lt => x < 0,
+        Rel::Ge => x >= 0,
+    }
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rel = match self.rel {
+            Rel::Eq => "==",
+            Rel::Ne => "!=",
+            Rel::Lt => "<",
+            Rel::Ge => ">=",
+        };
+
+        write!(f, "{} {} 0", self.expr, rel)
+    }
+}
+
+/// The outcome of solving a set of constraints.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SolveResult {
+    /// The constraints hold for this model.
+    Sat(Model),
+    /// No values within the bounds satisfy the constraints.
+    Unsat,
+    /// The solver gave up before finding out.
+    Unknown,
+}
+
+impl SolveResult {
+
+    /// The model found, if any.
+    pub fn model(self) -> Option<Model> {
+        match self {
+            SolveResult::Sat(m) => Some(m),
+            _ => None,
+        }
+    }
+}
+
+/// Finds integer values for the variables in linear constraints, by
+/// narrowing each variable's bounds and splitting them when stuck.
+/// Complete within the bounds given, up to a search budget.
+#[derive(Debug, Clone)]
+pub struct Solver {
+    bounds: (i64, i64),
+    var_bounds: HashMap<Var, (i64, i64)>,
+    budget: usize,
+}
+
+impl Default for Solver {
+    fn default() -> Solver {
+        Solver {
+            bounds: (i32::MIN as i64, i32::MAX as i64),
+            var_bounds: HashMap::new(),
+            budget: 10_000,
+        }
+    }
+}
+
+/// Bounds per variable, in a type wide enough to never overflow.
+type Domains = BTreeMap<Var, (i128, i128)>;
+
+impl Solver {
+
+    /// Create a solver searching the range of an `i32` for every variable.
+    pub fn new() -> Solver {
+        Solver::default()
+    }
+
+    /// Set the inclusive range searched for every variable.
+    pub fn with_bounds(mut self, lo: i64, hi: i64) -> Solver {
+        self.bounds = (lo, hi);
+        self
+    }
+
+    /// Set the inclusive range searched for one variable.
+    pub fn with_var_bounds(mut self, var: Var, lo: i64, hi: i64) -> Solver {
+        self.var_bounds.insert(var, (lo, hi));
+        self
+    }
+
+    /// Set how many search nodes to visit before giving up.
+    pub fn with_budget(mut self, budget: usize) -> Solver {
+        self.budget = budget;
+        self
+    }
+
+    /// Find values satisfying every constraint.
+    pub fn solve(&self, constraints: &[Constraint]) -> SolveResult {
+        self.solve_for(constraints, &LinExpr::default())
+    }
+
+    /// Find values satisfying every constraint, with the model also
+    /// covering every variable in `expr`.
+    pub fn solve_for(&self, constraints: &[Constraint], expr: &LinExpr) -> SolveResult {
+        let vars = constraints.iter()
+            .flat_map(|c| c.expr.terms())
+            .chain(expr.terms());
+
+        let mut domains = Domains::new();
+        for (v, _) in vars {
+            let (lo, hi) = self.var_bounds.get(&v).copied().unwrap_or(self.bounds);
+            domains.insert(v, (lo as i128, hi as i128));
+        }
+
+        let mut budget = self.budget;
+        search(domains, constraints, &mut budget)
+    }
+}
+
+fn search(mut domains: Domains, constraints: &[Constraint], budget: &mut usize) -> SolveResult {
+    if *budget == 0 {
+        return SolveResult::Unknown;
+    }
+    *budget -= 1;
+
+    if !propagate(&mut domains, constraints) {
+        return SolveResult::Unsat;
+    }
+
+    // Split the narrowest unfixed variable in half.
+    let split = domains.iter()
+        .filter(|(_, (lo, hi))| lo < hi)
+        .min_by_key(|(_, (lo, hi))| hi - lo)
+        .map(|(&v, &d)| (v, d));
+
+    let (var, (lo, hi)) = match split {
+        Some(s) => s,
+        None => {
+            let model: Model = domains.iter().map(|(&v, &(x, _))| (v, x as i64)).collect();
+            return if constraints.iter().all(|c| c.holds(&model) == Some(true)) {
+                SolveResult::Sat(model)
+            } else {
+                SolveResult::Unsat
+            };
+        },
+    };
+
+    // Search the half nearer zero first, so models stay small.
+    let mid = lo + (hi - lo) / 2;
+    let mut halves = [(lo, mid), (mid + 1, hi)];
+    if mid < 0 {
+        halves.swap(0, 1);
+    }
+
+    let mut unknown = false;
+    for half in halves {
+        let mut next = domains.clone();
+        next.insert(var, half);
+
+        match search(next, constraints, budget) {
+            SolveResult::Sat(m) => return SolveResult::Sat(m),
+            SolveResult::Unknown => unknown = true,
+            SolveResult::Unsat => {},
+        }
+    }
+
+    if unknown {
+        SolveResult::Unknown
+    } else {
+        SolveResult::Unsat
+    }
+}
+
+/// Narrows the domains using each constraint in turn until nothing
+/// changes (or enough rounds pass), returning false if one empties.
+fn propagate(domains: &mut Domains, constraints: &[Constraint]) -> bool {
+    const MAX_ROUNDS: usize = 64;
+
+    for _ in 0..MAX_ROUNDS {
+        let mut changed = false;
+
+        for c in constraints {
+            let range = |v: &Var, k: i64, d: &Domains| {
+                let (lo, hi) = d[v];
+                let (a, b) = (k as i128 * lo, k as i128 * hi);
+                (a.min(b), a.max(b))
+            };
+
+            let constant = c.expr.constant_term() as i128;
+            let (min, max) = c.expr.terms().fold((constant, constant), |(min, max), (v, k)| {
+                let (a, b) = range(&v, k, domains);
+                (min + a, max + b)
+            });
+
+            let possible = match c.rel {
+                Rel::Eq => min <= 0 && 0 <= max,
+                Rel::Ne => !(min == 0 && max == 0),
+                Rel::Lt => min < 0,
+                Rel::Ge => max >= 0,
+            };
+            if !possible {
+                return false;
+            }
+
+            if c.rel == Rel::Ne {
+                continue;
+            }
+
+            for (v, k) in c.expr.terms() {
+                let (a, b) = range(&v, k, domains);
+                let (rest_min, rest_max) = (min - a, max - b);
+
+                // Bounds on `k * v`.
+                let (term_lo, term_hi) = match c.rel {
+                    Rel::Eq => (Some(-rest_max), Some(-rest_min)),
+                    Rel::Lt => (None, Some(-1 - rest_min)),
+                    Rel::Ge => (Some(-rest_max), None),
+                    Rel::Ne => unreachable!(),
+                };
+
+                let k = k as i128;
+                let (var_lo, var_hi) = if k > 0 {
+                    (term_lo.map(|l| div_ceil(l, k)), term_hi.map(|h| div_floor(h, k)))
+                } else {
+                    (term_hi.map(|h| div_ceil(h, k)), term_lo.map(|l| div_floor(l, k)))
+                };
+
+                let (lo, hi) = domains[&v];
+                let lo = var_lo.map_or(lo, |x| lo.max(x));
+                let hi = var_hi.map_or(hi, |x| hi.min(x));
+                if lo > hi {
+                    return false;
+                }
+                if (lo, hi) != domains[&v] {
+                    domains.insert(v, (lo, hi));
+                    changed = true;
+                }
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    true
+}
+
+fn div_floor(a: i128, b: i128) -> i128 {
+    let q = a / b;
+    if a % b != 0 && (a < 0) != (b < 0) { q - 1 } else { q }
+}
+
+fn div_ceil(a: i128, b: i128) -> i128 {
+    let q = a / b;
+    if a % b != 0 && (a < 0) == (b < 0) { q + 1 } else { q }
+}
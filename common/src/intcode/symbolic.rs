@@ -0,0 +1,427 @@
+//! Symbolic execution, for finding the inputs that make a program
+//! behave a certain way without trying them one at a time.
+//!
+//! Inputs (and any chosen memory cells) start out as variables, and
+//! arithmetic on them builds linear expressions. Comparisons and jumps
+//! on symbolic values fork the path, recording which way each went as
+//! a constraint. Where a concrete value is unavoidable, such as an
+//! address or a product of two symbolic values, one is picked from a
+//! model of the path so far and pinned with a constraint.
+
+use std::collections::BTreeMap;
+
+use super::solver::{Constraint, LinExpr, Model, Rel, SolveResult, Solver, Var};
+use super::vm::MEMORY_SIZE;
+
+/// Why a path stopped.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathEnd {
+    /// The program halted normally.
+    Halted,
+    /// The program wanted more inputs than allowed.
+    InputLimit,
+    /// The path ran for too many instructions.
+    StepLimit,
+    /// Invalid instruction or address at the given instruction.
+    Fault(usize),
+    /// A value at the given instruction couldn't be made concrete.
+    Stuck(usize),
+}
+
+/// One way through the program.
+#[derive(Debug, Clone)]
+pub struct SymbolicPath {
+    /// What must hold of the variables for the program to go this way.
+    pub constraints: Vec<Constraint>,
+    /// Everything output along the way.
+    pub outputs: Vec<LinExpr>,
+    /// How many inputs were read.
+    pub inputs: usize,
+    pub end: PathEnd,
+}
+
+impl SymbolicPath {
+
+    /// Find variable values that send the program down this path.
+    pub fn solve(&self, solver: &Solver) -> SolveResult {
+        solver.solve(&self.constraints)
+    }
+}
+
+/// Explores the paths through a program with symbolic inputs.
+pub struct SymbolicExplorer {
+    program: Vec<i64>,
+    symbolic_cells: Vec<usize>,
+    concrete_inputs: Vec<i64>,
+    max_inputs: usize,
+    max_steps: usize,
+    max_paths: usize,
+    solver: Solver,
+    seed: Model,
+}
+
+impl SymbolicExplorer {
+
+    /// Create an explorer for a program.
+    pub fn new(program: &[i64]) -> SymbolicExplorer {
+        assert!(program.len() <= MEMORY_SIZE, "program too large: {} > {}", program.len(), MEMORY_SIZE);
+
+        SymbolicExplorer {
+            program: program.to_vec(),
+            symbolic_cells: Vec::new(),
+            concrete_inputs: Vec::new(),
+            max_inputs: 16,
+            max_steps: 100_000,
+            max_paths: 1024,
+            solver: Solver::new(),
+            seed: Model::new(),
+        }
+    }
+
+    /// Treat the initial value of the cell at `addr` as a variable.
+    pub fn with_symbolic_cell(mut self, addr: usize) -> SymbolicExplorer {
+        self.symbolic_cells.push(addr);
+        self
+    }
+
+    /// Feed these values as the first inputs, before any symbolic ones.
+    pub fn with_concrete_inputs(mut self, inputs: &[i64]) -> SymbolicExplorer {
+        self.concrete_inputs = inputs.to_vec();
+        self
+    }
+
+    /// Stop paths reading more than `n` inputs.
+    pub fn with_max_inputs(mut self, n: usize) -> SymbolicExplorer {
+        self.max_inputs = n;
+        self
+    }
+
+    /// Stop paths running more than `n` instructions.
+    pub fn with_max_steps(mut self, n: usize) -> SymbolicExplorer {
+        self.max_steps = n;
+        self
+    }
+
+    /// Stop exploring after finding `n` paths.
+    pub fn with_max_paths(mut self, n: usize) -> SymbolicExplorer {
+        self.max_paths = n;
+        self
+    }
+
+    /// Use `solver` to check forks are feasible and to pick concrete values.
+    pub fn with_solver(mut self, solver: Solver) -> SymbolicExplorer {
+        self.solver = solver;
+        self
+    }
+
+    /// Prefer these variable values when a concrete value must be picked,
+    /// e.g. inputs known to get the program past self-modifying code.
+    pub fn with_seed(mut self, seed: Model) -> SymbolicExplorer {
+        self.seed = seed;
+        self
+    }
+
+    /// Explore every feasible path, depth first, up to the path limit.
+    pub fn explore(&self) -> Vec<SymbolicPath> {
+        let mut memory = vec![0; MEMORY_SIZE];
+        memory[..self.program.len()].copy_from_slice(&self.program);
+
+        let mut symbolic = BTreeMap::new();
+        for &addr in &self.symbolic_cells {
+            symbolic.insert(addr, LinExpr::var(Var::Cell(addr)));
+        }
+
+        let mut pending = vec![State {
+            memory,
+            symbolic,
+            instr_pointer: 0,
+            instr_start: 0,
+            relative_base: 0,
+            inputs: 0,
+            outputs: Vec::new(),
+            constraints: Vec::new(),
+            steps: 0,
+            forced: None,
+        }];
+
+        let mut paths = Vec::new();
+        while let Some(mut state) = pending.pop() {
+            if paths.len() >= self.max_paths {
+                break;
+            }
+
+            let end = loop {
+                if state.steps >= self.max_steps {
+                    break Some(PathEnd::StepLimit);
+                }
+                state.steps += 1;
+
+                match state.step(self, &mut pending) {
+                    Ok(()) => {},
+                    Err(end) => break end,
+                }
+            };
+
+            if let Some(end) = end {
+                paths.push(SymbolicPath {
+                    constraints: state.constraints,
+                    outputs: state.outputs,
+                    inputs: state.inputs,
+                    end,
+                });
+            }
+        }
+
+        paths
+    }
+
+    /// Find variable values that make the program's output number `index`
+    /// equal to `value` on some path that halts.
+    pub fn solve_for_output(&self, index: usize, value: i64) -> Option<Model> {
+        self.explore().into_iter()
+            .filter(|p| p.end == PathEnd::Halted)
+            .filter_map(|p| {
+                let output = p.outputs.get(index)?.clone();
+                let mut constraints = p.constraints;
+                constraints.push(Constraint::new(output - LinExpr::constant(value), Rel::Eq));
+
+                self.solver.solve(&constraints).model()
+            })
+            .next()
+    }
+}
+
+/// A path in progress.
+#[derive(Clone)]
+struct State {
+    memory: Vec<i64>,
+    symbolic: BTreeMap<usize, LinExpr>,
+    instr_pointer: usize,
+    instr_start: usize,
+    relative_base: i64,
+    inputs: usize,
+    outputs: Vec<LinExpr>,
+    constraints: Vec<Constraint>,
+    steps: usize,
+    /// The outcome to take at the next fork, for a path that was
+    /// split off and is re-running the forking instruction.
+    forced: Option<bool>,
+}
+
+/// `Err(None)` drops a path that turned out to be infeasible.
+type Step<T> = Result<T, Option<PathEnd>>;
+
+impl State {
+
+    fn step(&mut self, explorer: &SymbolicExplorer, pending: &mut Vec<State>) -> Step<()> {
+        self.instr_start = self.instr_pointer;
+
+        let instr = self.fetch(0, explorer)?;
+        let opcode = instr % 100;
+        let modes = [(instr / 100) % 10, (instr / 1000) % 10, (instr / 10000) % 10];
+
+        match opcode {
+            1 | 2 => {
+                let a = self.read(1, modes[0], explorer)?;
+                let b = self.read(2, modes[1], explorer)?;
+                let value = if opcode == 1 {
+                    a + b
+                } else if let Some(k) = a.as_constant() {
+                    b * k
+                } else if let Some(k) = b.as_constant() {
+                    a * k
+                } else {
+                    let k = self.concretize(a, explorer)?;
+                    b * k
+                };
+
+                let addr = self.address(3, modes[2], explorer)?;
+                self.store(addr, value);
+                self.instr_pointer += 4;
+            },
+            3 => {
+                if self.inputs >= explorer.max_inputs {
+                    return Err(Some(PathEnd::InputLimit));
+                }
+
+                let value = match explorer.concrete_inputs.get(self.inputs) {
+                    Some(&x) => LinExpr::constant(x),
+                    None => LinExpr::var(Var::Input(self.inputs)),
+                };
+                self.inputs += 1;
+
+                let addr = self.address(1, modes[0], explorer)?;
+                self.store(addr, value);
+                self.instr_pointer += 2;
+            },
+            4 => {
+                let value = self.read(1, modes[0], explorer)?;
+                self.outputs.push(value);
+                self.instr_pointer += 2;
+            },
+            5 | 6 => {
+                let cond = self.read(1, modes[0], explorer)?;
+                let rel = if opcode == 5 { Rel::Ne } else { Rel::Eq };
+
+                if self.branch(Constraint::new(cond, rel), &explorer.solver, pending)? {
+                    let target = self.read(2, modes[1], explorer)?;
+                    let target = self.concretize(target, explorer)?;
+                    if target < 0 || target as usize >= MEMORY_SIZE {
+                        return Err(Some(PathEnd::Fault(self.instr_start)));
+                    }
+                    self.instr_pointer = target as usize;
+                } else {
+                    self.instr_pointer += 3;
+                }
+            },
+            7 | 8 => {
+                let a = self.read(1, modes[0], explorer)?;
+                let b = self.read(2, modes[1], explorer)?;
+                let rel = if opcode == 7 { Rel::Lt } else { Rel::Eq };
+
+                let holds = self.branch(Constraint::new(a - b, rel), &explorer.solver, pending)?;
+                let addr = self.address(3, modes[2], explorer)?;
+                self.store(addr, LinExpr::constant(holds as i64));
+                self.instr_pointer += 4;
+            },
+            9 => {
+                let offset = self.read(1, modes[0], explorer)?;
+                self.relative_base += self.concretize(offset, explorer)?;
+                self.instr_pointer += 2;
+            },
+            99 => return Err(Some(PathEnd::Halted)),
+            _ => return Err(Some(PathEnd::Fault(self.instr_start))),
+        }
+
+        Ok(())
+    }
+
+    /// The value of a cell, symbolic or not.
+    fn value(&self, addr: usize) -> LinExpr {
+        self.symbolic.get(&addr)
+            .cloned()
+            .unwrap_or_else(|| LinExpr::constant(self.memory[addr]))
+    }
+
+    fn store(&mut self, addr: usize, value: LinExpr) {
+        match value.as_constant() {
+            Some(x) => {
+                self.memory[addr] = x;
+                self.symbolic.remove(&addr);
+            },
+            None => {
+                self.symbolic.insert(addr, value);
+            },
+        }
+    }
+
+    /// The concrete value of the instruction word at `offset`.
+    fn fetch(&mut self, offset: usize, explorer: &SymbolicExplorer) -> Step<i64> {
+        let addr = self.check_addr(self.instr_pointer as i64 + offset as i64)?;
+        let value = self.value(addr);
+
+        self.concretize(value, explorer)
+    }
+
+    /// Reads the parameter at `offset` from the instruction start.
+    fn read(&mut self, offset: usize, mode: i64, explorer: &SymbolicExplorer) -> Step<LinExpr> {
+        // Immediate values can stay symbolic; only addresses must be concrete.
+        if mode == 1 {
+            let addr = self.check_addr(self.instr_pointer as i64 + offset as i64)?;
+            return Ok(self.value(addr));
+        }
+
+        let param = self.fetch(offset, explorer)?;
+
+        match mode {
+            0 => Ok(self.value(self.check_addr(param)?)),
+            2 => Ok(self.value(self.check_addr(self.relative_base + param)?)),
+            _ => Err(Some(PathEnd::Fault(self.instr_start))),
+        }
+    }
+
+    /// The address written by the parameter at `offset`.
+    fn address(&mut self, offset: usize, mode: i64, explorer: &SymbolicExplorer) -> Step<usize> {
+        let param = self.fetch(offset, explorer)?;
+
+        match mode {
+            0 => self.check_addr(param),
+            2 => self.check_addr(self.relative_base + param),
+            _ => Err(Some(PathEnd::Fault(self.instr_start))),
+        }
+    }
+
+    fn check_addr(&self, addr: i64) -> Step<usize> {
+        if addr >= 0 && (addr as usize) < MEMORY_SIZE {
+            Ok(addr as usize)
+        } else {
+            Err(Some(PathEnd::Fault(self.instr_start)))
+        }
+    }
+
+    /// Picks a value for `expr` that is possible on this path, from the
+    /// seed if it allows one, and constrains the path to it.
+    fn concretize(&mut self, expr: LinExpr, explorer: &SymbolicExplorer) -> Step<i64> {
+        if let Some(x) = expr.as_constant() {
+            return Ok(x);
+        }
+
+        let solver = &explorer.solver;
+        let pin = |value: i64| Constraint::new(expr.clone() - LinExpr::constant(value), Rel::Eq);
+
+        let seeded = expr.eval(&explorer.seed).filter(|&value| {
+            let mut cs = self.constraints.clone();
+            cs.push(pin(value));
+            solver.solve(&cs) != SolveResult::Unsat
+        });
+
+        let value = seeded
+            .or_else(|| solver.solve_for(&self.constraints, &expr).model().and_then(|m| expr.eval(&m)))
+            .ok_or(Some(PathEnd::Stuck(self.instr_start)))?;
+
+        self.constraints.push(pin(value));
+
+        Ok(value)
+    }
+
+    /// Decides whether `c` holds, forking if it could go either way.
+    /// The fork re-runs the current instruction taking the other outcome.
+    fn branch(&mut self, c: Constraint, solver: &Solver, pending: &mut Vec<State>) -> Step<bool> {
+        if let Some(holds) = c.as_constant() {
+            return Ok(holds);
+        }
+
+        if let Some(holds) = self.forced.take() {
+            self.constraints.push(if holds { c } else { c.negate() });
+            return Ok(holds);
+        }
+
+        let feasible = |extra: &Constraint| {
+            let mut cs = self.constraints.clone();
+            cs.push(extra.clone());
+            solver.solve(&cs) != SolveResult::Unsat
+        };
+
+        let negated = c.negate();
+        match (feasible(&c), feasible(&negated)) {
+            (true, true) => {
+                let mut other = self.clone();
+                other.forced = Some(false);
+                pending.push(other);
+
+                self.constraints.push(c);
+                Ok(true)
+            },
+            (true, false) => {
+                self.constraints.push(c);
+                Ok(true)
+            },
+            (false, true) => {
+                self.constraints.push(negated);
+                Ok(false)
+            },
+            (false, false) => Err(None),
+        }
+    }
+}
+
@@ -5,24 +5,33 @@
 //! root to run every test from every day.
 
 use crate::IntcodeVM;
+use crate::executor::{block_on, LocalExecutor};
+use crate::grid::Point;
+use crate::testing::ScratchDir;
+use super::{Bus, BusError, Device, Screen, Turtle};
+use super::{MachineState, Scheduler, SchedulerError};
+use super::{Decompilation, Disassembly, Optimizer};
+use super::{Constraint, LinExpr, Model, PathEnd, Rel, SolveResult, Solver, SymbolicExplorer, Var};
+use super::{Event, GdbStub, SelfModification, SelfModifyPolicy, TrySendError, VmError};
+use super::{find_tracking_cells, parse_program, CellChange, ImageError, LoadError, MemorySearch, ParseErrorKind, VmState};
 
 #[test]
 fn day02_examples() {
     let mut vm = IntcodeVM::new()
         .with_logging(2)
-        .with_program(&"1,0,0,0,99".to_string());
+        .with_program("1,0,0,0,99");
     vm.run();
     assert_eq!(vec![2,0,0,0,99], vm.dump_memory(0..5));
 
-    vm.load_program(&"2,3,0,3,99".to_string());
+    vm.load_program("2,3,0,3,99");
     vm.run();
     assert_eq!(vec![2,3,0,6,99], vm.dump_memory(0..5));
 
-    vm.load_program(&"2,4,4,5,99,0".to_string());
+    vm.load_program("2,4,4,5,99,0");
     vm.run();
     assert_eq!(vec![2,4,4,5,99,9801], vm.dump_memory(0..6));
 
-    vm.load_program(&"1,1,1,4,99,5,6,0,99".to_string());
+    vm.load_program("1,1,1,4,99,5,6,0,99");
     vm.run();
     assert_eq!(vec![30,1,1,4,2,5,6,0,99], vm.dump_memory(0..9));
 }
@@ -33,7 +42,7 @@ fn day05_examples() {
 
     let mut vm = IntcodeVM::new()
         .with_logging(2)
-        .with_program(&"1002,4,3,4,33".to_string());
+        .with_program("1002,4,3,4,33");
     vm.run();
     assert_eq!(vec![1002,4,3,4,99], vm.dump_memory(0..5));
 
@@ -41,39 +50,39 @@ fn day05_examples() {
 
     // Less than and equal tests
     let io_handle = vm.io();
-    vm.load_program(&"3,9,8,9,10,9,4,9,99,-1,8".to_string());
+    vm.load_program("3,9,8,9,10,9,4,9,99,-1,8");
     io_handle.send(8);
     vm.run();
     assert_eq!(1, io_handle.recv().unwrap());
 
-    vm.load_program(&"3,9,7,9,10,9,4,9,99,-1,8".to_string());
+    vm.load_program("3,9,7,9,10,9,4,9,99,-1,8");
     io_handle.send(8);
     vm.run();
     assert_eq!(0, io_handle.recv().unwrap());
 
-    vm.load_program(&"3,3,1108,-1,8,3,4,3,99".to_string());
+    vm.load_program("3,3,1108,-1,8,3,4,3,99");
     io_handle.send(8);
     vm.run();
     assert_eq!(1, io_handle.recv().unwrap());
 
-    vm.load_program(&"3,3,1107,-1,8,3,4,3,99".to_string());
+    vm.load_program("3,3,1107,-1,8,3,4,3,99");
     io_handle.send(8);
     vm.run();
     assert_eq!(0, io_handle.recv().unwrap());
 
     // Jump tests
-    vm.load_program(&"3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9".to_string());
+    vm.load_program("3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9");
     io_handle.send(0);
     vm.run();
     assert_eq!(0, io_handle.recv().unwrap());
 
-    vm.load_program(&"3,3,1105,-1,9,1101,0,0,12,4,12,99,1".to_string());
+    vm.load_program("3,3,1105,-1,9,1101,0,0,12,4,12,99,1");
     io_handle.send(1);
     vm.run();
     assert_eq!(1, io_handle.recv().unwrap());
 
     // Combined
-    vm.load_program(&"3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99".to_string());
+    vm.load_program("3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99");
     io_handle.send(3);
     vm.run();
     assert_eq!(999, io_handle.recv().unwrap());
@@ -88,27 +97,975 @@ fn day09_examples() {
 
     let mut vm = IntcodeVM::new()
         .with_logging(2)
-        .with_program(&"109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99".to_string());
+        .with_program("109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99");
     let io_handle = vm.io();
     vm.run();
     assert_eq!([109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99], &io_handle.dump()[..]);
 
-    vm.load_program(&"1102,34915192,34915192,7,4,7,99,0".to_string());
+    vm.load_program("1102,34915192,34915192,7,4,7,99,0");
     vm.run();
     assert_eq!(16, count_digits(io_handle.recv().unwrap()));
 
-    vm.load_program(&"104,1125899906842624,99".to_string());
+    vm.load_program("104,1125899906842624,99");
     vm.run();
     assert_eq!(1125899906842624, io_handle.recv().unwrap());
 }
 
+#[test]
+fn parse_tolerates_formatting() {
+    assert_eq!(vec![1,0,0,0,99], parse_program("1,0,0,0,99").unwrap());
+    assert_eq!(vec![1,0,0,0,99], parse_program("1, 0,\n0 ,0,\r\n99,\n").unwrap());
+
+    let commented = "# Day 2 example\n1,9,10,3, # add\n2,3,11,0,\n99,\n30,40,50\n";
+    assert_eq!(vec![1,9,10,3,2,3,11,0,99,30,40,50], parse_program(commented).unwrap());
+}
+
+#[test]
+fn parse_error_locations() {
+    let err = parse_program("1,2,,3").unwrap_err();
+    assert_eq!((2, 4, ParseErrorKind::EmptyToken), (err.token, err.offset, err.kind));
+
+    let err = parse_program("1,2\n3,4").unwrap_err();
+    assert_eq!((1, 4, ParseErrorKind::MissingSeparator), (err.token, err.offset, err.kind));
+
+    let err = parse_program("104,x1,99").unwrap_err();
+    assert_eq!((1, 4), (err.token, err.offset));
+    assert!(matches!(err.kind, ParseErrorKind::InvalidValue(ref s, _) if s == "x1"));
+
+    assert_eq!(ParseErrorKind::Empty, parse_program(" # nothing\n").unwrap_err().kind);
+}
+
+#[test]
+fn load_program_variants() {
+    let mut vm = IntcodeVM::new()
+        .with_program_slice(&[1,0,0,0,99]);
+    vm.run();
+    assert_eq!(vec![2,0,0,0,99], vm.dump_memory(0..5));
+
+    assert!(vm.try_load_program("1,0,,0,99").is_err());
+    assert_eq!(vec![2,0,0,0,99], vm.dump_memory(0..5));
+
+    let dir = ScratchDir::new("intcode-load");
+    let path = dir.write("program.txt", "# squares the value at 4\n2,4,4,5,99,0\n");
+    let mut vm = IntcodeVM::new()
+        .with_program_file(&path)
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    vm.run();
+    assert_eq!(vec![2,4,4,5,99,9801], vm.dump_memory(0..6));
+
+    assert!(vm.load_program_file(&path).is_err());
+
+    let path = dir.write("large.txt", &vec!["0"; 8193].join(","));
+    assert!(matches!(vm.load_program_file(&path), Err(LoadError::TooLarge { len: 8193 })));
+    assert_eq!(vec![2,4,4,5,99,9801], vm.dump_memory(0..6));
+}
+
+#[test]
+fn image_round_trip() {
+    // Doubles each input, so it stops partway waiting for more.
+    let program = "3,100,1002,100,2,100,4,100,109,-7,1105,1,0";
+    let mut vm = IntcodeVM::new()
+        .with_program(program);
+    let io_handle = vm.io();
+    io_handle.send(21);
+    while vm.step().is_none() {}
+    assert_eq!(Some(42), io_handle.recv());
+
+    let mut image = Vec::new();
+    vm.save_image(&mut image).unwrap();
+    assert!(image.len() < 160);
+
+    let mut resumed = IntcodeVM::new();
+    resumed.load_image(&image[..]).unwrap();
+    assert_eq!(vm.state(), resumed.state());
+    assert_eq!(vec![42], resumed.dump_memory(100..101));
+
+    let resumed_io = resumed.io();
+    resumed_io.send(-8);
+    while resumed.step().is_none() {}
+    assert_eq!(Some(-16), resumed_io.recv());
+}
+
+#[test]
+fn image_rejects_bad_data() {
+    let mut vm = IntcodeVM::new()
+        .with_program("1,0,0,0,99");
+    vm.run();
+
+    let mut image = VmState::to_bytes(&vm.state());
+    let last = image.len() - 5;
+    image[last] ^= 0x01;
+    assert!(matches!(VmState::from_bytes(&image), Err(ImageError::BadChecksum { .. })));
+
+    assert!(matches!(VmState::from_bytes(b"PNG\0 not an image"), Err(ImageError::BadMagic)));
+    assert!(matches!(VmState::from_bytes(&image[..20]), Err(ImageError::Truncated)));
+
+    // Well-formed images that still can't be loaded.
+    let mut state = vm.state();
+    state.instr_pointer = vm.memory_size();
+    assert!(matches!(VmState::from_bytes(&state.to_bytes()), Err(ImageError::BadInstrPointer(8192))));
+
+    let mut padded = VmState::to_bytes(&vm.state());
+    padded.truncate(padded.len() - 4);
+    padded.extend_from_slice(&[0, 0]);
+    let checksum = super::image::crc32(&padded);
+    padded.extend_from_slice(&checksum.to_le_bytes());
+    assert!(matches!(VmState::from_bytes(&padded), Err(ImageError::TrailingBytes(2))));
+
+    let before = vm.state();
+    assert!(vm.load_image(&image[..]).is_err());
+    assert_eq!(before, vm.state());
+}
+
+#[test]
+fn text_dump() {
+    let mut vm = IntcodeVM::new()
+        .with_program("1,9,10,3,2,3,11,0,99,30,40,50");
+    vm.run();
+    assert_eq!("3500,9,10,70,2,3,11,0,99,30,40,50", vm.dump_text_trimmed());
+
+    let full = vm.dump_text();
+    assert!(full.starts_with("3500,9,10,70,2,3,11,0,99,30,40,50,0,0,"));
+    let reloaded = IntcodeVM::new()
+        .with_program(&full);
+    assert_eq!(vm.memory_size(), full.split(',').count());
+    assert_eq!(vm.dump_memory(0..vm.memory_size()), reloaded.dump_memory(0..reloaded.memory_size()));
+
+    vm.load_program("99");
+    assert_eq!("99", vm.dump_text_trimmed());
+}
+
+/// Adds ten times each input to a score at 60 and counts rounds at 61.
+const SCORE_PROGRAM: &str = "3,62,1002,62,10,63,1,60,63,60,1001,61,1,61,4,60,1105,1,0";
+
+#[test]
+fn memory_diff() {
+    let mut vm = IntcodeVM::new()
+        .with_program(SCORE_PROGRAM);
+    let io_handle = vm.io();
+    let start = vm.state();
+
+    io_handle.send(3);
+    while vm.step().is_none() {}
+    let diff = vm.diff_since(&start);
+    assert_eq!(vec![
+        CellChange { addr: 60, old: 0, new: 30 },
+        CellChange { addr: 61, old: 0, new: 1 },
+        CellChange { addr: 62, old: 0, new: 3 },
+        CellChange { addr: 63, old: 0, new: 30 },
+    ], diff.changes);
+    assert_eq!(vec![61], diff.changed_by(1));
+    assert!(diff.to_string().lines().any(|l| l.split('|').map(str::trim).eq(["60", "0", "30", "+30"])));
+
+    let snapshot = vm.state();
+    assert!(vm.diff_since(&snapshot).is_empty());
+}
+
+#[test]
+fn memory_search() {
+    let mut vm = IntcodeVM::new()
+        .with_program(SCORE_PROGRAM);
+    let io_handle = vm.io();
+
+    let mut states = vec![vm.state()];
+    let mut scores = vec![0];
+    for &input in &[3, 5, 0, 2] {
+        io_handle.send(input);
+        while vm.step().is_none() {}
+        states.push(vm.state());
+        scores.push(io_handle.recv().unwrap());
+    }
+    assert_eq!(vec![60], find_tracking_cells(&states, &scores));
+
+    let mut search = MemorySearch::new(&states[0]);
+    search.changed(&states[1]);
+    assert_eq!(&[60, 61, 62, 63], search.candidates());
+    search.changed_by(&states[2], 1);
+    assert_eq!(&[61], search.candidates());
+    search.equal_to(&states[4], 4);
+    assert_eq!(&[61], search.candidates());
+}
+
+#[test]
+fn self_modification_report() {
+    // Overwrites its own (already executed) opcode.
+    let mut vm = IntcodeVM::new()
+        .with_self_modify_policy(SelfModifyPolicy::Report)
+        .with_program("1101,5,6,0,99");
+    let events = vm.subscribe();
+    vm.run();
+
+    let m = SelfModification { writer: 0, addr: 0, old: 1101, new: 11, pending: false };
+    assert_eq!(&[m], vm.self_modifications());
+    assert_eq!(vec![Event::Started, Event::SelfModified(m), Event::Halted], events.drain());
+    assert_eq!(vec![11,5,6,0,99], vm.dump_memory(0..5));
+
+    // Only writes data.
+    vm.load_program("3,9,8,9,10,9,4,9,99,-1,8");
+    vm.io().send(8);
+    vm.run();
+    assert!(vm.self_modifications().is_empty());
+    assert_eq!((0..=8).collect::<Vec<_>>(), vm.executed_cells());
+}
+
+#[test]
+fn self_modification_halt() {
+    // Patches the upcoming halt into a multiply.
+    let mut vm = IntcodeVM::new()
+        .with_self_modify_policy(SelfModifyPolicy::Halt)
+        .with_program("1,1,1,4,99,5,6,0,99");
+
+    let m = SelfModification { writer: 0, addr: 4, old: 99, new: 2, pending: true };
+    assert_eq!(Some(Event::Error(VmError::SelfModifyingWrite(m))), vm.step());
+    assert_eq!(Some(Event::Error(VmError::SelfModifyingWrite(m))), vm.step());
+    assert_eq!(vec![1,1,1,4,99], vm.dump_memory(0..5));
+
+    vm.load_program("1,1,1,4,99,5,6,0,99");
+    vm.run();
+    assert_eq!(vec![1,1,1,4,99], vm.dump_memory(0..5));
+
+    // The default policy doesn't track anything.
+    let mut vm = IntcodeVM::new()
+        .with_program("1,1,1,4,99,5,6,0,99");
+    vm.run();
+    assert_eq!(vec![30,1,1,4,2,5,6,0,99], vm.dump_memory(0..9));
+    assert!(vm.executed_cells().is_empty());
+}
+
+/// Outputs double each input, halting on a zero.
+const DOUBLER_PROGRAM: &str = "3,20,1006,20,14,1002,20,2,21,4,21,1105,1,0,99";
+
+#[test]
+fn async_block_on() {
+    let mut vm = IntcodeVM::new()
+        .with_program(DOUBLER_PROGRAM);
+    let io = vm.async_io();
+    block_on(io.send(21));
+    block_on(io.send(0));
+
+    assert_eq!(Ok(()), block_on(vm.run_async()));
+    assert_eq!(Some(42), block_on(io.recv()));
+    assert_eq!(None, block_on(io.recv()));
+}
+
+#[test]
+fn async_local_executor() {
+    let mut vm = IntcodeVM::new()
+        .with_program(DOUBLER_PROGRAM);
+    let io = vm.async_io();
+    let result = std::cell::RefCell::new(Vec::new());
+
+    let mut executor = LocalExecutor::new();
+    executor.spawn(async {
+        assert_eq!(Ok(()), vm.run_async().await);
+    });
+    executor.spawn(async {
+        for v in 1..=3 {
+            io.send(v).await;
+            let out = io.recv().await.unwrap();
+            result.borrow_mut().push(out);
+        }
+        io.send(0).await;
+        assert_eq!(None, io.recv().await);
+    });
+    assert_eq!(0, executor.run_until_stalled());
+    drop(executor);
+    assert_eq!(vec![2, 4, 6], result.into_inner());
+}
+
+#[test]
+fn async_stalls_without_input() {
+    let mut vm = IntcodeVM::new()
+        .with_program(DOUBLER_PROGRAM);
+    let io = vm.async_io();
+
+    let mut executor = LocalExecutor::new();
+    executor.spawn(async {
+        let _ = vm.run_async().await;
+    });
+    executor.spawn(async {
+        io.recv().await;
+    });
+    assert_eq!(2, executor.run_until_stalled());
+}
+
+#[test]
+fn io_queue_inspection() {
+    let mut vm = IntcodeVM::new()
+        .with_program(DOUBLER_PROGRAM);
+    let io = vm.io();
+    for v in [1, 2, 3] {
+        io.send(v);
+    }
+    assert_eq!(vec![1, 2, 3], io.pending_input());
+    assert_eq!(3, io.count_input());
+    io.clear_input();
+    assert_eq!(0, io.count_input());
+
+    io.send(5);
+    io.send(6);
+    while vm.step() != Some(Event::NeedInput) {}
+    assert_eq!(Some(10), io.peek());
+    assert_eq!(vec![10, 12], io.peek_all());
+    assert_eq!(2, io.count_output());
+    assert_eq!(Some(10), io.recv());
+
+    // Closing the input stops the machine where it would wait for more.
+    io.send(7);
+    io.close();
+    assert!(io.is_closed());
+    assert_eq!(Err(TrySendError::Closed(8)), io.try_send(8));
+    vm.run();
+    assert_eq!(vec![12, 14], io.dump());
+    assert_eq!(Some(Event::EndOfInput), vm.step());
+
+    // Loading a program opens it again.
+    vm.load_program(DOUBLER_PROGRAM);
+    assert!(!io.is_closed());
+}
+
+#[test]
+fn io_bounded_queues() {
+    let mut vm = IntcodeVM::new()
+        .with_program(DOUBLER_PROGRAM)
+        .with_input_capacity(2)
+        .with_output_capacity(1);
+    let io = vm.io();
+    io.send(1);
+    io.send(2);
+    assert_eq!(Err(TrySendError::Full(3)), io.try_send(3));
+
+    // The second output waits for the first to be taken.
+    assert_eq!(Some(Event::Output(2)), next_event(&mut vm));
+    assert_eq!(Some(Event::OutputFull), next_event(&mut vm));
+    assert_eq!(Some(2), io.recv());
+    while vm.step() != Some(Event::NeedInput) {}
+    assert_eq!(vec![4], io.dump());
+
+    // A producer on another thread is held back by the full input queue.
+    let producer = vm.io();
+    let thread = std::thread::spawn(move || {
+        for v in [3, 4, 5, 6, 0] {
+            producer.send(v);
+        }
+    });
+    let mut outputs = Vec::new();
+    loop {
+        match vm.step() {
+            Some(Event::Halted) => break,
+            Some(Event::NeedInput) => std::thread::yield_now(),
+            Some(Event::OutputFull) => outputs.extend(io.recv()),
+            _ => assert!(io.count_input() <= 2),
+        }
+    }
+    thread.join().unwrap();
+    outputs.extend(io.dump());
+    assert_eq!(vec![6, 8, 10, 12], outputs);
+
+    // Async readers make room for the machine as they go.
+    vm.load_program(DOUBLER_PROGRAM);
+    let io = vm.async_io();
+    let result = std::cell::RefCell::new(Vec::new());
+    let mut executor = LocalExecutor::new();
+    executor.spawn(async {
+        assert_eq!(Ok(()), vm.run_async().await);
+    });
+    executor.spawn(async {
+        for v in [7, 8, 0] {
+            io.send(v).await;
+        }
+        while let Some(out) = io.recv().await {
+            result.borrow_mut().push(out);
+        }
+    });
+    assert_eq!(0, executor.run_until_stalled());
+    drop(executor);
+    assert_eq!(vec![14, 16], result.into_inner());
+}
+
+#[test]
+fn vm_events() {
+    let mut vm = IntcodeVM::new().with_program(DOUBLER_PROGRAM);
+    let first = vm.subscribe();
+    let second = vm.subscribe_with_capacity(2);
+    let io = vm.io();
+
+    // Waiting for input is sent once, however often it's retried.
+    for _ in 0..10 {
+        assert_eq!(Some(Event::NeedInput), vm.step());
+    }
+    assert_eq!(vec![Event::Started, Event::NeedInput], first.drain());
+
+    io.send(3);
+    io.send(0);
+    assert_eq!(Event::Halted, vm.run());
+    assert_eq!(vec![Event::Output(6), Event::Halted], first.drain());
+
+    // Each subscriber gets every event, keeping only the newest.
+    assert_eq!(vec![Event::Output(6), Event::Halted], second.drain());
+    assert_eq!(2, second.missed());
+    drop(second);
+
+    // Loading a program starts over.
+    let third = vm.subscribe();
+    vm.load_program("104,1,99");
+    vm.run();
+    assert_eq!(vec![Event::Started, Event::Output(1), Event::Halted], third.drain());
+    assert_eq!(3, first.len());
+
+    // Unknown opcodes stop the machine where they are.
+    let error = Event::Error(VmError::UnknownOpcode { addr: 4, value: 42 });
+    vm.load_program("1,0,0,0,42");
+    assert_eq!(error, vm.run());
+    assert_eq!(Some(error), vm.step());
+    assert_eq!(4, vm.instr_pointer());
+}
+
+#[test]
+fn vm_breakpoints() {
+    let mut vm = IntcodeVM::new().with_program(DOUBLER_PROGRAM);
+    let io = vm.io();
+    assert!(vm.add_breakpoint(9));
+    assert!(!vm.add_breakpoint(9));
+    vm.add_breakpoint(0);
+    assert_eq!(vec![0, 9], vm.breakpoints());
+
+    // The instruction runs on the next step, and isn't hit again
+    // while it waits for input.
+    assert_eq!(Event::BreakpointHit(0), vm.run());
+    assert_eq!(Some(Event::NeedInput), vm.step());
+    io.send(4);
+    assert_eq!(None, vm.step());
+    assert_eq!(Event::BreakpointHit(9), vm.run());
+    assert_eq!(Some(Event::Output(8)), vm.step());
+
+    // Coming back around hits it again.
+    io.send(0);
+    assert_eq!(Event::BreakpointHit(0), vm.run());
+    assert!(vm.remove_breakpoint(0));
+    assert!(!vm.remove_breakpoint(0));
+    assert_eq!(Event::Halted, vm.run());
+
+    vm.clear_breakpoints();
+    assert!(vm.breakpoints().is_empty());
+}
+
+/// A scripted debugger client for `GdbStub`.
+struct GdbClient(std::net::TcpStream);
+
+impl GdbClient {
+    /// Send a packet and return the stub's reply.
+    fn request(&mut self, data: &str) -> String {
+        use std::io::{Read, Write};
+
+        let checksum = data.bytes().fold(0u8, |s, b| s.wrapping_add(b));
+        write!(self.0, "${}#{:02x}", data, checksum).unwrap();
+
+        let mut reply = Vec::new();
+        let mut byte = [0];
+        loop {
+            self.0.read_exact(&mut byte).unwrap();
+            match byte[0] {
+                b'+' if reply.is_empty() => {},
+                b'#' => break,
+                b => reply.push(b),
+            }
+        }
+
+        let mut sum = [0; 2];
+        self.0.read_exact(&mut sum).unwrap();
+        self.0.write_all(b"+").unwrap();
+        assert_eq!(b'$', reply.remove(0));
+
+        String::from_utf8(reply).unwrap()
+    }
+}
+
+#[test]
+fn gdb_session() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    // Counts m[100] from 0 to 3, then outputs it.
+    let vm = IntcodeVM::new()
+        .with_program("1001,100,1,100,1007,100,3,101,1005,101,0,4,100,99");
+    let io_handle = vm.io();
+    let stub = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        stream.set_nodelay(true).unwrap();
+        let mut stub = GdbStub::new(vm);
+        stub.serve(stream).unwrap();
+        stub.into_vm()
+    });
+
+    let mut gdb = GdbClient(std::net::TcpStream::connect(addr).unwrap());
+    gdb.0.set_nodelay(true).unwrap();
+    assert!(gdb.request("qSupported:multiprocess+").contains("qXfer:features:read+"));
+    assert!(gdb.request("qXfer:features:read:target.xml:0,ffb").contains(r#"name="rb""#));
+    assert_eq!("S05", gdb.request("?"));
+    assert_eq!("00000000000000000000000000000000", gdb.request("g"));
+
+    // First cell, 1001, as little-endian bytes.
+    assert_eq!("e903000000000000", gdb.request("m0,8"));
+
+    assert_eq!("S05", gdb.request("s"));
+    assert_eq!("2000000000000000", gdb.request("p0"));
+    assert_eq!("0100000000000000", gdb.request(&format!("m{:x},8", 100 * 8)));
+
+    // Break on the output instruction at cell 11.
+    assert_eq!("OK", gdb.request(&format!("Z0,{:x},1", 11 * 8)));
+    assert_eq!("S05", gdb.request("c"));
+    assert_eq!("5800000000000000", gdb.request("p0"));
+    assert_eq!("0300000000000000", gdb.request(&format!("m{:x},8", 100 * 8)));
+
+    // Patch the counter before it is output.
+    assert_eq!("OK", gdb.request(&format!("M{:x},2:2a00", 100 * 8)));
+    assert_eq!("OK", gdb.request(&format!("z0,{:x},1", 11 * 8)));
+    assert_eq!("W00", gdb.request("c"));
+    assert_eq!("", gdb.request("vCont?"));
+    assert_eq!("", gdb.request("é"));
+
+    // The instruction pointer can't be moved out of memory.
+    assert_eq!("E01", gdb.request("P0=0000010000000000"));
+    assert_eq!("E01", gdb.request("G00000100000000000000000000000000"));
+    assert_eq!("OK", gdb.request("P0=0000000000000000"));
+    assert_eq!("OK", gdb.request("D"));
+
+    let vm = stub.join().unwrap();
+    assert_eq!(vec![42], vm.dump_memory(100..101));
+    assert_eq!(Some(42), io_handle.recv());
+}
+
+#[test]
+fn screen_device() {
+    let mut vm = IntcodeVM::new()
+        .with_program("104,1,104,2,104,3,104,-1,104,0,104,50,104,4,104,2,104,2,104,7,99");
+    let mut screen = Screen::new();
+    Bus::new(&mut vm).attach(&mut screen).run().unwrap();
+
+    assert_eq!(3, screen.tile(1, 2));
+    assert_eq!(2, screen.tile(4, 2));
+    assert_eq!(0, screen.tile(0, 0));
+    assert_eq!(1, screen.count(2));
+    assert_eq!(Some(Point::new(1, 2)), screen.find(3));
+    assert_eq!(Some(50), screen.score());
+    assert_eq!("a..b\n", screen.render(|t| match t { 3 => 'a', 2 => 'b', _ => '.' }));
+
+    // The leftover message is incomplete and never reaches the screen.
+    assert_eq!(0, screen.tile(2, 7));
+
+    let mut vm = IntcodeVM::new()
+        .with_program("3,0,99");
+    let mut screen = Screen::new();
+    assert_eq!(Err(BusError::NoInput), Bus::new(&mut vm).attach(&mut screen).run());
+}
+
+#[test]
+fn turtle_device() {
+    // Paint white and turn left, paint white and turn right,
+    // then repaint the current color and turn left.
+    let mut vm = IntcodeVM::new()
+        .with_program("3,100,104,1,104,0,3,100,104,1,104,1,3,100,4,100,104,0,99");
+    let mut robot = Turtle::new(0);
+    Bus::new(&mut vm).attach(&mut robot).run().unwrap();
+
+    // Y grows downwards, so the panels above the start have negative y.
+    assert_eq!(Some(&1), robot.panels().get(Point::new(0, 0)));
+    assert_eq!(Some(&1), robot.panels().get(Point::new(-1, 0)));
+    assert_eq!(Some(&0), robot.panels().get(Point::new(-1, -1)));
+    assert_eq!(Point::new(-2, -1), robot.position());
+    assert_eq!("..\n##\n", robot.render(|c| if c == 1 { '#' } else { '.' }));
+}
+
+#[test]
+fn bus_broadcasts_messages() {
+    struct Recorder(Vec<Vec<i64>>);
+
+    impl Device for Recorder {
+        fn arity(&self) -> usize {
+            3
+        }
+
+        fn receive(&mut self, values: &[i64]) {
+            self.0.push(values.to_vec());
+        }
+    }
+
+    let mut vm = IntcodeVM::new()
+        .with_program("104,5,104,6,104,1,99");
+    let mut screen = Screen::new();
+    let mut recorder = Recorder(Vec::new());
+    Bus::new(&mut vm)
+        .attach(&mut recorder)
+        .attach(&mut screen)
+        .run()
+        .unwrap();
+
+    assert_eq!(vec![vec![5, 6, 1]], recorder.0);
+    assert_eq!(1, screen.tile(5, 6));
+}
+
+#[test]
+fn scheduler_pipeline() {
+    let mut first = IntcodeVM::new().with_program(DOUBLER_PROGRAM);
+    let mut second = IntcodeVM::new().with_program(DOUBLER_PROGRAM);
+    let mut seen = Vec::new();
+    {
+        let seen = &mut seen;
+        let mut scheduler = Scheduler::new();
+        let a = scheduler.add(&mut first);
+        let b = scheduler.add(&mut second);
+        scheduler.connect(a, b);
+        scheduler.on_output(b, move |value, outbox| {
+            seen.push(value);
+            // Stop both machines once the value has gone round twice.
+            if seen.len() < 2 {
+                outbox.send(a, value);
+            } else {
+                outbox.send(a, 0);
+                outbox.send(b, 0);
+            }
+        });
+
+        scheduler.send(a, 3);
+        assert_eq!(Ok(()), scheduler.run());
+        assert_eq!(&MachineState::Halted, scheduler.state(a));
+        assert_eq!(&MachineState::Halted, scheduler.state(b));
+    }
+
+    assert_eq!(vec![12, 48], seen);
+}
+
+#[test]
+fn scheduler_input_and_deadlock() {
+    let mut doubler = IntcodeVM::new().with_program(DOUBLER_PROGRAM);
+    let mut waiter = IntcodeVM::new().with_program("3,0,99");
+
+    let mut inputs = vec![0, 5].into_iter();
+    let mut scheduler = Scheduler::new();
+    let a = scheduler.add(&mut doubler);
+    let b = scheduler.add(&mut waiter);
+    scheduler.on_input(a, move || inputs.next_back());
+
+    assert_eq!(Err(SchedulerError::Deadlock(vec![b])), scheduler.run());
+    assert_eq!(vec![10], scheduler.take_output(a));
+    assert_eq!(&MachineState::Halted, scheduler.state(a));
+    assert_eq!(&MachineState::Waiting, scheduler.state(b));
+
+    scheduler.send(b, 1);
+    assert_eq!(Ok(()), scheduler.run());
+}
+
+#[test]
+fn symbolic_comparisons() {
+    // Day 5: outputs whether the input equals 8.
+    let explorer = SymbolicExplorer::new(&[3,9,8,9,10,9,4,9,99,-1,8]);
+    let paths = explorer.explore();
+    assert_eq!(2, paths.len());
+    assert!(paths.iter().all(|p| p.end == PathEnd::Halted && p.inputs == 1));
+    assert_eq!("in0 - 8 == 0", paths[0].constraints[0].to_string());
+
+    let model = explorer.solve_for_output(0, 1).unwrap();
+    assert_eq!(Some(&8), model.get(&Var::Input(0)));
+    let model = explorer.solve_for_output(0, 0).unwrap();
+    assert_ne!(Some(&8), model.get(&Var::Input(0)));
+
+    // Day 5: outputs whether the input is less than 8.
+    let explorer = SymbolicExplorer::new(&[3,3,1107,-1,8,3,4,3,99]);
+    let model = explorer.solve_for_output(0, 0).unwrap();
+    assert!(model[&Var::Input(0)] >= 8);
+}
+
+#[test]
+fn symbolic_arithmetic() {
+    // Outputs `3 * input + 7`.
+    let explorer = SymbolicExplorer::new(&[3,13,1002,13,3,13,1001,13,7,13,4,13,99,0]);
+    let paths = explorer.explore();
+    assert_eq!(1, paths.len());
+    assert_eq!("3*in0 + 7", paths[0].outputs[0].to_string());
+    assert_eq!(Some(&11), explorer.solve_for_output(0, 40).unwrap().get(&Var::Input(0)));
+    assert_eq!(None, explorer.solve_for_output(0, 41));
+
+    // Outputs the sum of two cells, like day 2's noun and verb.
+    let solver = Solver::new()
+        .with_var_bounds(Var::Cell(7), 0, 99)
+        .with_var_bounds(Var::Cell(8), 0, 99);
+    let explorer = SymbolicExplorer::new(&[1,7,8,0,4,0,99,0,0])
+        .with_symbolic_cell(7)
+        .with_symbolic_cell(8)
+        .with_solver(solver);
+    let model = explorer.solve_for_output(0, 150).unwrap();
+    let (noun, verb) = (model[&Var::Cell(7)], model[&Var::Cell(8)]);
+    assert_eq!(150, noun + verb);
+    assert!((0..=99).contains(&noun) && (0..=99).contains(&verb));
+    assert_eq!(None, explorer.solve_for_output(0, 250));
+
+    // A product of two inputs pins one of them to a concrete value.
+    let explorer = SymbolicExplorer::new(&[3,0,3,1,2,0,1,2,4,2,99]);
+    let paths = explorer.explore();
+    assert_eq!(1, paths.len());
+    assert_eq!(PathEnd::Halted, paths[0].end);
+    assert_eq!(1, paths[0].constraints.len());
+
+    // The input becomes part of an opcode, so it must be made concrete;
+    // a seed picks a value that gets past it.
+    let program = [3,11,1,11,12,6,0,13,99,0,0,0,3,42];
+    let paths = SymbolicExplorer::new(&program).explore();
+    assert!(paths[0].outputs.is_empty());
+    let mut seed = Model::new();
+    seed.insert(Var::Input(0), 1);
+    let paths = SymbolicExplorer::new(&program).with_seed(seed).explore();
+    assert_eq!(vec![LinExpr::constant(42)], paths[0].outputs);
+    assert_eq!("in0 - 1 == 0", paths[0].constraints[0].to_string());
+
+    // Inputs beyond the limit stop the path.
+    let paths = SymbolicExplorer::new(&[3,10,1105,1,0])
+        .with_max_inputs(3)
+        .explore();
+    assert_eq!(PathEnd::InputLimit, paths[0].end);
+    assert_eq!(3, paths[0].inputs);
+}
+
+#[test]
+fn solver_linear_cases() {
+    let (x, y) = (LinExpr::var(Var::Input(0)), LinExpr::var(Var::Input(1)));
+    let solver = Solver::new();
+
+    // x + y == 10, x - y >= 4, y > 2
+    let constraints = [
+        Constraint::new(x.clone() + y.clone() - LinExpr::constant(10), Rel::Eq),
+        Constraint::new(x.clone() - y.clone() - LinExpr::constant(4), Rel::Ge),
+        Constraint::new(LinExpr::constant(2) - y.clone(), Rel::Lt),
+    ];
+    let model = solver.solve(&constraints).model().unwrap();
+    assert!(constraints.iter().all(|c| c.holds(&model) == Some(true)));
+
+    // 2x == 1 has no integer solution.
+    let odd = Constraint::new(x.clone() * 2 - LinExpr::constant(1), Rel::Eq);
+    assert_eq!(SolveResult::Unsat, solver.solve(&[odd]));
+
+    // x != 0 within [0, 0].
+    let nonzero = Constraint::new(x.clone(), Rel::Ne);
+    assert_eq!(SolveResult::Unsat, solver.solve(&[nonzero.clone(), nonzero.negate()]));
+    assert_eq!(SolveResult::Unsat, Solver::new().with_bounds(0, 0).solve(&[nonzero]));
+
+    // x < y and y < x can't be settled by narrowing bounds alone.
+    let cycle = [
+        Constraint::new(x.clone() - y.clone(), Rel::Lt),
+        Constraint::new(y - x, Rel::Lt),
+    ];
+    assert_eq!(SolveResult::Unknown, solver.clone().with_budget(50).solve(&cycle));
+}
+
+#[test]
+fn disassembly() {
+    let program = [1105,1,7,104,99,99,0,3,30,1002,30,2,31,1101,2,3,32,1,31,32,31,4,31,99];
+    let dis = Disassembly::new(&program);
+    assert_eq!(7, dis.instructions().len());
+    assert!(!dis.is_code(3) && dis.is_code(7));
+    assert!(!dis.has_dynamic_jumps() && !dis.uses_relative() && !dis.is_self_modifying());
+
+    let listing = dis.to_string();
+    assert!(listing.starts_with("     0: JNZ #1, #7\n     3: DATA 104\n"));
+    assert!(listing.contains("     9: MUL [30], #2, [31]\n"));
+
+    // Day 9's quine uses relative addressing.
+    let dis = Disassembly::new(&[109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99]);
+    assert!(dis.uses_relative() && !dis.has_dynamic_jumps());
+    assert!(dis.to_string().contains("     2: OUT [rb - 1]\n"));
+}
+
+#[test]
+fn optimizer_folds_and_removes() {
+    let program = [1105,1,7,104,99,99,0,3,30,1002,30,2,31,1101,2,3,32,1,31,32,31,4,31,99];
+
+    let optimized = Optimizer::new().optimize(&program);
+    assert_eq!(1, optimized.folded);
+    assert_eq!(3, optimized.removed);
+    assert_eq!(vec![0, 0, 0, 0], optimized.program[3..7].to_vec());
+    assert_eq!(vec![1101, 5, 0, 32], optimized.program[13..17].to_vec());
+    assert_eq!(vec![25], run_with_inputs(&optimized.program, &[10]));
+
+    let compacted = Optimizer::new().with_compaction(true).optimize(&program);
+    assert_eq!(20, compacted.program.len());
+    assert_eq!(Some(&3), compacted.relocations.as_ref().unwrap().get(&7));
+    assert_eq!(vec![1105, 1, 3, 3, 26], compacted.program[..5].to_vec());
+    assert_eq!(vec![25], run_with_inputs(&compacted.program, &[10]));
+
+    // Writes into code leave every cell where it is.
+    let program = [1101,1,1,4,0,99];
+    let optimized = Optimizer::new().with_compaction(true).optimize(&program);
+    assert_eq!(None, optimized.relocations);
+    assert_eq!(program.to_vec(), optimized.program);
+}
+
+#[test]
+fn optimizer_leaves_relative_programs() {
+    // Outputs the second cell of the `1101` through relative mode,
+    // so folding that instruction would change what is printed.
+    let program = [109,2,1101,2,3,11,204,1,99];
+    let optimized = Optimizer::new().optimize(&program);
+    assert_eq!(0, optimized.folded);
+    assert_eq!(program.to_vec(), optimized.program);
+    assert_eq!(vec![2], run_with_inputs(&optimized.program, &[]));
+
+    // A computed jump could land anywhere, even inside a folded instruction.
+    let program = [1101,2,3,11,6,12,13,99,0,0,0,0,0,7];
+    let optimized = Optimizer::new().optimize(&program);
+    assert_eq!(0, optimized.folded);
+    assert_eq!(program.to_vec(), optimized.program);
+
+    // Code jumped away from before the relative read still folds.
+    let program = [1101,2,3,17,1105,1,7,109,1,204,16,99,0,0,0,0,0,0];
+    let optimized = Optimizer::new().optimize(&program);
+    assert_eq!(1, optimized.folded);
+    assert_eq!(vec![1101,5,0,17], optimized.program[..4].to_vec());
+    assert_eq!(vec![5], run_with_inputs(&optimized.program, &[]));
+}
+
+#[test]
+fn optimizer_preserves_puzzle_behavior() {
+    let load = |src: &str| {
+        let original = parse_program(src).unwrap();
+        let optimized = Optimizer::new().optimize(&original);
+        (original, optimized)
+    };
+
+    // Day 2, with its noun and verb patched in. It only writes into code
+    // that has already run, so the cells after its halt can go.
+    let (original, optimized) = load(include_str!("../../../day02/input.txt"));
+    assert_eq!(2, optimized.removed);
+    assert_ne!(original, optimized.program);
+    let result = |program: &[i64]| {
+        let mut vm = IntcodeVM::new().with_program_slice(program);
+        vm.patch_memory(1, &[12, 2]);
+        vm.run();
+        vm.dump_memory(0..1)
+    };
+    assert_eq!(result(&original), result(&optimized.program));
+
+    // Day 5 writes its first instruction from the input, but the tests
+    // after it still fold.
+    let (original, optimized) = load(include_str!("../../../day05/input.txt"));
+    assert!(optimized.folded > 0);
+    assert_ne!(original, optimized.program);
+    for input in [1, 5] {
+        assert_eq!(run_with_inputs(&original, &[input]), run_with_inputs(&optimized.program, &[input]));
+    }
+
+    // Day 7 only does arithmetic on its inputs, and jumps through a table,
+    // so has nothing to fold or remove.
+    let (original, optimized) = load(include_str!("../../../day07/input.txt"));
+    for phase in 0..5 {
+        assert_eq!(run_with_inputs(&original, &[phase, 7]), run_with_inputs(&optimized.program, &[phase, 7]));
+    }
+
+    let (original, optimized) = load(include_str!("../../../day09/input.txt"));
+    for input in [1, 2] {
+        assert_eq!(run_with_inputs(&original, &[input]), run_with_inputs(&optimized.program, &[input]));
+    }
+
+    let (original, optimized) = load(include_str!("../../../day11/input.txt"));
+    let paint = |program: &[i64]| {
+        let mut vm = IntcodeVM::new().with_program_slice(program);
+        let mut robot = Turtle::new(1);
+        Bus::new(&mut vm).attach(&mut robot).run().unwrap();
+        robot.render(|c| if c == 0 { ' ' } else { '#' })
+    };
+    assert_eq!(paint(&original), paint(&optimized.program));
+
+    let (original, optimized) = load(include_str!("../../../day13/input.txt"));
+    let draw = |program: &[i64]| {
+        let mut vm = IntcodeVM::new().with_program_slice(program);
+        let mut screen = Screen::new();
+        Bus::new(&mut vm).attach(&mut screen).run().unwrap();
+        screen.render(|t| char::from(b'0' + t as u8))
+    };
+    assert_eq!(draw(&original), draw(&optimized.program));
+}
+
+#[test]
+fn decompiler() {
+    // A countdown loop.
+    let program = [3,100,1006,100,14,4,100,1001,100,-1,100,1105,1,2,99];
+    let expected = "\
+fn main() {
+    g100 = input();
+    while (g100 != 0) {
+        output(g100);
+        g100 = g100 - 1;
+    }
+    halt();
+}
+";
+    assert_eq!(expected, Decompilation::new(&program).to_string());
+
+    // A call passing one argument, doubled in place.
+    let program = [109,50,21101,5,0,1,21101,13,0,0,1106,0,16,204,1,99,109,2,21202,-1,2,-1,109,-2,2106,0,0];
+    assert_eq!(vec![10], run_with_inputs(&program, &[]));
+    let decompiled = Decompilation::new(&program);
+    assert_eq!(vec![0, 16], decompiled.functions().keys().copied().collect::<Vec<_>>());
+    assert_eq!(Some(2), decompiled.function(16).unwrap().frame);
+    assert_eq!(1, decompiled.function(16).unwrap().params);
+    let expected = "\
+fn main() {
+    rb += 50;
+    f_16(5);
+    output(stack[rb + 1]);
+    halt();
+}
+
+fn f_16(p1) {
+    p1 = p1 * 2;
+    return;
+}
+";
+    assert_eq!(expected, decompiled.to_string());
+
+    // BOOST's recursive function.
+    let program = parse_program(include_str!("../../../day09/input.txt")).unwrap();
+    let decompiled = Decompilation::new(&program);
+    let f = decompiled.function(922).unwrap();
+    assert_eq!((Some(3), 1), (f.frame, f.params));
+    let text = decompiled.to_string();
+    assert!(text.contains("fn f_922(p1) {\n    var l1;\n    if (p1 >= 3) {\n        f_922(p1 - 1);\n"));
+
+    // The arcade's drawing routines.
+    let program = parse_program(include_str!("../../../day13/input.txt")).unwrap();
+    let decompiled = Decompilation::new(&program);
+    assert_eq!(vec![0, 393, 456, 549, 578, 601], decompiled.functions().keys().copied().collect::<Vec<_>>());
+    assert!(decompiled.to_string().contains("fn f_549(p1, p2, p3) {"));
+}
+
+/// Runs a program to completion with the given inputs, returning its output.
+fn run_with_inputs(program: &[i64], inputs: &[i64]) -> Vec<i64> {
+    let mut vm = IntcodeVM::new().with_program_slice(program);
+    let io = vm.io();
+    for &input in inputs {
+        io.send(input);
+    }
+    vm.run();
+
+    io.dump()
+}
+
+/// Steps a machine until a step returns an event.
+fn next_event(vm: &mut IntcodeVM) -> Option<Event> {
+    loop {
+        if let Some(event) = vm.step() {
+            return Some(event);
+        }
+    }
+}
+
 /// Count the number of digits in a number.
 fn count_digits(n: i64) -> usize {
     let mut n = n.abs();
     let mut digits = 0;
     while n > 0 {
         digits += 1;
-        n = n / 10;
+        n /= 10;
     }
     digits
-}
\ No newline at end of file
+}
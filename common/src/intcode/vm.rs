@@ -1,69 +1,139 @@
-//! Uses channels for input, output, and message passing.
+//! Uses shared queues for input and output, and reports what it does
+//! as events to any number of subscribers.
 //! Doing so hopefully simplifies future multithreading.
 
-use crossbeam_channel::{unbounded, Sender, Receiver};
+use std::collections::BTreeSet;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use super::async_vm::AsyncState;
+use super::diff::MemoryDiff;
+use super::event::{Event, Subscribers, Subscription, DEFAULT_SUBSCRIPTION_CAPACITY};
+use super::image::{ImageError, VmState};
+use super::parse::{parse_program, parse_program_file, LoadError, ParseError};
+use super::queue::{Queue, TrySendError};
+
+pub(crate) const MEMORY_SIZE: usize = 8192;
+
+/// Errors that stop the machine.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VmError {
+    SelfModifyingWrite(SelfModification),
+    /// The instruction at `addr` has an opcode that doesn't exist.
+    UnknownOpcode { addr: usize, value: i64 },
+}
+
+/// How the machine reacts to a program writing into its own code.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SelfModifyPolicy {
+    /// Don't track executed cells at all.
+    Ignore,
+    /// Perform the write, log it and send `Event::SelfModified`.
+    Report,
+    /// Refuse the write and halt with `Event::Error`.
+    Halt,
+}
 
-const MEMORY_SIZE: usize = 8192;
+/// A write that landed on a cell already executed as code,
+/// or belonging to the instruction about to be executed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SelfModification {
+    /// Address of the instruction performing the write.
+    pub writer: usize,
+    pub addr: usize,
+    pub old: i64,
+    pub new: i64,
+    /// Whether the cell belongs to the next instruction,
+    /// rather than one that has already run.
+    pub pending: bool,
+}
 
-pub enum Message {
-    HaltTerminate,
-    HaltNeedInput,
+/// A handle for giving a machine input and taking its output.
+/// Every handle to a machine shares the same queues.
+pub struct IOHandle<T, R> {
+    input: Queue<T>,
+    output: Queue<R>,
 }
 
-pub struct IOHandle<T, R>(Sender<T>, pub Receiver<R>);
 impl<T, R> IOHandle<T, R> {
 
-    /// Send `data` as input.
+    /// Send `data` as input, waiting for room if the input queue is bounded
+    /// and full. Panics if the input has been closed.
     pub fn send(&self, data: T) {
-        self.0.send(data)
-            .expect("unable to send input");
+        if self.input.push(data).is_err() {
+            panic!("unable to send input: input is closed");
+        }
+    }
+
+    /// Send `data` as input if there is room and the input is still open,
+    /// otherwise handing it back.
+    pub fn try_send(&self, data: T) -> Result<(), TrySendError<T>> {
+        self.input.try_push(data)
     }
 
     /// Receive output data, or `None` if there are none in the output queue.
     pub fn recv(&self) -> Option<R> {
-        if let Ok(data) = self.1.try_recv() {
-            Some(data)
-        } else {
-            None
-        }
+        self.output.pop()
     }
 
     /// Blocks the thread waiting for output data.
+    /// Returns `None` once the machine is dropped.
     pub fn wait_recv(&self) -> Option<R> {
-        if let Ok(data) = self.1.recv() {
-            Some(data)
-        } else {
-            None
-        }
+        self.output.wait_pop()
     }
 
     /// Collects all data from the output queue and returns it.
     pub fn dump(&self) -> Vec<R> {
-        self.1.try_iter().collect()
+        self.output.drain()
     }
 
     /// Get the number of queued outputs.
     pub fn count_output(&self) -> usize {
-        self.1.len()
+        self.output.len()
+    }
+
+    /// Get the number of inputs not yet read by the machine.
+    pub fn count_input(&self) -> usize {
+        self.input.len()
+    }
+
+    /// Discard every input not yet read by the machine.
+    pub fn clear_input(&self) {
+        self.input.drain();
+    }
+
+    /// Mark the end of the input. Once the machine has read everything
+    /// already queued, it halts with `Event::EndOfInput` rather
+    /// than waiting for more.
+    pub fn close(&self) {
+        self.input.close();
+    }
+
+    /// Whether the input has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.input.is_closed()
     }
 }
 
-pub struct Messenger<Message>(Sender<Message>, Receiver<Message>);
-impl<Message> Messenger<Message> {
+impl<T: Clone, R> IOHandle<T, R> {
 
-    /// Send a `Message` through the `Messenger`.
-    pub fn send(&self, msg: Message) {
-        self.0.send(msg)
-            .expect("unable to send message");
+    /// The inputs not yet read by the machine, in order, left queued.
+    pub fn pending_input(&self) -> Vec<T> {
+        self.input.snapshot()
     }
+}
 
-    /// Receive a `Message` from the `Messenger`.
-    pub fn recv(&self) -> Option<Message> {
-        if let Ok(msg) = self.1.try_recv() {
-            Some(msg)
-        } else {
-            None
-        }
+impl<T, R: Clone> IOHandle<T, R> {
+
+    /// The next output, left in the output queue.
+    pub fn peek(&self) -> Option<R> {
+        self.output.peek()
+    }
+
+    /// Every queued output, left in the output queue.
+    pub fn peek_all(&self) -> Vec<R> {
+        self.output.snapshot()
     }
 }
 
@@ -72,38 +142,59 @@ pub struct IntcodeVM {
     memory: [i64; MEMORY_SIZE],
     instr_pointer: usize,
     relative_base: i64,
-    input_sender: Sender<i64>,
-    input_recver: Receiver<i64>,
-    output_sender: Sender<i64>,
-    output_recver: Receiver<i64>,
-    message_sender: Sender<Message>,
-    message_recver: Receiver<Message>,
+    input: Queue<i64>,
+    output: Queue<i64>,
+    subscribers: Subscribers,
+    started: bool,
+    stalled: Option<Event>,
+    breakpoints: BTreeSet<usize>,
+    breakpoint_reported: Option<usize>,
     log_level: u8,
     input_fn: Option<fn() -> i64>,
     output_fn: Option<fn(i64)>,
+    instr_start: usize,
+    self_modify_policy: SelfModifyPolicy,
+    executed: Vec<bool>,
+    self_modifications: Vec<SelfModification>,
+    async_state: Arc<Mutex<AsyncState>>,
+}
+
+impl Drop for IntcodeVM {
+    /// Wakes anyone waiting on output that will now never come.
+    fn drop(&mut self) {
+        self.output.close();
+    }
+}
+
+impl Default for IntcodeVM {
+    fn default() -> IntcodeVM {
+        IntcodeVM::new()
+    }
 }
 
 impl IntcodeVM {
 
     /// Create a new Intcode virtual machine.
     pub fn new() -> IntcodeVM {
-        let (input_s, input_r) = unbounded();
-        let (output_s, output_r) = unbounded();
-        let (msg_s, msg_r) = unbounded();
-
         IntcodeVM {
             memory: [0; MEMORY_SIZE],
             instr_pointer: 0,
             relative_base: 0,
-            input_sender: input_s,
-            input_recver: input_r,
-            output_sender: output_s,
-            output_recver: output_r,
-            message_sender: msg_s,
-            message_recver: msg_r,
+            input: Queue::new(),
+            output: Queue::new(),
+            subscribers: Subscribers::default(),
+            started: false,
+            stalled: None,
+            breakpoints: BTreeSet::new(),
+            breakpoint_reported: None,
             log_level: 0,
             input_fn: None,
             output_fn: None,
+            instr_start: 0,
+            self_modify_policy: SelfModifyPolicy::Ignore,
+            executed: Vec::new(),
+            self_modifications: Vec::new(),
+            async_state: Arc::new(Mutex::new(AsyncState::default())),
         }
     }
 
@@ -115,12 +206,27 @@ impl IntcodeVM {
     }
 
     /// Load the specified program.
-    pub fn with_program(mut self, program: &String) -> IntcodeVM {
+    /// Panics with the location of the problem if it fails to parse.
+    pub fn with_program(mut self, program: &str) -> IntcodeVM {
         self.load_program(program);
 
         self
     }
 
+    /// Load an already parsed program.
+    pub fn with_program_slice(mut self, program: &[i64]) -> IntcodeVM {
+        self.load_program_slice(program);
+
+        self
+    }
+
+    /// Load the program stored in the file at `path`.
+    pub fn with_program_file<P: AsRef<Path>>(mut self, path: P) -> Result<IntcodeVM, LoadError> {
+        self.load_program_file(path)?;
+
+        Ok(self)
+    }
+
     pub fn with_input(mut self, f: fn() -> i64) -> IntcodeVM {
         self.input_fn = Some(f);
 
@@ -133,127 +239,233 @@ impl IntcodeVM {
         self
     }
 
+    /// Bound the input queue, so `IOHandle::send` waits for the machine
+    /// to read input once `capacity` values are queued.
+    pub fn with_input_capacity(self, capacity: usize) -> IntcodeVM {
+        self.input.set_capacity(Some(capacity));
+
+        self
+    }
+
+    /// Bound the output queue. Once `capacity` values are queued the
+    /// machine stops with `Event::OutputFull` until some are taken.
+    pub fn with_output_capacity(self, capacity: usize) -> IntcodeVM {
+        self.output.set_capacity(Some(capacity));
+
+        self
+    }
+
+    /// Track which cells get executed, reacting to writes into them
+    /// according to `policy`.
+    pub fn with_self_modify_policy(mut self, policy: SelfModifyPolicy) -> IntcodeVM {
+        self.self_modify_policy = policy;
+        self.clear_executed();
+
+        self
+    }
+
     /// Resets the Intcode virtual machine.
     pub fn reset(&mut self) {
         self.memory = [0; MEMORY_SIZE];
         self.instr_pointer = 0;
         self.relative_base = 0;
-
-        // Drain the channels.
-        self.input_recver.try_iter().for_each(drop);
-        self.output_recver.try_iter().for_each(drop);
-        self.message_recver.try_iter().for_each(drop);
+        self.clear_executed();
+        self.started = false;
+        self.stalled = None;
+        self.breakpoint_reported = None;
+
+        // Drain the queues.
+        self.input.drain();
+        self.input.reopen();
+        self.output.drain();
     }
 
     /// Load a program into memory.
-    pub fn load_program(&mut self, program: &String) {
-        let program = parse_program(program);
-        
+    /// Panics with the location of the problem if it fails to parse.
+    pub fn load_program(&mut self, program: &str) {
+        if let Err(e) = self.try_load_program(program) {
+            panic!("failed to parse program: {}", e);
+        }
+    }
+
+    /// Load a program into memory, returning an error if it fails to parse.
+    /// The machine is left untouched on failure.
+    pub fn try_load_program(&mut self, program: &str) -> Result<(), ParseError> {
+        let program = parse_program(program)?;
+        self.load_program_slice(&program);
+
+        Ok(())
+    }
+
+    /// Load an already parsed program into memory.
+    pub fn load_program_slice(&mut self, program: &[i64]) {
+        assert!(program.len() <= MEMORY_SIZE,
+            "program of {} values does not fit in memory", program.len());
+
         self.reset();
+        self.memory[..program.len()].copy_from_slice(program);
+    }
 
-        for i in 0..program.len() {
-            self.memory[i] = program[i];
+    /// Load the program stored in the file at `path` into memory.
+    /// The machine is left untouched on failure.
+    pub fn load_program_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), LoadError> {
+        let program = parse_program_file(path)?;
+        if program.len() > MEMORY_SIZE {
+            return Err(LoadError::TooLarge { len: program.len() });
         }
+        self.load_program_slice(&program);
+
+        Ok(())
     }
 
-    /// Step through the program until a `Message::HaltTerminate` is received.
-    pub fn run(&mut self) {
+    /// Step through the program until it halts, fails, hits a breakpoint,
+    /// or needs input after the input has been closed.
+    /// Returns the event it stopped on.
+    pub fn run(&mut self) -> Event {
         loop {
-            if let Ok(msg) = self.message_recver.try_recv() {
-                match msg {
-                    Message::HaltTerminate => {
-                        break;
-                    },
-                    Message::HaltNeedInput => {
-                        // std::thread::sleep(std::time::Duration::from_millis(100));
-                        continue;
-                    },
-                }
-            }
-
-            if let Some(Message::HaltTerminate) = self.step() {
-                break;
+            match self.step() {
+                Some(e @ Event::Halted) | Some(e @ Event::EndOfInput)
+                | Some(e @ Event::Error(_)) | Some(e @ Event::BreakpointHit(_)) => return e,
+                // Wait for another thread to give input or take output.
+                Some(Event::NeedInput) | Some(Event::OutputFull) => std::thread::yield_now(),
+                _ => {},
             }
         }
     }
 
     /// Process a single instruction.
-    /// Returns a `Message` as well as sending it to the
-    /// virtual machine's `Messenger`.
-    /// The returned `Message` can be used for careful synchronous
-    /// stepping while the `Messenger` is recommended for asynchronous
-    /// stepping.
-    pub fn step(&mut self) -> Option<Message> {
+    /// Returns the event the instruction caused, if any, which is also
+    /// sent to every subscriber. Subscribers additionally see `Started`
+    /// and `SelfModified`, while repeated stalls on the same instruction
+    /// are only sent to them once.
+    pub fn step(&mut self) -> Option<Event> {
+        if !self.started {
+            self.started = true;
+            self.subscribers.send(&Event::Started);
+        }
+
+        let addr = self.instr_pointer;
+        if self.breakpoints.contains(&addr) && self.breakpoint_reported != Some(addr) {
+            self.breakpoint_reported = Some(addr);
+            return self.emit(Event::BreakpointHit(addr));
+        }
+        self.breakpoint_reported = None;
+
+        let event = self.execute();
+        match &event {
+            Some(e) if e.is_stall() => {
+                if self.stalled.as_ref() != Some(e) {
+                    self.stalled = event.clone();
+                    self.subscribers.send(e);
+                }
+                // Don't report a breakpoint again while the
+                // instruction is being retried.
+                self.breakpoint_reported = Some(addr);
+            },
+            Some(e) => {
+                self.stalled = None;
+                self.subscribers.send(e);
+            },
+            None => self.stalled = None,
+        }
+
+        event
+    }
+
+    /// Send `event` to the subscribers and return it.
+    fn emit(&mut self, event: Event) -> Option<Event> {
+        self.subscribers.send(&event);
+
+        Some(event)
+    }
+
+    fn execute(&mut self) -> Option<Event> {
+        self.instr_start = self.instr_pointer;
         let (opcode, modes) = self.read_instr();
 
         match opcode {
 
             // Opcode: add OR mul
             // Params: read read write
-            01 | 02 => {
+            1 | 2 => {
                 let p1 = self.read_param(modes[0]);
                 let p2 = self.read_param(modes[1]);
 
                 let val = if opcode == 1 {
                     let v = p1 + p2;
-                    self.info(self.instr_pointer - 4,
+                    self.info(self.instr_pointer - 3,
                         || instr_encode("ADD", [Some(p1), Some(p2), Some(v)], modes));
 
                     v
                 } else {
                     let v = p1 * p2;
-                    self.info(self.instr_pointer - 4,
+                    self.info(self.instr_pointer - 3,
                         || instr_encode("MUL", [Some(p1), Some(p2), Some(v)], modes));
                     
                     v
                 };
 
-                self.write_param(val, modes[2]);
+                if let Err(m) = self.write_param(val, modes[2]) {
+                    return Some(self.refuse_write(m));
+                }
             },
             
             // Opcode: input
             // Params: write
-            03 => {
-                if let Ok(int) = self.input_recver.try_recv() {
+            3 => {
+                if let Some(int) = self.input.pop() {
                     self.info(self.instr_pointer - 1,
                         || instr_encode("NPT", [Some(int), None, None], modes));
 
-                    self.write_param(int, modes[0]);
+                    if let Err(m) = self.write_param(int, modes[0]) {
+                        return Some(self.refuse_write(m));
+                    }
                 } else if let Some(f) = self.input_fn {
                     let val = (f)();
                     self.info(self.instr_pointer - 1,
                         || instr_encode("NPT", [Some(val), None, None], modes));
                     
-                    self.write_param(val, modes[0]);
+                    if let Err(m) = self.write_param(val, modes[0]) {
+                        return Some(self.refuse_write(m));
+                    }
                 } else {
-                    self.message_sender.send(Message::HaltNeedInput)
-                        .expect("unable to send wait message");
-
                     // Rewind the instr_pointer
                     self.instr_pointer -= 1;
 
-                    return Some(Message::HaltNeedInput);
+                    return Some(if self.input.is_closed() {
+                        Event::EndOfInput
+                    } else {
+                        Event::NeedInput
+                    });
                 }
             },
 
             // Opcode: output
             // Params: read
-            04 => {
+            4 => {
+                if self.output_fn.is_none() && self.output.is_full() {
+                    // Rewind the instr_pointer
+                    self.instr_pointer -= 1;
+
+                    return Some(Event::OutputFull);
+                }
+
                 let val = self.read_param(modes[0]);
                 self.info(self.instr_pointer - 2,
                     || instr_encode("OPT", [Some(val), None, None], modes));
 
                 if let Some(f) = self.output_fn {
                     (f)(val);
-                } else {
-                    self.output_sender.send(val)
-                        .expect("unable to send output");
+                } else if self.output.push(val).is_err() {
+                    panic!("unable to send output: output is closed");
                 }
+
+                return Some(Event::Output(val));
             },
             
             // Opcode: jump-if-true OR jump-if-false
             // Params: read read
-            05 | 06 => {
+            5 | 6 => {
                 let p1 = self.read_param(modes[0]);
                 let p2 = self.read_param(modes[1]);
 
@@ -276,28 +488,26 @@ impl IntcodeVM {
             
             // Opcode: less than OR equals
             // Params: read read write
-            07 | 08 => {
+            7 | 8 => {
                 let p1 = self.read_param(modes[0]);
                 let p2 = self.read_param(modes[1]);
 
                 let cond = if opcode == 7 {
                     let val = p1 < p2;
-                    self.info(self.instr_pointer - 4,
+                    self.info(self.instr_pointer - 3,
                         || instr_encode("LT", [Some(p1), Some(p2), Some(val as i64)], modes));
 
                     val
                 } else {
                     let val = p1 == p2;
-                    self.info(self.instr_pointer - 4,
+                    self.info(self.instr_pointer - 3,
                         || instr_encode("EQ", [Some(p1), Some(p2), Some(val as i64)], modes));
 
                     val
                 };
 
-                if cond {
-                    self.write_param(1, modes[2]);
-                } else {
-                    self.write_param(0, modes[2]);
+                if let Err(m) = self.write_param(cond as i64, modes[2]) {
+                    return Some(self.refuse_write(m));
                 }
             },
 
@@ -316,22 +526,22 @@ impl IntcodeVM {
             // Params: none
             99 => {
                 self.info(self.instr_pointer - 1,
-                    || format!("HLT"));
-
-                self.message_sender.send(Message::HaltTerminate)
-                    .expect("unable to send message");
+                    || "HLT".to_string());
 
-                return Some(Message::HaltTerminate);
+                return Some(Event::Halted);
             },
             
             // Opcode: unknown
             // Params: perhaps many
-            __ => {
+            _ => {
                 self.error(self.instr_pointer - 1,
                     || format!("unknown opcode: {}", opcode));
 
-                self.message_sender.send(Message::HaltTerminate)
-                    .expect("unable to send message");
+                // Rewind so the fault is reported again if stepped.
+                self.instr_pointer = self.instr_start;
+                let value = self.memory[self.instr_start];
+
+                return Some(Event::Error(VmError::UnknownOpcode { addr: self.instr_start, value }));
             }
         }
 
@@ -340,11 +550,42 @@ impl IntcodeVM {
 
     /// Get a handle to the machines IO.
     pub fn io(&self) -> IOHandle<i64, i64> {
-        IOHandle(self.input_sender.clone(), self.output_recver.clone())
+        IOHandle {
+            input: self.input.clone(),
+            output: self.output.clone(),
+        }
+    }
+
+    /// Subscribe to the machine's events from now on, keeping at most
+    /// `DEFAULT_SUBSCRIPTION_CAPACITY` of them until they're taken.
+    pub fn subscribe(&mut self) -> Subscription {
+        self.subscribe_with_capacity(DEFAULT_SUBSCRIPTION_CAPACITY)
+    }
+
+    /// Subscribe to the machine's events from now on, keeping at most
+    /// `capacity` of them until they're taken.
+    pub fn subscribe_with_capacity(&mut self, capacity: usize) -> Subscription {
+        self.subscribers.subscribe(capacity)
     }
 
-    pub fn messenger(&self) -> Messenger<Message> {
-        Messenger(self.message_sender.clone(), self.message_recver.clone())
+    /// Stop before running the instruction at `addr`, with
+    /// `Event::BreakpointHit`. Returns whether it is a new breakpoint.
+    pub fn add_breakpoint(&mut self, addr: usize) -> bool {
+        self.breakpoints.insert(addr)
+    }
+
+    /// Returns whether there was a breakpoint at `addr`.
+    pub fn remove_breakpoint(&mut self, addr: usize) -> bool {
+        self.breakpoints.remove(&addr)
+    }
+
+    /// The addresses of every breakpoint, in order.
+    pub fn breakpoints(&self) -> Vec<usize> {
+        self.breakpoints.iter().copied().collect()
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
     }
 
     /// Get a reference to the machine's memory.
@@ -352,9 +593,175 @@ impl IntcodeVM {
         self.memory[range].to_vec()
     }
 
+    /// Overwrite memory starting at `addr` with `values`.
+    pub fn patch_memory(&mut self, addr: usize, values: &[i64]) {
+        self.memory[addr..addr + values.len()].copy_from_slice(values);
+    }
+
+    /// The number of cells of memory.
+    pub fn memory_size(&self) -> usize {
+        MEMORY_SIZE
+    }
+
+    /// The address of the next instruction to execute.
+    pub fn instr_pointer(&self) -> usize {
+        self.instr_pointer
+    }
+
+    pub fn set_instr_pointer(&mut self, addr: usize) {
+        self.instr_pointer = addr;
+    }
+
+    /// The base address used by relative mode parameters.
+    pub fn relative_base(&self) -> i64 {
+        self.relative_base
+    }
+
+    pub fn set_relative_base(&mut self, base: i64) {
+        self.relative_base = base;
+    }
+
+    /// Take a snapshot of the machine's memory and registers.
+    pub fn state(&self) -> VmState {
+        VmState {
+            memory: self.memory.to_vec(),
+            instr_pointer: self.instr_pointer,
+            relative_base: self.relative_base,
+        }
+    }
+
+    /// Restore memory and registers from a snapshot.
+    /// Pending input and output are left alone.
+    pub fn restore(&mut self, state: &VmState) {
+        assert!(state.memory.len() <= MEMORY_SIZE,
+            "state of {} cells does not fit in memory", state.memory.len());
+
+        self.memory = [0; MEMORY_SIZE];
+        self.memory[..state.memory.len()].copy_from_slice(&state.memory);
+        self.instr_pointer = state.instr_pointer;
+        self.relative_base = state.relative_base;
+        self.clear_executed();
+    }
+
+    /// Compare the machine's current state against an `earlier` snapshot.
+    pub fn diff_since(&self, earlier: &VmState) -> MemoryDiff {
+        earlier.diff(&self.state())
+    }
+
+    /// Write the machine's memory and registers as a binary memory image.
+    pub fn save_image<W: Write>(&self, writer: W) -> std::io::Result<()> {
+        self.state().write_to(writer)
+    }
+
+    /// Reset the machine and load a binary memory image.
+    /// The machine is left untouched on failure.
+    pub fn load_image<R: Read>(&mut self, reader: R) -> Result<(), ImageError> {
+        let state = VmState::read_from(reader)?;
+
+        self.reset();
+        self.restore(&state);
+
+        Ok(())
+    }
+
+    /// Export all of the machine's memory in the usual comma-separated
+    /// program form, one value per cell.
+    pub fn dump_text(&self) -> String {
+        self.state().to_text()
+    }
+
+    /// Export the machine's memory like `dump_text`, without the trailing
+    /// zero cells.
+    pub fn dump_text_trimmed(&self) -> String {
+        self.state().to_text_trimmed()
+    }
+
+    /// Writes that landed on executed code so far; only recorded when
+    /// the self-modify policy is `Report`.
+    pub fn self_modifications(&self) -> &[SelfModification] {
+        &self.self_modifications
+    }
+
+    /// Addresses of every cell executed as part of an instruction so far.
+    /// Empty unless a self-modify policy other than `Ignore` is set.
+    pub fn executed_cells(&self) -> Vec<usize> {
+        self.executed.iter()
+            .enumerate()
+            .filter(|(_, &e)| e)
+            .map(|(addr, _)| addr)
+            .collect()
+    }
+
+    /// Wakers shared with the machine's async handles.
+    pub(super) fn async_state(&self) -> Arc<Mutex<AsyncState>> {
+        self.async_state.clone()
+    }
+
+    /// Whether any input is queued.
+    pub(super) fn input_pending(&self) -> bool {
+        !self.input.is_empty()
+    }
+
+    /// Whether the output queue is at its capacity.
+    pub(super) fn output_full(&self) -> bool {
+        self.output.is_full()
+    }
+
+    /// Forget which cells have been executed.
+    fn clear_executed(&mut self) {
+        self.self_modifications.clear();
+        self.executed = match self.self_modify_policy {
+            SelfModifyPolicy::Ignore => Vec::new(),
+            _ => vec![false; MEMORY_SIZE],
+        };
+    }
+
+    /// Marks the cell at the instruction pointer as executed.
+    fn mark_executed(&mut self) {
+        if let Some(e) = self.executed.get_mut(self.instr_pointer) {
+            *e = true;
+        }
+    }
+
+    /// Checks whether writing `value` to `addr` modifies code,
+    /// in which case the write is described.
+    /// Called once the whole current instruction has been read.
+    fn check_write(&self, addr: usize, value: i64) -> Option<SelfModification> {
+        if self.executed.is_empty() {
+            return None;
+        }
+
+        let next = self.instr_pointer;
+        let next_len = self.memory.get(next).map_or(1, |&v| instr_len(v));
+        let pending = addr >= next && addr < next + next_len;
+
+        if pending || self.executed[addr] {
+            Some(SelfModification {
+                writer: self.instr_start,
+                addr,
+                old: self.memory[addr],
+                new: value,
+                pending,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Halts on a write refused by the self-modify policy, rewinding so
+    /// the faulting instruction is reported again if stepped.
+    fn refuse_write(&mut self, m: SelfModification) -> Event {
+        self.error(m.writer,
+            || format!("write to code at {} ({} -> {})", m.addr, m.old, m.new));
+        self.instr_pointer = self.instr_start;
+
+        Event::Error(VmError::SelfModifyingWrite(m))
+    }
+
     /// Reads an instruction from memory at the instruction pointer
     /// as well as returns the modes for each parameter.
     fn read_instr(&mut self) -> (u8, [u8; 3]) {
+        self.mark_executed();
         let value = self.memory[self.instr_pointer];
         self.instr_pointer += 1;
         
@@ -370,6 +777,7 @@ impl IntcodeVM {
 
     /// Reads a parameter from memory.
     fn read_param(&mut self, mode: u8) -> i64 {
+        self.mark_executed();
         let param = self.memory[self.instr_pointer];
         self.instr_pointer += 1;
 
@@ -384,25 +792,42 @@ impl IntcodeVM {
             2 => self.memory[(self.relative_base + param) as usize],
 
             // Invalid, return garbage.
-            _ => std::i64::MAX,
+            _ => i64::MAX,
         }
     }
 
     /// Write a value to memory.
-    fn write_param(&mut self, value: i64, mode: u8) {
+    /// Fails if the self-modify policy refuses a write into code.
+    fn write_param(&mut self, value: i64, mode: u8) -> Result<(), SelfModification> {
+        self.mark_executed();
         let param = self.memory[self.instr_pointer];
         self.instr_pointer += 1;
         
-        match mode {
+        let addr = match mode {
             // Position
-            0 => self.memory[param as usize] = value,
+            0 => param as usize,
 
             // Relative
-            2 => self.memory[(self.relative_base + param) as usize] = value,
+            2 => (self.relative_base + param) as usize,
 
             // Invalid, do nothing.
-            _ => {},
+            _ => return Ok(()),
+        };
+
+        if let Some(m) = self.check_write(addr, value) {
+            if self.self_modify_policy == SelfModifyPolicy::Halt {
+                return Err(m);
+            }
+
+            self.info(m.writer,
+                || format!("write to code at {} ({} -> {})", m.addr, m.old, m.new));
+            self.self_modifications.push(m);
+            self.subscribers.send(&Event::SelfModified(m));
         }
+
+        self.memory[addr] = value;
+
+        Ok(())
     }
 
     /// Prints an informative message, including the current instruction
@@ -422,13 +847,6 @@ impl IntcodeVM {
     }
 }
 
-/// Parse a program from a `String`, returning it as a `Vec<i64>`.
-pub fn parse_program(program: &String) -> Vec<i64> {
-    program.trim().split(',')
-        .map(|s| s.parse::<i64>().unwrap())
-        .collect()
-}
-
 
 pub fn default_input() -> i64 {
     let mut line = String::new();
@@ -441,6 +859,16 @@ pub fn default_input() -> i64 {
         .expect("input was not an integer")
 }
 
+/// The number of cells taken by the instruction starting with `value`.
+fn instr_len(value: i64) -> usize {
+    match value % 100 {
+        1 | 2 | 7 | 8 => 4,
+        5 | 6 => 3,
+        3 | 4 | 9 => 2,
+        _ => 1,
+    }
+}
+
 /// Turns an instruction into a String, for printing purposes.
 fn instr_encode(opcode: &'static str, params: [Option<i64>; 3], modes: [u8; 3]) -> String {
     let mut out = String::new();
@@ -1,40 +1,65 @@
+pub mod executor;
+pub mod grid;
+pub mod ocr;
+mod cli;
+mod examples;
+mod input;
 mod intcode;
-pub use intcode::{IntcodeVM, Message as IntcodeMessage, default_input};
+mod solution;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub use intcode::{IntcodeVM, Event as IntcodeEvent, Subscription, DEFAULT_SUBSCRIPTION_CAPACITY, default_input};
+pub use intcode::{SelfModification, SelfModifyPolicy, TrySendError, VmError};
+pub use intcode::{AsyncIOHandle, RunFuture};
+pub use intcode::{Bus, BusError, Device, Screen, Turtle};
+pub use intcode::{Constraint, LinExpr, Model, Rel, SolveResult, Solver, Var};
+pub use intcode::{PathEnd, SymbolicExplorer, SymbolicPath};
+pub use intcode::{MachineState, Outbox, Scheduler, SchedulerError, VmId};
+pub use intcode::{GdbStub, RemoteStream};
+pub use intcode::{parse_program, parse_program_file, LoadError, ParseError, ParseErrorKind};
+pub use intcode::{ImageError, VmState};
+pub use intcode::{find_tracking_cells, CellChange, MemoryDiff, MemorySearch};
+pub use intcode::{Disassembly, Instruction, Opcode, Param, Optimized, Optimizer};
+pub use intcode::{Decompilation, Function};
 
-pub use std::io::Read;
-pub use std::fs::File;
+pub use cli::Args;
+pub use examples::{find_examples, run_examples, Example};
+pub use input::{get_input, read_input, InputError, InputSource, Params, INPUT_DIR_VAR};
+pub use solution::{Answer, Parsed, Puzzle, Solution};
 
 use std::path::Path;
-use std::time::Instant;
-
-/// Setup a BufReader on the provided `input.txt`.
-pub fn get_input(p: &'static str) -> String {
-    let mut input = String::new();
-    let mut file = File::open(Path::new(p).join("input.txt"))
-        .expect("bad path");
-    
-    file.read_to_string(&mut input)
-        .expect("failed to read input to String");
-    
-    input
-}
 
-/// Runs a specified part, or both.
-pub fn part_selector(input: &String, a: fn(&String), b: fn(&String)) {
-    let args: Vec<String> = std::env::args().collect();
-
-    let start = Instant::now();
-    if args.len() == 2 && args[1].as_str() == "1" {
-        a(input);
-    } else if args.len() == 2 && args[1].as_str() == "2" {
-        b(input);
-    } else {
-        a(input);
-        b(input);
-    }
+/// Runs the part picked on the command line, or both, printing the answers.
+/// `dir` is the day's directory, holding its input.
+pub fn part_selector<S: Solution>(dir: &str) {
+    let mut args = std::env::args();
+    let name = args.next()
+        .and_then(|a| Path::new(&a).file_stem().map(|s| s.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "day".to_string());
+
+    let args = match Args::parse(args) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", cli::usage(&name));
+            return;
+        },
+        Err(e) => {
+            eprintln!("{}: {}\n\n{}", name, e, cli::usage(&name));
+            std::process::exit(2);
+        },
+    };
 
-    let time = Instant::now().duration_since(start);
-    println!("Time: {}ms ({}us)", time.as_millis(), time.as_micros());
+    let result = args.read_input(dir)
+        .and_then(|input| Ok((input, args.params(dir)?)))
+        .map_err(|e| e.to_string())
+        .and_then(|(input, params)| {
+            cli::solve::<S>(&args, &input, &params, &mut std::io::stdout().lock()).map_err(|e| e.to_string())
+        });
+
+    if let Err(e) = result {
+        eprintln!("{}: {}", name, e);
+        std::process::exit(1);
+    }
 }
 
 /// Read a line from stdin.
@@ -0,0 +1,176 @@
+//! Reading the block letters some puzzles draw as their answer.
+//! Advent of Code uses two fonts: letters 6 pixels tall, most of them
+//! 4 wide, and letters 10 pixels tall and 6 wide. Letters are told
+//! apart by the blank columns between them.
+
+use std::error::Error;
+use std::fmt;
+
+const FONT_6: &[(char, &[&str])] = &[
+    ('A', &[".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', &["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', &[".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', &["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', &["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', &[".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', &["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', &["###", ".#.", ".#.", ".#.", ".#.", "###"]),
+    ('J', &["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', &["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', &["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', &[".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', &["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', &["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', &[".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', &["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', &["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+    ('Z', &["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+const FONT_10: &[(char, &[&str])] = &[
+    ('A', &["..##..", ".#..#.", "#....#", "#....#", "#....#",
+            "######", "#....#", "#....#", "#....#", "#....#"]),
+    ('B', &["#####.", "#....#", "#....#", "#....#", "#####.",
+            "#....#", "#....#", "#....#", "#....#", "#####."]),
+    ('C', &[".####.", "#....#", "#.....", "#.....", "#.....",
+            "#.....", "#.....", "#.....", "#....#", ".####."]),
+    ('E', &["######", "#.....", "#.....", "#.....", "#####.",
+            "#.....", "#.....", "#.....", "#.....", "######"]),
+    ('F', &["######", "#.....", "#.....", "#.....", "#####.",
+            "#.....", "#.....", "#.....", "#.....", "#....."]),
+    ('G', &[".####.", "#....#", "#.....", "#.....", "#.....",
+            "#..###", "#....#", "#....#", "#...##", ".###.#"]),
+    ('H', &["#....#", "#....#", "#....#", "#....#", "######",
+            "#....#", "#....#", "#....#", "#....#", "#....#"]),
+    ('J', &["...###", "....#.", "....#.", "....#.", "....#.",
+            "....#.", "....#.", "#...#.", "#...#.", ".###.."]),
+    ('K', &["#....#", "#...#.", "#..#..", "#.#...", "##....",
+            "##....", "#.#...", "#..#..", "#...#.", "#....#"]),
+    ('L', &["#.....", "#.....", "#.....", "#.....", "#.....",
+            "#.....", "#.....", "#.....", "#.....", "######"]),
+    ('N', &["#....#", "##...#", "##...#", "#.#..#", "#.#..#",
+            "#..#.#", "#..#.#", "#...##", "#...##", "#....#"]),
+    ('P', &["#####.", "#....#", "#....#", "#....#", "#####.",
+            "#.....", "#.....", "#.....", "#.....", "#....."]),
+    ('R', &["#####.", "#....#", "#....#", "#....#", "#####.",
+            "#..#..", "#...#.", "#...#.", "#....#", "#....#"]),
+    ('X', &["#....#", "#....#", ".#..#.", ".#..#.", "..##..",
+            "..##..", ".#..#.", ".#..#.", "#....#", "#....#"]),
+    ('Z', &["######", ".....#", ".....#", "....#.", "...#..",
+            "..#...", ".#....", "#.....", "#.....", "######"]),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcrError {
+    /// Once blank rows are trimmed, the image is neither 6 nor 10 pixels
+    /// tall, as the fonts are.
+    Height(usize),
+    /// Some glyphs aren't letters of the font. They are given by the
+    /// column each starts at, with what could be read, where `?` marks
+    /// each of them.
+    Unrecognized { columns: Vec<usize>, text: String },
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OcrError::Height(h) => write!(f, "letters are 6 or 10 pixels tall, not {}", h),
+            OcrError::Unrecognized { columns, text } => {
+                let columns: Vec<String> = columns.iter().map(usize::to_string).collect();
+                write!(f, "unrecognized glyphs at columns {}, reading {:?}", columns.join(", "), text)
+            },
+        }
+    }
+}
+
+impl Error for OcrError {}
+
+/// Reads the letters drawn by the lit pixels in `rows`.
+pub fn recognize<R: AsRef<[bool]>>(rows: &[R]) -> Result<String, OcrError> {
+    let blank = |row: &&R| !row.as_ref().iter().any(|&lit| lit);
+    let first = rows.iter().position(|r| !blank(&r)).unwrap_or(rows.len());
+    let last = rows.iter().rposition(|r| !blank(&r)).map_or(first, |l| l + 1);
+    let rows = &rows[first..last];
+
+    let font = match rows.len() {
+        6 => FONT_6,
+        10 => FONT_10,
+        h => return Err(OcrError::Height(h)),
+    };
+
+    let width = rows.iter().map(|r| r.as_ref().len()).max().unwrap_or(0);
+    let lit = |x: usize, y: usize| rows[y].as_ref().get(x).copied().unwrap_or(false);
+    let column_blank = |x: usize| (0..rows.len()).all(|y| !lit(x, y));
+
+    let mut text = String::new();
+    let mut columns = Vec::new();
+    let mut x = 0;
+    while x < width {
+        if column_blank(x) {
+            x += 1;
+            continue;
+        }
+
+        let start = x;
+        while x < width && !column_blank(x) {
+            x += 1;
+        }
+        let glyph: Vec<String> = (0..rows.len())
+            .map(|y| (start..x).map(|x| if lit(x, y) { '#' } else { '.' }).collect())
+            .collect();
+
+        match font.iter().find(|(_, shape)| *shape == &glyph[..]) {
+            Some(&(letter, _)) => text.push(letter),
+            None => {
+                text.push('?');
+                columns.push(start);
+            },
+        }
+    }
+
+    if columns.is_empty() {
+        Ok(text)
+    } else {
+        Err(OcrError::Unrecognized { columns, text })
+    }
+}
+
+#[cfg(test)]
+fn draw(text: &str, font: &[(char, &[&str])], gap: usize) -> Vec<Vec<bool>> {
+    let height = font[0].1.len();
+    let mut rows = vec![Vec::new(); height];
+    for c in text.chars() {
+        let shape = font.iter().find(|(l, _)| *l == c).map_or(&[][..], |(_, s)| s);
+        for (y, row) in rows.iter_mut().enumerate() {
+            row.extend(shape.get(y).unwrap_or(&"#").chars().map(|p| p == '#'));
+            row.extend(std::iter::repeat_n(false, gap));
+        }
+    }
+
+    rows
+}
+
+#[test]
+fn reads_letters() {
+    let all: String = FONT_6.iter().map(|(c, _)| c).collect();
+    assert_eq!(Ok(all.clone()), recognize(&draw(&all, FONT_6, 1)));
+    let all: String = FONT_10.iter().map(|(c, _)| c).collect();
+    assert_eq!(Ok(all.clone()), recognize(&draw(&all, FONT_10, 2)));
+
+    // Blank rows around the letters don't matter.
+    let mut rows = draw("HI", FONT_6, 1);
+    rows.insert(0, vec![false; 3]);
+    rows.push(Vec::new());
+    assert_eq!(Ok("HI".to_string()), recognize(&rows));
+}
+
+#[test]
+fn reports_unknown_glyphs() {
+    // `?` isn't in the font, so is drawn as a bar.
+    let rows = draw("A?B?", FONT_6, 1);
+    let unrecognized = OcrError::Unrecognized { columns: vec![5, 12], text: "A?B?".to_string() };
+    assert_eq!(Err(unrecognized), recognize(&rows));
+    assert_eq!(Err(OcrError::Height(2)), recognize(&[vec![true], vec![true]]));
+    assert_eq!(Err(OcrError::Height(0)), recognize::<Vec<bool>>(&[]));
+    assert!(recognize(&rows).unwrap_err().to_string().starts_with("unrecognized glyphs at columns 5, 12"));
+}
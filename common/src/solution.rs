@@ -0,0 +1,199 @@
+//! The shape every day's solution takes, so answers can be printed,
+//! checked, timed and collected the same way for each of them.
+
+use std::any::Any;
+use std::fmt;
+
+use super::input::{InputError, Params};
+use super::ocr;
+
+/// A puzzle answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Number(i64),
+    Text(String),
+    /// Lit pixels, row by row, to be read by a human.
+    Image(Vec<Vec<bool>>),
+    /// A part that hasn't been solved yet, as a new day's parts start out.
+    Unsolved,
+}
+
+impl Answer {
+
+    /// Reads an image from rendered text, where `lit` marks the lit pixels.
+    pub fn image_from_text(text: &str, lit: char) -> Answer {
+        Answer::Image(text.lines()
+            .map(|row| row.chars().map(|c| c == lit).collect())
+            .collect())
+    }
+
+    /// Reads the block letters drawn in an image, so the answer can be
+    /// checked as text. Anything else, or an image that isn't made of
+    /// known letters, is left as it was.
+    pub fn read_letters(self) -> Answer {
+        match self {
+            Answer::Image(rows) => match ocr::recognize(&rows) {
+                Ok(text) => Answer::Text(text),
+                Err(_) => Answer::Image(rows),
+            },
+            answer => answer,
+        }
+    }
+
+    /// Whether the answer takes up more than a line when displayed.
+    pub fn is_multiline(&self) -> bool {
+        matches!(self, Answer::Image(_))
+    }
+
+    /// The answer as a JSON value. Images are arrays of rows,
+    /// with `#` for lit pixels and `.` for the rest.
+    pub fn to_json(&self) -> String {
+        match self {
+            Answer::Number(n) => n.to_string(),
+            Answer::Text(s) => json_string(s),
+            Answer::Unsolved => "null".to_string(),
+            Answer::Image(rows) => {
+                let rows: Vec<String> = rows.iter()
+                    .map(|row| json_string(&row.iter().map(|&p| if p { '#' } else { '.' }).collect::<String>()))
+                    .collect();
+
+                format!("[{}]", rows.join(", "))
+            },
+        }
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+            Answer::Unsolved => write!(f, "unsolved"),
+            Answer::Image(rows) => {
+                for (y, row) in rows.iter().enumerate() {
+                    if y > 0 {
+                        writeln!(f)?;
+                    }
+                    for &pixel in row {
+                        write!(f, "{}", if pixel { '█' } else { ' ' })?;
+                    }
+                }
+
+                Ok(())
+            },
+        }
+    }
+}
+
+/// Quotes `s` as a JSON string.
+fn json_string(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+macro_rules! answer_from_int {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(n: $t) -> Answer {
+                Answer::Number(n as i64)
+            }
+        })*
+    };
+}
+
+answer_from_int!(i16, i32, i64, u16, u32, u64, usize);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Answer {
+        Answer::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Answer {
+        Answer::Text(s.to_string())
+    }
+}
+
+/// A day's puzzle. The input is parsed once and shared by both parts.
+pub trait Solution {
+    type Input;
+
+    fn parse(input: &str) -> Self::Input;
+
+    /// Parse with the puzzle's parameters, for puzzles that take any.
+    /// Fails if a parameter is invalid, or doesn't suit the input.
+    fn parse_with(input: &str, _params: &Params) -> Result<Self::Input, InputError> {
+        Ok(Self::parse(input))
+    }
+
+    fn part_one(input: &Self::Input) -> Answer;
+    fn part_two(input: &Self::Input) -> Answer;
+}
+
+/// A day's solution with its input type hidden, so every day
+/// can be listed and run together.
+#[derive(Clone, Copy)]
+pub struct Puzzle {
+    day: u8,
+    dir: &'static str,
+    parse: fn(&str, &Params) -> Result<Parsed, InputError>,
+    parts: [fn(&Parsed) -> Answer; 2],
+}
+
+/// A day's parsed input, as made by `Puzzle::parse`.
+pub struct Parsed(Box<dyn Any>);
+
+impl Puzzle {
+
+    /// The puzzle for `day`, solved by `S`, whose input lives in `dir`.
+    pub fn new<S: Solution>(day: u8, dir: &'static str) -> Puzzle
+    where S::Input: 'static {
+        Puzzle {
+            day,
+            dir,
+            parse: |input, params| S::parse_with(input, params).map(|input| Parsed(Box::new(input))),
+            parts: [
+                |parsed| S::part_one(parsed.get::<S::Input>()),
+                |parsed| S::part_two(parsed.get::<S::Input>()),
+            ],
+        }
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// The directory holding the day's input.
+    pub fn dir(&self) -> &'static str {
+        self.dir
+    }
+
+    pub fn parse(&self, input: &str, params: &Params) -> Result<Parsed, InputError> {
+        (self.parse)(input, params)
+    }
+
+    /// Solve `part` (1 or 2) from input parsed by this puzzle.
+    pub fn solve(&self, parsed: &Parsed, part: u8) -> Answer {
+        assert!(part == 1 || part == 2, "there is no part {}", part);
+
+        (self.parts[part as usize - 1])(parsed)
+    }
+}
+
+impl Parsed {
+    fn get<T: 'static>(&self) -> &T {
+        self.0.downcast_ref().expect("input parsed by a different puzzle")
+    }
+}
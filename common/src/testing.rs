@@ -0,0 +1,81 @@
+//! Fixtures shared by the tests here and in the runner, which gets them
+//! through the `testing` feature.
+
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+use super::input::{InputError, Params};
+use super::solution::{Answer, Solution};
+
+/// An empty directory for a test's files, removed again when dropped.
+pub struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+
+    /// A fresh directory in the system's temporary directory, named
+    /// after `name` and this process so tests running at once don't meet.
+    pub fn new(name: &str) -> ScratchDir {
+        let path = std::env::temp_dir().join(format!("aoc-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+
+        ScratchDir(path)
+    }
+
+    /// Writes `text` to `file`, a path within the directory, making any
+    /// directories it needs. Returns the file's full path.
+    pub fn write(&self, file: &str, text: &str) -> PathBuf {
+        let path = self.0.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, text).unwrap();
+
+        path
+    }
+
+    /// The directory's path as a string, as days give their directories.
+    pub fn as_str(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+}
+
+impl Deref for ScratchDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// A puzzle over comma-separated numbers, with the `extra` parameter's
+/// value appended as one more when given. Part one sums them, and part two
+/// draws which of them are positive.
+pub struct Sum;
+
+impl Solution for Sum {
+    type Input = Vec<i64>;
+
+    fn parse(input: &str) -> Vec<i64> {
+        input.split(',').map(|v| v.trim().parse().unwrap()).collect()
+    }
+
+    fn parse_with(input: &str, params: &Params) -> Result<Vec<i64>, InputError> {
+        let mut values = Sum::parse(input);
+        values.extend(params.get::<i64>("extra")?);
+
+        Ok(values)
+    }
+
+    fn part_one(values: &Vec<i64>) -> Answer {
+        values.iter().sum::<i64>().into()
+    }
+
+    fn part_two(values: &Vec<i64>) -> Answer {
+        Answer::Image(vec![values.iter().map(|&v| v > 0).collect()])
+    }
+}
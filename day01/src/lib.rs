@@ -0,0 +1,78 @@
+//! For now, both parts of a problem pair are in their own function.
+//! As days progress, I'll work on reducing code duplication.
+//! I kind of like the `part_selector` function right now though.
+
+use common::*;
+
+pub struct Day01;
+
+/// Day 1, for running alongside the other days.
+pub fn puzzle() -> Puzzle {
+    Puzzle::new::<Day01>(1, env!("CARGO_MANIFEST_DIR"))
+}
+
+impl Solution for Day01 {
+    /// The mass of each module.
+    type Input = Vec<i32>;
+
+    fn parse(input: &str) -> Vec<i32> {
+        input.lines()
+            .map(|line| line.parse::<i32>().unwrap())
+            .collect()
+    }
+
+    /// Sum of fuel requirements.
+    fn part_one(masses: &Vec<i32>) -> Answer {
+        masses.iter().map(|&mass| get_fuel(mass)).sum::<i32>().into()
+    }
+
+    /// Sum of fuel requirements, including the fuel's own.
+    fn part_two(masses: &Vec<i32>) -> Answer {
+        masses.iter().map(|&mass| get_fuel_recursive(mass)).sum::<i32>().into()
+    }
+}
+
+/// Use the formula provided to calculate the fuel needed for a given mass.
+/// Integer division truncates, i.e. rounds down.
+fn get_fuel(mass: i32) -> i32 {
+    (mass / 3) - 2
+}
+
+/// Calculate the fuel needed for a given mass plus its fuel (until no more fuel needed).
+/// As the function name suggests, recursion could be used. However, simple iteration works too.
+fn get_fuel_recursive(mass: i32) -> i32 {
+    let mut fuel = get_fuel(mass);
+
+    let mut sum = 0;
+    while fuel > 0 {
+        sum += fuel;
+        fuel = get_fuel(fuel);
+    }
+
+    sum
+}
+
+#[test]
+fn part_one_examples() {
+    let examples = [
+        (include_str!("../examples/1.txt"), 2),
+        (include_str!("../examples/2.txt"), 2),
+        (include_str!("../examples/3.txt"), 654),
+        (include_str!("../examples/4.txt"), 33583),
+    ];
+    for (input, fuel) in examples.iter() {
+        assert_eq!(*fuel, get_fuel(input.trim().parse().unwrap()));
+    }
+}
+
+#[test]
+fn part_two_examples() {
+    let examples = [
+        (include_str!("../examples/2.txt"), 2),
+        (include_str!("../examples/3.txt"), 966),
+        (include_str!("../examples/4.txt"), 50346),
+    ];
+    for (input, fuel) in examples.iter() {
+        assert_eq!(*fuel, get_fuel_recursive(input.trim().parse().unwrap()));
+    }
+}
\ No newline at end of file
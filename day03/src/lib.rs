@@ -0,0 +1,158 @@
+use common::*;
+use common::grid::{Direction, Point};
+
+use std::convert::TryFrom;
+
+type Line = (Point<i32>, Point<i32>);
+
+pub struct Day03;
+
+/// Day 3, for running alongside the other days.
+pub fn puzzle() -> Puzzle {
+    Puzzle::new::<Day03>(3, env!("CARGO_MANIFEST_DIR"))
+}
+
+impl Solution for Day03 {
+    /// The path of each wire.
+    type Input = Vec<Vec<(Line, i32)>>;
+
+    fn parse(input: &str) -> Self::Input {
+        input.lines()
+            .filter(|l| !l.is_empty())
+            .map(find_path)
+            .collect()
+    }
+
+    /// Distance from origin to closest intersection.
+    fn part_one(wires: &Self::Input) -> Answer {
+        let crosses = find_crosses(wires[0].clone(), wires[1].clone())
+            .iter()
+            .map(|(p, _)| *p)
+            .collect();
+
+        nearest_cross_dist(crosses).into()
+    }
+
+    /// Fewest steps to reach an intersection.
+    fn part_two(wires: &Self::Input) -> Answer {
+        let crosses = find_crosses(wires[0].clone(), wires[1].clone());
+
+        nearest_cross_steps(crosses).into()
+    }
+}
+
+/// Parse a `String` wire path into a `Vec` containing the start and end points
+/// of each straight section of the wire as well as the steps required to traverse
+/// the section.
+fn find_path<S: Into<String>>(input: S) -> Vec<(Line, i32)> {
+    let mut lines = Vec::new();
+
+    let mut last_point = Point::origin();
+    let mut total_distance = 0;
+    for next in input.into().split(',') {
+        let (direction, distance) = next.split_at(1);
+        let direction = direction.chars().next()
+            .and_then(|d| Direction::try_from(d).ok())
+            .expect("invalid direction");
+        let distance = distance.trim().parse::<i32>().unwrap();
+        let point = last_point + direction.offset() * distance;
+
+        total_distance += distance;
+        lines.push(((last_point, point), total_distance));
+        last_point = point;
+    }
+
+    lines
+}
+
+/// Find all the intersections from two vectors of lines.
+fn find_crosses(a: Vec<(Line, i32)>, b: Vec<(Line, i32)>) -> Vec<(Point<i32>, i32)> {
+    let mut intersections = Vec::new();
+
+    for &(a_line, a_steps) in &a {
+        for &(b_line, b_steps) in &b {
+
+            if let Some(point) = intersect(a_line, b_line) {
+                if point != Point::origin() {
+                    let a_dist = a_steps - point.manhattan(a_line.1);
+                    let b_dist = b_steps - point.manhattan(b_line.1);
+
+                    intersections.push((point, a_dist + b_dist));
+                }
+            }
+        }
+    }
+
+    intersections
+}
+
+/// Adapted from: https://stackoverflow.com/a/1968345
+/// Finds an intersection, if any, between two lines.
+fn intersect(s1: Line, s2: Line) -> Option<Point<i32>> {
+    let (p1, p2) = s1;
+    let (p3, p4) = s2;
+
+    let s1 = p2 - p1;
+    let s2 = p4 - p3;
+    let det = -s2.x * s1.y + s1.x * s2.y;
+
+    if det == 0 {
+        return None;
+    }
+
+    let s = (-s1.y * (p1.x - p3.x) + s1.x * (p1.y - p3.y)) as f32 / det as f32;
+    let t = (s2.x * (p1.y - p3.y) - s2.y * (p1.x - p3.x)) as f32 / det as f32;
+
+    if (0.0..=1.0).contains(&s) && (0.0..=1.0).contains(&t) {
+        return Some(Point::new(p1.x + (t * s1.x as f32) as i32, p1.y + (t * s1.y as f32) as i32));
+    }
+
+    None
+}
+
+/// Find the nearest intersection, based on distance alone.
+fn nearest_cross_dist(crosses: Vec<Point<i32>>) -> i32 {
+    crosses.iter()
+        .map(|p| p.manhattan(Point::origin()))
+        .min()
+        .unwrap()
+}
+
+/// Find the smallest number of steps to get to an intersection.
+fn nearest_cross_steps(crosses: Vec<(Point<i32>, i32)>) -> i32 {
+    crosses.iter()
+        .map(|(_, s)| *s)
+        .min()
+        .unwrap()
+}
+
+#[test]
+fn part_one_examples() {
+    let examples = [
+        (include_str!("../examples/1.txt"), 6),
+        (include_str!("../examples/2.txt"), 159),
+        (include_str!("../examples/3.txt"), 135),
+    ];
+    for (input, distance) in examples.iter() {
+        let wires = Day03::parse(input);
+        let crosses = find_crosses(wires[0].clone(), wires[1].clone())
+            .iter()
+            .map(|(p, _)| *p)
+            .collect();
+        assert_eq!(*distance, nearest_cross_dist(crosses));
+    }
+}
+
+#[test]
+fn part_two_examples() {
+    let examples = [
+        (include_str!("../examples/1.txt"), 30),
+        (include_str!("../examples/2.txt"), 610),
+        (include_str!("../examples/3.txt"), 410),
+    ];
+    for (input, steps) in examples.iter() {
+        let wires = Day03::parse(input);
+        let crosses = find_crosses(wires[0].clone(), wires[1].clone());
+        assert_eq!(*steps, nearest_cross_steps(crosses));
+    }
+}
@@ -0,0 +1,4 @@
+/// Checks the answers to the puzzle's examples, kept in `examples/`.
+fn main() {
+    common::run_examples(day03::puzzle());
+}
@@ -0,0 +1,110 @@
+//! More optimization could probably be done during iteration,
+//! like skipping numbers that are certainly not valid,
+//! but this runs reasonably quick as is (~0.75ms).
+
+use common::*;
+
+pub struct Day04;
+
+/// Day 4, for running alongside the other days.
+pub fn puzzle() -> Puzzle {
+    Puzzle::new::<Day04>(4, env!("CARGO_MANIFEST_DIR"))
+}
+
+impl Solution for Day04 {
+    /// The range of passwords, given as `a-b`.
+    type Input = (u32, u32);
+
+    fn parse(input: &str) -> (u32, u32) {
+        let (a, b) = input.trim().split_once('-').expect("range should be a-b");
+
+        (a.parse().unwrap(), b.parse().unwrap())
+    }
+
+    /// Number of possible passwords within the given range.
+    fn part_one(&(a, b): &(u32, u32)) -> Answer {
+        count_valid(a, b, None).into()
+    }
+
+    /// Same as part 1, with a group of exactly two repeated digits.
+    fn part_two(&(a, b): &(u32, u32)) -> Answer {
+        count_valid(a, b, Some(2)).into()
+    }
+}
+
+/// Count the number of valid passwords contained in the `a..=b` range.
+/// The `limit` ensures a group of repeated digits of `Some(..)` length
+/// exists that isn't also part of a larger group.
+fn count_valid(a: u32, b: u32, limit: Option<u8>) -> usize {
+    let mut valid = Vec::with_capacity(1000);
+
+    let end = b;
+    let mut num = a;
+    'outer: while num <= end {
+        let mut number = num;
+        let mut last_digit = 10;
+
+        // Ensure consequtive digits never decrease.
+        for i in 1..=6 {
+            let digit = number % 10;
+            if last_digit < digit {
+                num += 10u32.pow(i - 2);
+                continue 'outer;
+            }
+
+            last_digit = digit;
+            number /= 10;
+        }
+
+        // Reset digit iteration variables.
+        number = num;
+        last_digit = 10;
+
+        // Ensure at least two repeating digits.
+        let mut groups = [0u8; 10];
+        let limit = limit.unwrap_or(0);
+        for _ in 1..=6 {
+            let digit = number % 10;
+            if last_digit == digit {
+                if limit > 0 {
+                    groups[digit as usize] += 1;
+                } else {
+                    valid.push(num);
+                    break;
+                }
+            }
+
+            last_digit = digit;
+            number /= 10;
+        }
+
+        // Skipped when passed `limit == None`.
+        if limit > 0 && groups.contains(&(limit - 1)) {
+            valid.push(num);
+        }
+        
+        num += 1;
+    }
+
+    valid.len()
+}
+
+#[test]
+fn part_one_examples() {
+    assert_eq!(1, count_valid(111111, 111111, None));
+    assert_eq!(0, count_valid(223450, 223450, None));
+    assert_eq!(0, count_valid(123789, 123789, None));
+}
+
+#[test]
+fn part_two_examples() {
+    assert_eq!(1, count_valid(112233, 112233, Some(2)));
+    assert_eq!(0, count_valid(123444, 123444, Some(2)));
+    assert_eq!(1, count_valid(111122, 111122, Some(2)));
+}
+
+#[test]
+fn extra_tests() {
+    assert_eq!(1, count_valid(123444, 123444, Some(3)));
+    assert_eq!(0, count_valid(124444, 124444, Some(3)));
+}
\ No newline at end of file
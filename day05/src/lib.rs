@@ -0,0 +1,86 @@
+//! Most of the code from this day is within
+//! the `common` library's Intcode virtual machine.
+
+use common::*;
+
+pub struct Day05;
+
+/// Day 5, for running alongside the other days.
+pub fn puzzle() -> Puzzle {
+    Puzzle::new::<Day05>(5, env!("CARGO_MANIFEST_DIR"))
+}
+
+impl Solution for Day05 {
+    type Input = Vec<i64>;
+
+    fn parse(input: &str) -> Vec<i64> {
+        parse_program(input).expect("invalid Intcode program")
+    }
+
+    /// Final output of the diagnostic for the air conditioner unit (ID 1).
+    fn part_one(program: &Vec<i64>) -> Answer {
+        diagnose(program, 1).into()
+    }
+
+    /// Final output of the diagnostic for the thermal radiator controller (ID 5).
+    fn part_two(program: &Vec<i64>) -> Answer {
+        diagnose(program, 5).into()
+    }
+}
+
+/// Runs the diagnostic program for the system `id`, returning the final output.
+fn diagnose(program: &[i64], id: i64) -> i64 {
+    let mut vm = IntcodeVM::new()
+        .with_logging(0)
+        .with_program_slice(program);
+    let io_handle = vm.io();
+    io_handle.send(id);
+    vm.run();
+
+    *io_handle.dump().last().expect("diagnostic gave no output")
+}
+
+/// This is a bad way to do tests. They should really be split
+/// into many smaller tests if this were for anything serious.
+#[test]
+fn part_two_examples() {
+    // Less than and equal tests
+    let mut vm = IntcodeVM::new()
+        .with_program("3,9,8,9,10,9,4,9,99,-1,8");
+    let io_handle = vm.io();
+    io_handle.send(8);
+    vm.run();
+    assert_eq!(1, io_handle.recv().unwrap());
+
+    vm.load_program("3,9,7,9,10,9,4,9,99,-1,8");
+    io_handle.send(8);
+    vm.run();
+    assert_eq!(0, io_handle.recv().unwrap());
+    
+    vm.load_program("3,3,1108,-1,8,3,4,3,99");
+    io_handle.send(8);
+    vm.run();
+    assert_eq!(1, io_handle.recv().unwrap());
+    
+    vm.load_program("3,3,1107,-1,8,3,4,3,99");
+    io_handle.send(8);
+    vm.run();
+    assert_eq!(0, io_handle.recv().unwrap());
+    
+    // Jump tests
+    vm.load_program("3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9");
+    io_handle.send(0);
+    vm.run();
+    assert_eq!(0, io_handle.recv().unwrap());
+    
+    vm.load_program("3,3,1105,-1,9,1101,0,0,12,4,12,99,1");
+    io_handle.send(1);
+    vm.run();
+    assert_eq!(1, io_handle.recv().unwrap());
+    
+    // Combined
+    vm.load_program("3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99");
+    io_handle.send(3);
+    vm.run();
+    assert_eq!(999, io_handle.recv().unwrap());
+}
\ No newline at end of file
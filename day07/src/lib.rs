@@ -0,0 +1,191 @@
+use common::*;
+
+use std::cell::Cell;
+
+pub struct Day07;
+
+/// Day 7, for running alongside the other days.
+pub fn puzzle() -> Puzzle {
+    Puzzle::new::<Day07>(7, env!("CARGO_MANIFEST_DIR"))
+}
+
+impl Solution for Day07 {
+    /// The amplifier program, loaded fresh for every sequence.
+    type Input = String;
+
+    fn parse(input: &str) -> String {
+        input.to_string()
+    }
+
+    /// Maximum signal.
+    fn part_one(program: &String) -> Answer {
+        let mut max_signal = 0;
+        let mut amplifiers = create_amplifiers(5, program);
+
+        for p in permutations([0, 1, 2, 3, 4]) {
+            let signal = process_sequence(&mut amplifiers, &p);
+            max_signal = max_signal.max(signal); // Hah
+            reset_amplifiers(&mut amplifiers, program);
+        }
+
+        max_signal.into()
+    }
+
+    /// Maximum signal with the amplifiers in a feedback loop.
+    fn part_two(program: &String) -> Answer {
+        let mut max_signal = 0;
+        let mut amplifiers = create_amplifiers(5, program);
+
+        for p in permutations([5, 6, 7, 8, 9]) {
+            let signal = process_sequence_loop(&mut amplifiers, &p);
+            max_signal = max_signal.max(signal);
+            reset_amplifiers(&mut amplifiers, program);
+        }
+
+        max_signal.into()
+    }
+}
+
+/// Runs a sequence of integers (the `phase_settings`)
+/// through a number of amplifiers, returning the final signal.
+fn process_sequence(amplifiers: &mut [IntcodeVM], phase_settings: &[i64; 5]) -> i64 {
+    let mut output = 0;
+
+    for i in 0..5 {
+        let io_handle = amplifiers[i].io();
+
+        // First input
+        io_handle.send(phase_settings[i]);
+
+        // Second input
+        io_handle.send(output);
+
+        // Run the program until halting or it needs input.
+        while !matches!(amplifiers[i].step(), Some(IntcodeEvent::Halted) | Some(IntcodeEvent::NeedInput)) {}
+
+        // Store the last output.
+        output = io_handle.recv().unwrap();
+    }
+
+    output
+}
+
+/// Same as `process_sequence`, but wraps the amplifiers into a feedback loop,
+/// running them together until they have all halted and returning the
+/// final output of the last amplifier.
+fn process_sequence_loop(amplifiers: &mut [IntcodeVM], phase_settings: &[i64; 5]) -> i64 {
+    let last_signal = &Cell::new(0);
+    let mut scheduler = Scheduler::new();
+    let ids: Vec<VmId> = amplifiers.iter_mut()
+        .map(|vm| scheduler.add(vm))
+        .collect();
+
+    for (i, &id) in ids.iter().enumerate() {
+        scheduler.send(id, phase_settings[i]);
+        if i + 1 < ids.len() {
+            scheduler.connect(id, ids[i + 1]);
+        }
+    }
+
+    // Remember what the last amplifier sends back around to the first.
+    let first = ids[0];
+    scheduler.on_output(ids[4], move |signal, outbox| {
+        last_signal.set(signal);
+        outbox.send(first, signal);
+    });
+
+    scheduler.send(first, 0);
+    scheduler.run().expect("amplifiers stopped early");
+
+    last_signal.get()
+}
+
+/// Calculate permutations of numbers from the 5 given.
+/// Adapted from: https://rosettacode.org/wiki/Permutations#Rust
+fn permutations(set: [i64; 5]) -> Vec<[i64; 5]> {
+    let mut perms: Vec<[i64; 5]> = Vec::new();
+
+    let mut ints: [i64; 5] = set;
+    let mut swaps: [i64; 5] = [0; 5];
+    let mut current = 0;
+    'outer: loop {
+        if current > 0 {
+            'inner: loop {
+                if current >= swaps.len() {
+                    break 'outer;
+                }
+                if swaps[current] < current as i64 {
+                    break 'inner;
+                }
+
+                swaps[current] = 0;
+                current += 1;
+            }
+            
+            ints.swap(current, (current & 1) * swaps[current] as usize);
+            swaps[current] += 1;
+        }
+        
+        current = 1;
+
+        let perm = [ints[0], ints[1], ints[2], ints[3], ints[4]];
+        if let Err(i) = perms.binary_search(&perm) {
+            perms.insert(i, perm);
+        }
+    }
+    
+    perms
+}
+
+/// Creates and returns a number of `IntcodeVM`s.
+fn create_amplifiers(amount: usize, program: &str) -> Vec<IntcodeVM> {
+    let mut amplifiers = Vec::with_capacity(amount);
+    for _ in 0..5 {
+        let vm = IntcodeVM::new()
+            .with_logging(0)
+            .with_program(program);
+        amplifiers.push(vm);
+    }
+
+    amplifiers
+}
+
+/// Resets a number of `IntcodeVM`s to a given program.
+fn reset_amplifiers(amplifiers: &mut [IntcodeVM], program: &str) {
+    for vm in amplifiers {
+        vm.reset();
+        vm.load_program(program);
+    }
+}
+
+#[test]
+fn permutations_test() {
+    assert_eq!(120, permutations([0, 1, 2, 3, 4]).len());
+    assert_eq!(120, permutations([5, 6, 7, 8, 9]).len());
+}
+
+#[test]
+fn part_one_examples() {
+    let mut program = include_str!("../examples/1.txt");
+    let mut amplifiers = create_amplifiers(5, program);
+    assert_eq!(43210, process_sequence(&mut amplifiers, &[4,3,2,1,0]));
+    
+    program = include_str!("../examples/2.txt");
+    reset_amplifiers(&mut amplifiers, program);
+    assert_eq!(54321, process_sequence(&mut amplifiers, &[0,1,2,3,4]));
+
+    program = include_str!("../examples/3.txt");
+    reset_amplifiers(&mut amplifiers, program);
+    assert_eq!(65210, process_sequence(&mut amplifiers, &[1,0,4,3,2]));
+}
+
+#[test]
+fn part_two_examples() {
+    let mut program = include_str!("../examples/4.txt");
+    let mut amplifiers = create_amplifiers(5, program);
+    assert_eq!(139629729, process_sequence_loop(&mut amplifiers, &[9,8,7,6,5]));
+
+    program = include_str!("../examples/5.txt");
+    reset_amplifiers(&mut amplifiers, program);
+    assert_eq!(18216, process_sequence_loop(&mut amplifiers, &[9,7,8,5,6]));
+}
\ No newline at end of file
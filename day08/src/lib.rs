@@ -0,0 +1,160 @@
+use common::*;
+
+const WIDTH: usize = 25;
+const HEIGHT: usize = 6;
+
+pub struct Day08;
+
+/// Day 8, for running alongside the other days.
+pub fn puzzle() -> Puzzle {
+    Puzzle::new::<Day08>(8, env!("CARGO_MANIFEST_DIR"))
+}
+
+/// The image's layers, each `width * height` pixels.
+pub struct Image {
+    layers: Vec<Vec<u32>>,
+    width: usize,
+    height: usize,
+}
+
+impl Solution for Day08 {
+    type Input = Image;
+
+    fn parse(input: &str) -> Image {
+        Day08::parse_with(input, &Params::default()).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// The image is `width` by `height` pixels, 25 by 6 unless given,
+    /// and the input must hold a whole number of layers that size.
+    fn parse_with(input: &str, params: &Params) -> Result<Image, InputError> {
+        let width: usize = params.get_or("width", WIDTH)?;
+        let height: usize = params.get_or("height", HEIGHT)?;
+
+        if let Some(c) = input.trim().chars().find(|c| !c.is_ascii_digit()) {
+            return Err(InputError::Invalid(format!("{:?} is not a pixel", c)));
+        }
+
+        let pixels = input.trim().chars().count();
+        let whole = width.checked_mul(height)
+            .is_some_and(|size| size > 0 && pixels > 0 && pixels.is_multiple_of(size));
+        if !whole {
+            return Err(InputError::Invalid(
+                format!("{} pixels don't make whole layers of {} by {}", pixels, width, height)));
+        }
+
+        Ok(Image { layers: create_layers(input, width, height), width, height })
+    }
+
+    /// Number of 1 digits * number of 2 digits, in the layer with the fewest 0 digits.
+    fn part_one(image: &Image) -> Answer {
+        // Uses iterators, but filters and counts each layer twice.
+        let layer: (usize, usize) = image.layers.iter()
+            .map(|l| {
+                (l.iter().filter(|n| **n == 0).count(),
+                 l.iter().filter(|n| **n == 1).count())
+            })
+            .min_by_key(|k| k.0).unwrap();
+        let ones = layer.1;
+        let twos = image.width * image.height - ones - layer.0;
+
+        (ones * twos).into()
+    }
+
+    /// The letters in the decoded image.
+    fn part_two(image: &Image) -> Answer {
+        let pixels = flatten_layers(&image.layers, image.width, image.height);
+
+        draw_image(&pixels, image.width, image.height).read_letters()
+    }
+}
+
+/// Turns `data` into a `Vec` of `Vec<u32>`, or a `Vec` of flattened layers.
+fn create_layers(data: &str, width: usize, height: usize) -> Vec<Vec<u32>> {
+    let pixels: Vec<u32> = data.trim().chars()
+        .map(|c| c.to_digit(10).unwrap())
+        .collect();
+    let mut layers = Vec::with_capacity(pixels.len() / (width * height));
+    let layer_length = width * height;
+    let mut pixel = 0;
+    while pixel < pixels.len() {
+        let mut layer = Vec::with_capacity(width * height);
+
+        for _ in 0..layer_length {
+            layer.push(pixels[pixel]);
+            pixel += 1;
+        }
+
+        layers.push(layer);
+    }
+
+    layers
+}
+
+/// Flatten all the layers into one final image.
+fn flatten_layers(layers: &[Vec<u32>], width: usize, height: usize) -> Vec<u32> {
+    let mut image: Vec<u32> = vec![2; width * height];
+
+    for layer in layers {
+        for i in 0..(width * height) {
+            if image[i] == 2 {
+                image[i] = layer[i];
+            }
+        }
+    }
+
+    image
+}
+
+/// Turn a given `Vec` into an image, ensuring proper dimensions.
+/// Only white (1) pixels are lit.
+fn draw_image(image: &[u32], width: usize, height: usize) -> Answer {
+    let rows = (0..height)
+        .map(|y| (0..width).map(|x| image[width * y + x] == 1).collect())
+        .collect();
+
+    Answer::Image(rows)
+}
+
+#[test]
+fn part_one_examples() {
+    let layers = create_layers("123456789012", 3, 2);
+    assert_eq!(vec![1, 2, 3, 4, 5, 6], layers[0]);
+    assert_eq!(vec![7, 8, 9, 0, 1, 2], layers[1]);
+}
+
+#[test]
+fn part_two_examples() {
+    let layers = create_layers(include_str!("../examples/1.txt"), 2, 2);
+    assert_eq!(vec![0, 2, 2, 2], layers[0]);
+    assert_eq!(vec![1, 1, 2, 2], layers[1]);
+    assert_eq!(vec![2, 2, 1, 2], layers[2]);
+    assert_eq!(vec![0, 0, 0, 0], layers[3]);
+    assert_eq!(vec![0, 1, 1, 0], flatten_layers(&layers, 2, 2));
+}
+
+#[test]
+fn parameters() {
+    let input = include_str!("../examples/1.txt");
+    let mut params = Params::default();
+    params.set("width", "2");
+    params.set("height", "2");
+    let image = Day08::parse_with(input, &params).unwrap();
+    assert_eq!(4, image.layers.len());
+    assert_eq!(Answer::Image(vec![vec![false, true], vec![true, false]]), Day08::part_two(&image));
+
+    // Sizes that don't fit the input, or can't be read, are rejected.
+    params.set("width", "3");
+    assert!(matches!(Day08::parse_with(input, &params), Err(InputError::Invalid(_))));
+    params.set("width", "0");
+    assert!(matches!(Day08::parse_with(input, &params), Err(InputError::Invalid(_))));
+    params.set("width", "x");
+    assert!(matches!(Day08::parse_with(input, &params), Err(InputError::Param(_))));
+    params.set("width", "4294967296");
+    params.set("height", "4294967296");
+    assert!(matches!(Day08::parse_with(input, &params), Err(InputError::Invalid(_))));
+
+    // So are pixels that aren't digits.
+    params.set("width", "3");
+    params.set("height", "2");
+    assert!(matches!(Day08::parse_with("12x456", &params), Err(InputError::Invalid(_))));
+}
@@ -0,0 +1,76 @@
+use common::*;
+
+pub struct Day09;
+
+/// Day 9, for running alongside the other days.
+pub fn puzzle() -> Puzzle {
+    Puzzle::new::<Day09>(9, env!("CARGO_MANIFEST_DIR"))
+}
+
+impl Solution for Day09 {
+    type Input = Vec<i64>;
+
+    fn parse(input: &str) -> Vec<i64> {
+        parse_program(input).expect("invalid Intcode program")
+    }
+
+    /// BOOST keycode, from test mode.
+    fn part_one(program: &Vec<i64>) -> Answer {
+        run_boost(program, 1).into()
+    }
+
+    /// Distress signal coordinates, from sensor boost mode.
+    fn part_two(program: &Vec<i64>) -> Answer {
+        run_boost(program, 2).into()
+    }
+}
+
+/// Runs the BOOST program in the given `mode`, returning its only output.
+fn run_boost(program: &[i64], mode: i64) -> i64 {
+    let mut vm = IntcodeVM::new()
+        .with_logging(1)
+        .with_program_slice(program);
+    let io_handle = vm.io();
+
+    io_handle.send(mode);
+    vm.run();
+
+    assert_eq!(1, io_handle.count_output());
+
+    io_handle.recv().unwrap()
+}
+
+#[test]
+fn part_one_examples() {
+    let mut vm = IntcodeVM::new()
+        .with_logging(2)
+        .with_program("109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99");
+    let io_handle = vm.io();
+    vm.run();
+    assert_eq!([109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99], &io_handle.dump()[..]);
+
+    vm.load_program("1102,34915192,34915192,7,4,7,99,0");
+    vm.run();
+    assert_eq!(16, count_digits(io_handle.recv().unwrap()));
+
+    vm.load_program("104,1125899906842624,99");
+    vm.run();
+    assert_eq!(1125899906842624, io_handle.recv().unwrap());
+}
+
+#[test]
+fn part_two_examples() {
+    // TODO
+}
+
+/// Count the number of digits in a number.
+#[cfg(test)]
+fn count_digits(n: i64) -> usize {
+    let mut n = n.abs();
+    let mut digits = 0;
+    while n > 0 {
+        digits += 1;
+        n /= 10;
+    }
+    digits
+}
\ No newline at end of file
@@ -0,0 +1,43 @@
+use common::*;
+
+pub struct Day11;
+
+/// Day 11, for running alongside the other days.
+pub fn puzzle() -> Puzzle {
+    Puzzle::new::<Day11>(11, env!("CARGO_MANIFEST_DIR"))
+}
+
+impl Solution for Day11 {
+    type Input = Vec<i64>;
+
+    fn parse(input: &str) -> Vec<i64> {
+        parse_program(input).expect("invalid Intcode program")
+    }
+
+    /// Number of painted panels.
+    fn part_one(program: &Vec<i64>) -> Answer {
+        paint_panels(program, 0).panels().len().into()
+    }
+
+    /// The registration identifier, painted starting on a white panel.
+    fn part_two(program: &Vec<i64>) -> Answer {
+        let robot = paint_panels(program, 1);
+        let image = robot.render(|color| if color == 0 { ' ' } else { '#' });
+
+        Answer::image_from_text(&image, '#').read_letters()
+    }
+}
+
+fn paint_panels(program: &[i64], start_color: i64) -> Turtle {
+    let mut vm = IntcodeVM::new()
+        .with_logging(1)
+        .with_program_slice(program);
+
+    let mut robot = Turtle::new(start_color);
+    Bus::new(&mut vm)
+        .attach(&mut robot)
+        .run()
+        .expect("robot program failed");
+
+    robot
+}
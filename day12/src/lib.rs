@@ -0,0 +1,188 @@
+use common::*;
+
+type Moon = ([i64; 3], [i64; 3]);
+
+const STEPS: usize = 1000;
+
+pub struct Day12;
+
+/// Day 12, for running alongside the other days.
+pub fn puzzle() -> Puzzle {
+    Puzzle::new::<Day12>(12, env!("CARGO_MANIFEST_DIR"))
+}
+
+/// The moons, and how many steps to simulate before measuring their energy.
+pub struct System {
+    moons: Vec<Moon>,
+    steps: usize,
+}
+
+impl Solution for Day12 {
+    type Input = System;
+
+    fn parse(input: &str) -> System {
+        Day12::parse_with(input, &Params::default()).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Energy is measured after `steps` steps, 1000 unless given.
+    fn parse_with(input: &str, params: &Params) -> Result<System, InputError> {
+        Ok(System { moons: parse_moons(input), steps: params.get_or("steps", STEPS)? })
+    }
+
+    /// Total energy in the system after the given number of steps.
+    fn part_one(system: &System) -> Answer {
+        total_energy(&system.moons, system.steps).into()
+    }
+
+    /// Necessary steps to reach a cycle.
+    fn part_two(system: &System) -> Answer {
+        find_cycle(&mut system.moons.clone()).into()
+    }
+}
+
+fn parse_moons(input: &str) -> Vec<Moon> {
+    let mut moons = Vec::with_capacity(4);
+
+    for line in input.lines() {
+        let pos: Vec<i64> = line.split(',')
+            .map(|v| v.parse::<i64>().unwrap())
+            .collect();
+
+        let moon_pos = [pos[0], pos[1], pos[2]];
+        moons.push((moon_pos, [0; 3]));
+    }
+
+    moons
+}
+
+/// Perform a time step over a given dimension.
+fn step(moons: &mut [Moon], d: usize) {
+    let mut new_moons = [([0; 3], [0; 3]); 4];
+    for m1 in 0..4 {
+        let mut m1_pos = moons[m1].0;
+        let mut m1_vel = moons[m1].1;
+
+        for (m2, (m2_pos, _)) in moons.iter().enumerate() {
+            if m1 == m2 {
+                continue;
+            }
+
+            // Gravity
+            m1_vel[d] += (m2_pos[d] - m1_pos[d]).signum();
+        }
+        
+        // Velocity
+        m1_pos[d] += m1_vel[d];
+
+        // Update
+        new_moons[m1] = (m1_pos, m1_vel);
+    }
+
+    moons.swap_with_slice(&mut new_moons);
+}
+
+fn total_energy(moons: &[Moon], steps: usize) -> i64 {
+    let mut moons = moons.to_vec();
+
+    let mut step_energy = 0;
+    for _step in 0..steps {
+        step(&mut moons, 0);
+        step(&mut moons, 1);
+        step(&mut moons, 2);
+
+        // println!("After {} steps:", _step + 1);
+
+        step_energy = 0;
+        for (pos, vel) in &moons {
+            let pot: i64 = pos.iter().map(|d| d.abs()).sum();
+            let kin: i64 = vel.iter().map(|d| d.abs()).sum();
+
+            // println!("pos=<x={:>3}, y={:>3}, z={:>3}>, vel=<x={:>3}, y={:>3}, z={:>3}>",
+            //     pos[0], pos[1], pos[2], vel[0], vel[1], vel[2]);
+
+            step_energy += pot * kin;
+        }
+
+        // println!("Total energy in the system: {}", step_energy);
+
+        // println!();
+    }
+
+    step_energy
+}
+
+/// Converts 4 moons into an array that makes it easier to compare
+/// state on a per dimension basis.
+fn get_state(moons: &[Moon], d: usize) -> [i64; 8] {
+    [
+        moons[0].0[d], moons[0].1[d], moons[1].0[d], moons[1].1[d],
+        moons[2].0[d], moons[2].1[d], moons[3].0[d], moons[3].1[d],
+    ]
+}
+
+/// Calculate the Greatest Common Divisor of two given numbers using Euclid's Algorithm.
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Calculate the Least Common Multiple of two numbers using the Greatest Common Divisor.
+fn lcm(a: i64, b: i64) -> i64 {
+    (a * b) / gcd(a, b)
+}
+
+fn find_cycle(moons: &mut [Moon]) -> i64 {
+    let initial_state = [get_state(moons, 0), get_state(moons, 1), get_state(moons, 2)];
+    let mut cycles = [0; 3];
+    for steps in 1.. {
+        if cycles[0] != 0 && cycles[1] != 0 && cycles[2] != 0 {
+            break;
+        }
+
+        for d in 0..3 {
+            step(moons, d);
+
+            if cycles[d] == 0 && get_state(moons, d) == initial_state[d] {
+                cycles[d] = steps;
+            }
+        }
+    }
+
+    lcm(cycles[0], lcm(cycles[1], cycles[2]))
+}
+
+#[test]
+fn part_one_examples() {
+    let examples = [
+        (include_str!("../examples/1.txt"), 10, 179),
+        (include_str!("../examples/2.txt"), 100, 1940),
+    ];
+    for (input, steps, energy) in examples.iter() {
+        assert_eq!(*energy, total_energy(&parse_moons(input), *steps));
+    }
+}
+
+#[test]
+fn part_two_examples() {
+    let examples = [
+        (include_str!("../examples/1.txt"), 2772),
+        (include_str!("../examples/2.txt"), 4686774924),
+    ];
+    for (input, cycle) in examples.iter() {
+        assert_eq!(*cycle, find_cycle(&mut parse_moons(input)));
+    }
+}
+
+#[test]
+fn parameters() {
+    let mut params = Params::default();
+    params.set("steps", "10");
+    let system = Day12::parse_with(include_str!("../examples/1.txt"), &params).unwrap();
+    assert_eq!(Answer::from(179), Day12::part_one(&system));
+
+    params.set("steps", "-1");
+    assert!(matches!(Day12::parse_with("", &params), Err(InputError::Param(_))));
+}
\ No newline at end of file
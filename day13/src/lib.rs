@@ -0,0 +1,71 @@
+use common::*;
+
+pub struct Day13;
+
+/// Day 13, for running alongside the other days.
+pub fn puzzle() -> Puzzle {
+    Puzzle::new::<Day13>(13, env!("CARGO_MANIFEST_DIR"))
+}
+
+impl Solution for Day13 {
+    type Input = Vec<i64>;
+
+    fn parse(input: &str) -> Vec<i64> {
+        parse_program(input).expect("invalid Intcode program")
+    }
+
+    /// Total blocks on the screen when the game exits.
+    fn part_one(program: &Vec<i64>) -> Answer {
+        let mut vm = IntcodeVM::new()
+            .with_logging(1)
+            .with_program_slice(program);
+
+        let mut screen = Screen::new();
+        Bus::new(&mut vm)
+            .attach(&mut screen)
+            .run()
+            .expect("game program failed");
+
+        screen.count(2).into()
+    }
+
+    /// Score after the last block is broken, playing for free.
+    fn part_two(program: &Vec<i64>) -> Answer {
+        let mut vm = IntcodeVM::new()
+            .with_logging(1)
+            .with_program_slice(program);
+        vm.patch_memory(0, &[2]);
+
+        let mut arcade = Arcade::default();
+        Bus::new(&mut vm)
+            .attach(&mut arcade)
+            .run()
+            .expect("game program failed");
+
+        arcade.screen.score().unwrap_or(0).into()
+    }
+}
+
+/// The arcade cabinet: a screen, plus a joystick that
+/// keeps the paddle under the ball.
+#[derive(Default)]
+struct Arcade {
+    screen: Screen,
+}
+
+impl Device for Arcade {
+    fn arity(&self) -> usize {
+        self.screen.arity()
+    }
+
+    fn receive(&mut self, values: &[i64]) {
+        self.screen.receive(values);
+    }
+
+    fn provide(&mut self) -> Option<i64> {
+        let ball = self.screen.find(4)?;
+        let paddle = self.screen.find(3)?;
+
+        Some((ball.x - paddle.x).signum())
+    }
+}
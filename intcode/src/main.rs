@@ -0,0 +1,435 @@
+//! Runs any Intcode program from the command line.
+//! See `intcode --help` for the options.
+
+use common::*;
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::io::{self, BufRead, Read, Write};
+use std::process;
+
+const USAGE: &str = "\
+Usage: intcode [OPTIONS] <PROGRAM>
+
+Runs an Intcode program, read from the file PROGRAM or from stdin if it is `-`.
+When the program needs more input than was given, lines are read from stdin.
+
+Options:
+  -i, --input <VALUES>    Queue input: comma-separated numbers, or text with --ascii
+      --input-file <PATH> Queue input read from a file, as with --input
+      --no-stdin          Fail instead of reading input from stdin
+  -a, --ascii             Input is text, and output is shown as text
+  -p, --patch <ADDR=V,..> Write values into memory before running
+  -n, --max-steps <N>     Stop with an error after N instructions
+  -f, --format <FORMAT>   Output as `numbers` (one per line), `text` or `json`
+      --trace             Print each instruction to stderr as it runs
+      --profile           Print instruction counts to stderr when done
+  -h, --help              Show this message";
+
+/// How outputs are printed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Numbers,
+    Text,
+    Json,
+}
+
+/// Where queued input comes from.
+#[derive(Debug, Clone, PartialEq)]
+enum InputArg {
+    Values(String),
+    File(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Options {
+    program: String,
+    inputs: Vec<InputArg>,
+    stdin: bool,
+    ascii: bool,
+    patches: Vec<(usize, Vec<i64>)>,
+    max_steps: Option<usize>,
+    format: Format,
+    trace: bool,
+    profile: bool,
+}
+
+impl Options {
+
+    /// Parse the arguments following the binary's name.
+    /// `Ok(None)` means help was asked for.
+    fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Option<Options>, String> {
+        let mut program = None;
+        let mut inputs = Vec::new();
+        let mut stdin = true;
+        let mut ascii = false;
+        let mut patches = Vec::new();
+        let mut max_steps = None;
+        let mut format = None;
+        let mut trace = false;
+        let mut profile = false;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| args.next().ok_or_else(|| format!("{} needs a value", name));
+
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "-i" | "--input" => inputs.push(InputArg::Values(value(&arg)?)),
+                "--input-file" => inputs.push(InputArg::File(value(&arg)?)),
+                "--no-stdin" => stdin = false,
+                "-a" | "--ascii" => ascii = true,
+                "-p" | "--patch" => patches.push(parse_patch(&value(&arg)?)?),
+                "-n" | "--max-steps" => {
+                    let n = value(&arg)?;
+                    max_steps = Some(n.parse().map_err(|_| format!("invalid step limit {:?}", n))?);
+                },
+                "-f" | "--format" => {
+                    format = Some(match value(&arg)?.as_str() {
+                        "numbers" => Format::Numbers,
+                        "text" => Format::Text,
+                        "json" => Format::Json,
+                        f => return Err(format!("unknown format {:?}", f)),
+                    });
+                },
+                "--trace" => trace = true,
+                "--profile" => profile = true,
+                a if a.starts_with('-') && a != "-" => return Err(format!("unknown option {}", a)),
+                _ if program.is_some() => return Err(format!("unexpected argument {:?}", arg)),
+                _ => program = Some(arg),
+            }
+        }
+
+        let program = program.ok_or("no program given")?;
+        let format = format.unwrap_or(if ascii { Format::Text } else { Format::Numbers });
+
+        Ok(Some(Options {
+            stdin: stdin && program != "-",
+            program,
+            inputs,
+            ascii,
+            patches,
+            max_steps,
+            format,
+            trace,
+            profile,
+        }))
+    }
+}
+
+/// Parses `ADDR=V,V,...`.
+fn parse_patch(patch: &str) -> Result<(usize, Vec<i64>), String> {
+    let invalid = || format!("invalid patch {:?}, expected ADDR=VALUE,...", patch);
+
+    let (addr, values) = patch.split_once('=').ok_or_else(invalid)?;
+    let addr = addr.trim().parse().map_err(|_| invalid())?;
+    let values = parse_values(values).map_err(|_| invalid())?;
+
+    Ok((addr, values))
+}
+
+/// Parses numbers separated by commas or whitespace.
+fn parse_values(text: &str) -> Result<Vec<i64>, String> {
+    text.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|v| !v.is_empty())
+        .map(|v| v.parse().map_err(|_| format!("invalid input value {:?}", v)))
+        .collect()
+}
+
+/// Turns text into input, one character per value.
+/// Each line given on the command line or stdin ends in a newline.
+fn text_values(text: &str) -> Vec<i64> {
+    text.bytes().map(i64::from).collect()
+}
+
+/// What happened during a run.
+#[derive(Debug, Clone, PartialEq)]
+struct Report {
+    outputs: Vec<i64>,
+    steps: usize,
+}
+
+/// Runs `program` with the given options, reading extra input from
+/// `stdin` and printing output and diagnostics to `out` and `err`.
+fn run(
+    opts: &Options,
+    program: &[i64],
+    stdin: &mut dyn BufRead,
+    out: &mut dyn Write,
+    err: &mut dyn Write,
+) -> Result<Report, String> {
+    let mut vm = IntcodeVM::new().with_program_slice(program);
+    for (addr, values) in &opts.patches {
+        if addr + values.len() > vm.memory_size() {
+            return Err(format!("patch at {} doesn't fit in memory", addr));
+        }
+        vm.patch_memory(*addr, values);
+    }
+
+    let io_handle = vm.io();
+    for input in &opts.inputs {
+        let text = match input {
+            InputArg::Values(v) if opts.ascii => format!("{}\n", v),
+            InputArg::Values(v) => v.clone(),
+            InputArg::File(path) => std::fs::read_to_string(path)
+                .map_err(|e| format!("unable to read {}: {}", path, e))?,
+        };
+
+        let values = if opts.ascii { text_values(&text) } else { parse_values(&text)? };
+        for v in values {
+            io_handle.send(v);
+        }
+    }
+
+    let mut report = Report {
+        outputs: Vec::new(),
+        steps: 0,
+    };
+    let mut profile: BTreeMap<usize, usize> = BTreeMap::new();
+    let io_err = |e: io::Error| e.to_string();
+
+    loop {
+        let ip = vm.instr_pointer();
+        if ip >= vm.memory_size() {
+            return Err(format!("instruction pointer {} out of memory", ip));
+        }
+        let instr = Instruction::decode(&vm.dump_memory(ip..(ip + 4).min(vm.memory_size())), 0)
+            .ok_or_else(|| format!("invalid instruction {} at {}", vm.dump_memory(ip..ip + 1)[0], ip))?;
+        for param in &instr.params {
+            let addr = match *param {
+                Param::Position(a) => a,
+                Param::Relative(offset) => vm.relative_base().saturating_add(offset),
+                Param::Immediate(_) => continue,
+            };
+            if addr < 0 || addr >= vm.memory_size() as i64 {
+                return Err(format!("address {} out of memory at {}", addr, ip));
+            }
+        }
+
+        if opts.max_steps.is_some_and(|max| report.steps >= max) {
+            return Err(format!("step limit of {} reached at {}", report.steps, ip));
+        }
+        if opts.trace {
+            writeln!(err, "[{:>6}] {:<32} rb={}", ip, instr.to_string(), vm.relative_base())
+                .map_err(io_err)?;
+        }
+
+        let msg = vm.step();
+        if msg != Some(IntcodeEvent::NeedInput) {
+            report.steps += 1;
+            if opts.profile {
+                *profile.entry(ip).or_default() += 1;
+            }
+        }
+
+        while let Some(v) = io_handle.recv() {
+            report.outputs.push(v);
+            match opts.format {
+                Format::Numbers => writeln!(out, "{}", v).map_err(io_err)?,
+                Format::Text => write_text(out, v).map_err(io_err)?,
+                Format::Json => {},
+            }
+        }
+
+        match msg {
+            Some(IntcodeEvent::Halted) => break,
+            Some(IntcodeEvent::Error(e)) => return Err(format!("machine error: {:?}", e)),
+            Some(IntcodeEvent::EndOfInput) => return Err(format!("program needs input at {}, but it is closed", ip)),
+            Some(IntcodeEvent::NeedInput) => {
+                if !opts.stdin {
+                    return Err(format!("program needs input at {}, but none is left", ip));
+                }
+
+                out.flush().map_err(io_err)?;
+                let mut line = String::new();
+                if stdin.read_line(&mut line).map_err(io_err)? == 0 {
+                    return Err(format!("program needs input at {}, but stdin has ended", ip));
+                }
+
+                let values = if opts.ascii { text_values(&line) } else { parse_values(&line)? };
+                for v in values {
+                    io_handle.send(v);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    if opts.format == Format::Json {
+        let outputs: Vec<String> = report.outputs.iter().map(|v| v.to_string()).collect();
+        writeln!(out, "{{\"outputs\": [{}], \"steps\": {}}}", outputs.join(", "), report.steps)
+            .map_err(io_err)?;
+    }
+    if opts.profile {
+        write_profile(err, &vm, &profile, report.steps).map_err(io_err)?;
+    }
+
+    Ok(report)
+}
+
+/// Prints a value as a character if it is one, otherwise as a number.
+fn write_text(out: &mut dyn Write, v: i64) -> io::Result<()> {
+    match u8::try_from(v) {
+        Ok(c) if c.is_ascii() => out.write_all(&[c]),
+        _ => writeln!(out, "{}", v),
+    }
+}
+
+/// Prints how often each kind of instruction ran,
+/// then the instructions that ran the most.
+fn write_profile(
+    err: &mut dyn Write,
+    vm: &IntcodeVM,
+    profile: &BTreeMap<usize, usize>,
+    steps: usize,
+) -> io::Result<()> {
+    let memory = vm.dump_memory(0..vm.memory_size());
+    let decode = |addr: usize| Instruction::decode(&memory, addr);
+
+    let mut by_opcode: BTreeMap<&str, usize> = BTreeMap::new();
+    for (&addr, &count) in profile {
+        let name = decode(addr).map_or("?", |i| i.opcode.mnemonic());
+        *by_opcode.entry(name).or_default() += count;
+    }
+
+    writeln!(err, "Steps: {}", steps)?;
+    let mut by_opcode: Vec<_> = by_opcode.into_iter().collect();
+    by_opcode.sort_by_key(|&(name, count)| (std::cmp::Reverse(count), name));
+    for (name, count) in by_opcode {
+        writeln!(err, "  {:<5} {:>10}", name, count)?;
+    }
+
+    writeln!(err, "Hottest instructions (as memory ended up):")?;
+    let mut hottest: Vec<_> = profile.iter().collect();
+    hottest.sort_by_key(|&(&addr, &count)| (std::cmp::Reverse(count), addr));
+    for (&addr, &count) in hottest.into_iter().take(10) {
+        let instr = decode(addr).map_or_else(|| "?".to_string(), |i| i.to_string());
+        writeln!(err, "  {:>6}: {:<32} {:>10}", addr, instr, count)?;
+    }
+
+    Ok(())
+}
+
+/// Reads the program from its path, or stdin, checking it fits in memory.
+fn load(path: &str) -> Result<Vec<i64>, String> {
+    let program = if path == "-" {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text).map_err(|e| format!("unable to read program: {}", e))?;
+        parse_program(&text).map_err(|e| format!("unable to parse program: {}", e))?
+    } else {
+        parse_program_file(path).map_err(|e| format!("{}: {}", path, e))?
+    };
+
+    if program.len() > IntcodeVM::new().memory_size() {
+        return Err(LoadError::TooLarge { len: program.len() }.to_string());
+    }
+
+    Ok(program)
+}
+
+fn main() {
+    let opts = match Options::parse(std::env::args().skip(1)) {
+        Ok(Some(opts)) => opts,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        },
+        Err(e) => {
+            eprintln!("intcode: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        },
+    };
+
+    let result = load(&opts.program).and_then(|program| {
+        let stdin = io::stdin();
+        let stdout = io::stdout();
+        run(&opts, &program, &mut stdin.lock(), &mut stdout.lock(), &mut io::stderr())
+    });
+
+    if let Err(e) = result {
+        eprintln!("intcode: {}", e);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+fn args(line: &str) -> Options {
+    Options::parse(line.split_whitespace().map(String::from)).unwrap().unwrap()
+}
+
+#[cfg(test)]
+fn run_capture(opts: &Options, program: &str, stdin: &str) -> (Result<Report, String>, String, String) {
+    let program = parse_program(program).unwrap();
+    let (mut out, mut err) = (Vec::new(), Vec::new());
+    let result = run(opts, &program, &mut stdin.as_bytes(), &mut out, &mut err);
+
+    (result, String::from_utf8(out).unwrap(), String::from_utf8(err).unwrap())
+}
+
+#[test]
+fn parses_options() {
+    let opts = args("prog.txt -i 1,2 --input-file in.txt -a -p 1=12,2 -n 100 --trace");
+    assert_eq!("prog.txt", opts.program);
+    assert_eq!(vec![InputArg::Values("1,2".into()), InputArg::File("in.txt".into())], opts.inputs);
+    assert_eq!(vec![(1, vec![12, 2])], opts.patches);
+    assert_eq!((Some(100), Format::Text, true, true), (opts.max_steps, opts.format, opts.ascii, opts.trace));
+
+    assert!(!args("-").stdin);
+    assert_eq!(Format::Json, args("p -a -f json").format);
+    assert_eq!(Ok(None), Options::parse(vec!["-h".to_string()]));
+    assert!(Options::parse(vec!["p".to_string(), "--bogus".to_string()]).is_err());
+    assert!(Options::parse(vec!["-p".to_string(), "1:2".to_string(), "p".to_string()]).is_err());
+    assert!(Options::parse(Vec::new()).is_err());
+}
+
+#[test]
+fn runs_programs() {
+    // Day 9's quine, printed as numbers or JSON.
+    let quine = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+    let (result, out, _) = run_capture(&args("p"), quine, "");
+    assert_eq!(quine.replace(',', "\n") + "\n", out);
+    assert_eq!(16, result.unwrap().outputs.len());
+
+    let (_, out, _) = run_capture(&args("p -f json"), "104,1,104,-2,99", "");
+    assert!(out.starts_with("{\"outputs\": [1, -2], \"steps\": 3}"));
+
+    // Day 2's first example, with a patch, then the day 5 comparison
+    // against 8, reading input from arguments and then stdin.
+    let (_, out, _) = run_capture(&args("p -p 11=10"), "1,11,12,3,2,3,13,0,4,0,99,30,40,50", "");
+    assert_eq!("2500\n", out);
+    let program = "3,9,8,9,10,9,4,9,99,-1,8";
+    assert_eq!("1\n", run_capture(&args("p -i 8"), program, "").1);
+    assert_eq!("0\n", run_capture(&args("p"), program, "7\n").1);
+    assert!(run_capture(&args("p"), program, "").0.is_err());
+    assert!(run_capture(&args("p --no-stdin"), program, "8\n").0.is_err());
+
+    // Echoing text.
+    let echo = "3,100,4,100,1008,100,10,101,1006,101,0,99";
+    assert_eq!("hi\n", run_capture(&args("p -a -i hi"), echo, "").1);
+
+    // A step limit stops a loop.
+    let (result, _, _) = run_capture(&args("p -n 50"), "1105,1,0", "");
+    assert!(result.unwrap_err().contains("step limit"));
+
+    // Jumping past the end of memory, or reading or writing outside it.
+    let (result, _, _) = run_capture(&args("p"), "1105,1,100000", "");
+    assert_eq!(Err("instruction pointer 100000 out of memory".to_string()), result.map(|_| ()));
+    let (result, _, _) = run_capture(&args("p"), "4,-1,99", "");
+    assert_eq!(Err("address -1 out of memory at 0".to_string()), result.map(|_| ()));
+    let (result, _, _) = run_capture(&args("p"), "109,8190,21101,1,1,2,99", "");
+    assert_eq!(Err("address 8192 out of memory at 2".to_string()), result.map(|_| ()));
+
+    let (_, _, err) = run_capture(&args("p --trace --profile"), "1101,1,2,5,99,0", "");
+    assert!(err.contains("ADD #1, #2, [5]"));
+    assert!(err.contains("Steps: 2"));
+}
+
+#[test]
+fn loads_programs() {
+    let dir = common::testing::ScratchDir::new("intcode-cli");
+    let path = dir.write("program.txt", "104,7,99\n");
+    assert_eq!(Ok(vec![104, 7, 99]), load(path.to_str().unwrap()));
+
+    let path = dir.write("large.txt", &vec!["0"; 8193].join(","));
+    assert_eq!(Err("program of 8193 values does not fit in memory".to_string()), load(path.to_str().unwrap()));
+}
\ No newline at end of file